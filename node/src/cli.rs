@@ -43,4 +43,7 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Print the encrypted fields of a profile, using a key from the local keystore.
+	DecryptProfile(crate::decrypt::DecryptProfileCmd),
 }
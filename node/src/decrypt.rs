@@ -0,0 +1,200 @@
+//! `decrypt-profile` subcommand: print the encrypted fields of a profile in plaintext.
+//!
+//! Every reader, owner or grantee, has its own x25519 key, derived from an ed25519 key of
+//! type `prfl` in its local keystore the way libsodium converts signing keys: the secret
+//! scalar is the first half of the SHA-512 hash of the ed25519 seed. Readers insert that key
+//! with `key insert --scheme ed25519 --key-type prfl` and register its x25519 public half with
+//! `set_encryption_key`. Owners seal a copy of each field to every grantee reading it, so no
+//! secret key ever leaves its reader's keystore.
+
+use crate::service::FullClient;
+use chacha20poly1305::{
+	aead::{Aead, KeyInit, Payload},
+	ChaCha20Poly1305, Key, Nonce,
+};
+use codec::{Decode, Encode};
+use profile::{
+	runtime_api::ProfileApi, BloodType, District, Gender, MaritalStatus, PictureRef, ProfileField,
+	Province,
+};
+use sc_cli::{CliConfiguration, KeystoreParams, SharedParams};
+use sc_keystore::LocalKeystore;
+use sha2::{Digest, Sha512};
+use solochain_template_runtime::AccountId;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, hashing::blake2_256, hexdisplay::HexDisplay};
+use sp_keystore::Keystore;
+use std::sync::Arc;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Key type of the ed25519 keys profile encryption keys are derived from.
+pub const PROFILE_KEY_TYPE: sp_core::crypto::KeyTypeId = sp_core::crypto::KeyTypeId(*b"prfl");
+
+mod app {
+	use sp_application_crypto::{app_crypto, ed25519};
+	app_crypto!(ed25519, super::PROFILE_KEY_TYPE);
+}
+
+/// Decrypt the encrypted fields of a profile with a key from the local keystore.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct DecryptProfileCmd {
+	/// SS58 address of the profile owner or one of its linked accounts.
+	#[arg(long)]
+	pub owner: String,
+
+	/// SS58 address of the account reading the profile, a grantee or the owner itself.
+	/// Defaults to `--owner`.
+	#[arg(long)]
+	pub reader: Option<String>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl DecryptProfileCmd {
+	/// Read the copies sealed to the reader at the best block and print one `field: value`
+	/// line per encrypted field.
+	pub(crate) fn run(&self, client: Arc<FullClient>, keystore: Arc<LocalKeystore>) -> sc_cli::Result<()> {
+		let owner = AccountId::from_ss58check(&self.owner)
+			.map_err(|e| format!("invalid owner address: {e:?}"))?;
+		let reader = match &self.reader {
+			Some(reader) => AccountId::from_ss58check(reader)
+				.map_err(|e| format!("invalid reader address: {e:?}"))?,
+			None => owner.clone(),
+		};
+		let (public, fields) = client
+			.runtime_api()
+			.get_encrypted_fields(client.info().best_hash, owner, reader)
+			.map_err(|e| format!("runtime api: {e}"))?
+			.ok_or("the reader has not registered an encryption key")?;
+
+		let secret = keystore
+			.ed25519_public_keys(PROFILE_KEY_TYPE)
+			.into_iter()
+			.find_map(|key| {
+				let pair = keystore.key_pair::<app::Pair>(&key.into()).ok()??;
+				let secret = x25519_secret(&pair.into_inner().seed());
+				(PublicKey::from(&secret).to_bytes() == public).then_some(secret)
+			})
+			.ok_or("no `prfl` key in the keystore matches the reader's encryption key")?;
+
+		for (field, sealed) in fields {
+			let value = open(&secret, &PublicKey::from(public), field, &sealed)
+				.and_then(|plaintext| render(field, &plaintext));
+			match value {
+				Ok(value) => println!("{field:?}: {value}"),
+				Err(e) => println!("{field:?}: <{e}>"),
+			}
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for DecryptProfileCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
+
+/// The x25519 secret of an ed25519 seed.
+fn x25519_secret(seed: &[u8; 32]) -> StaticSecret {
+	let mut scalar = [0u8; 32];
+	scalar.copy_from_slice(&Sha512::digest(seed)[..32]);
+	StaticSecret::from(scalar)
+}
+
+/// Open a blob sealed as described at `profile::EncryptedFields`.
+fn open(
+	secret: &StaticSecret,
+	recipient: &PublicKey,
+	field: ProfileField,
+	sealed: &[u8],
+) -> Result<Vec<u8>, String> {
+	let (ephemeral, body) = sealed.split_at_checked(32).ok_or("ciphertext too short")?;
+	let ephemeral: [u8; 32] = ephemeral.try_into().expect("split at 32 bytes; qed");
+	let shared = secret.diffie_hellman(&PublicKey::from(ephemeral));
+	let key = blake2_256(&[&shared.as_bytes()[..], &ephemeral, recipient.as_bytes()].concat());
+
+	ChaCha20Poly1305::new(Key::from_slice(&key))
+		.decrypt(&Nonce::default(), Payload { msg: body, aad: &field.encode() })
+		.map_err(|_| "cannot decrypt, wrong key or corrupted ciphertext".into())
+}
+
+/// Decode the SCALE-encoded value of `field`.
+fn render(field: ProfileField, mut plaintext: &[u8]) -> Result<String, String> {
+	let input = &mut plaintext;
+	let value = match field {
+		ProfileField::MaritalStatus => MaritalStatus::decode(input).map(|v| format!("{v:?}")),
+		ProfileField::Gender => Gender::decode(input).map(|v| format!("{v:?}")),
+		ProfileField::BloodGroup => BloodType::decode(input).map(|v| format!("{v:?}")),
+		ProfileField::Province => Province::decode(input).map(|v| format!("{v:?}")),
+		ProfileField::District => District::decode(input).map(|v| format!("{v:?}")),
+		ProfileField::PostalCode => u32::decode(input).map(|v| v.to_string()),
+		ProfileField::BirthDate => user::Date::decode(input).map(|v| v.to_string()),
+		ProfileField::Picture => PictureRef::decode(input).map(|v| {
+			format!("{} {:?} 0x{} ({} bytes)", v.mime.as_str(), v.algorithm, HexDisplay::from(&v.digest), v.size)
+		}),
+		_ => Vec::<u8>::decode(input).map(|v| String::from_utf8_lossy(&v).into_owned()),
+	};
+	value.map_err(|e| format!("cannot decode plaintext: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Seal `plaintext` to `recipient` the way clients do, see `profile::EncryptedFields`.
+	fn seal(ephemeral: [u8; 32], recipient: &PublicKey, field: ProfileField, plaintext: &[u8]) -> Vec<u8> {
+		let ephemeral = StaticSecret::from(ephemeral);
+		let ephemeral_public = PublicKey::from(&ephemeral).to_bytes();
+		let shared = ephemeral.diffie_hellman(recipient);
+		let key = blake2_256(&[&shared.as_bytes()[..], &ephemeral_public, recipient.as_bytes()].concat());
+		let body = ChaCha20Poly1305::new(Key::from_slice(&key))
+			.encrypt(&Nonce::default(), Payload { msg: plaintext, aad: &field.encode() })
+			.unwrap();
+		[&ephemeral_public[..], &body].concat()
+	}
+
+	#[test]
+	fn sealed_fields_open_for_their_reader() {
+		let secret = x25519_secret(&[1; 32]);
+		let public = PublicKey::from(&secret);
+		let religion = b"Buddhism".to_vec().encode();
+
+		let sealed = seal([7; 32], &public, ProfileField::Religion, &religion);
+		assert_eq!(sealed.len() as u32, religion.len() as u32 + profile::CIPHERTEXT_OVERHEAD);
+		let plaintext = open(&secret, &public, ProfileField::Religion, &sealed).unwrap();
+		assert_eq!(render(ProfileField::Religion, &plaintext).unwrap(), "Buddhism");
+
+		let sealed = seal([8; 32], &public, ProfileField::BloodGroup, &BloodType::APos.encode());
+		let plaintext = open(&secret, &public, ProfileField::BloodGroup, &sealed).unwrap();
+		assert_eq!(render(ProfileField::BloodGroup, &plaintext).unwrap(), format!("{:?}", BloodType::APos));
+	}
+
+	#[test]
+	fn sealed_fields_do_not_open_for_anyone_else() {
+		let (owner, grantee) = (x25519_secret(&[1; 32]), x25519_secret(&[2; 32]));
+		let owner_public = PublicKey::from(&owner);
+		let sealed = seal([7; 32], &owner_public, ProfileField::Religion, &b"Buddhism".to_vec().encode());
+
+		// another reader's key, even claiming the owner's public key
+		assert!(open(&grantee, &owner_public, ProfileField::Religion, &sealed).is_err());
+		assert!(open(&grantee, &PublicKey::from(&grantee), ProfileField::Religion, &sealed).is_err());
+		// the field is bound as associated data
+		assert!(open(&owner, &owner_public, ProfileField::City, &sealed).is_err());
+		let mut corrupted = sealed.clone();
+		*corrupted.last_mut().unwrap() ^= 1;
+		assert!(open(&owner, &owner_public, ProfileField::Religion, &corrupted).is_err());
+		assert!(open(&owner, &owner_public, ProfileField::Religion, &sealed[..31]).is_err());
+	}
+}
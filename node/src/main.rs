@@ -5,6 +5,8 @@ mod benchmarking;
 mod chain_spec;
 mod cli;
 mod command;
+mod decrypt;
+mod pictures;
 mod rpc;
 mod service;
 
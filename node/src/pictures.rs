@@ -0,0 +1,173 @@
+//! `profilePicture_*` RPC: a content-addressed store for the images `profile::PictureRef`
+//! points at, kept in the node's persistent offchain storage.
+//!
+//! Pictures are keyed by hash algorithm and digest, and checked against the digest both when
+//! uploaded and when served, so a damaged database never serves another image. Uploading is
+//! an unsafe method: only nodes started with `--rpc-methods unsafe` accept pictures.
+
+use codec::Encode;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::{ErrorObject, ErrorObjectOwned},
+	Extensions,
+};
+use profile::HashAlgorithm;
+use solochain_template_runtime::configs::ProfileMaxPictureSize;
+use sp_core::{
+	offchain::{OffchainStorage, STORAGE_PREFIX},
+	Bytes, H256,
+};
+use std::sync::{PoisonError, RwLock};
+
+/// Prefix of the offchain storage keys of pictures.
+const KEY_PREFIX: &[u8] = b"profile-picture:";
+
+/// The uploaded bytes do not hash to the digest, or are empty or larger than the runtime's
+/// `MaxPictureSize`.
+const INVALID_PICTURE: i32 = 7001;
+/// The stored bytes no longer hash to their digest.
+const CORRUPTED_PICTURE: i32 = 7002;
+
+/// Upload and fetch profile pictures by content hash.
+#[rpc(server)]
+pub trait ProfilePictureApi {
+	/// Store `data` as the picture whose `algorithm` hash is `digest`.
+	#[method(name = "profilePicture_upload", with_extensions)]
+	fn upload(&self, algorithm: HashAlgorithm, digest: H256, data: Bytes) -> RpcResult<()>;
+
+	/// The picture whose `algorithm` hash is `digest`, `None` if this node does not have it.
+	#[method(name = "profilePicture_get")]
+	fn get(&self, algorithm: HashAlgorithm, digest: H256) -> RpcResult<Option<Bytes>>;
+}
+
+/// Implements [`ProfilePictureApiServer`] on an offchain storage.
+pub struct ProfilePictures<S> {
+	storage: RwLock<S>,
+}
+
+impl<S> ProfilePictures<S> {
+	/// Serve the pictures in `storage`.
+	pub fn new(storage: S) -> Self {
+		Self { storage: RwLock::new(storage) }
+	}
+}
+
+fn key(algorithm: HashAlgorithm, digest: &H256) -> Vec<u8> {
+	[KEY_PREFIX, &algorithm.encode(), digest.as_bytes()].concat()
+}
+
+fn error(code: i32, message: &str) -> ErrorObjectOwned {
+	ErrorObject::owned(code, message, None::<()>)
+}
+
+impl<S: OffchainStorage + 'static> ProfilePictureApiServer for ProfilePictures<S> {
+	fn upload(
+		&self,
+		ext: &Extensions,
+		algorithm: HashAlgorithm,
+		digest: H256,
+		data: Bytes,
+	) -> RpcResult<()> {
+		sc_rpc_api::check_if_safe(ext)?;
+		if !(1..=ProfileMaxPictureSize::get() as usize).contains(&data.len()) {
+			return Err(error(INVALID_PICTURE, "picture is empty or larger than MaxPictureSize"));
+		}
+		if algorithm.hash(&data) != digest.0 {
+			return Err(error(INVALID_PICTURE, "picture does not match the digest"));
+		}
+		let mut storage = self.storage.write().unwrap_or_else(PoisonError::into_inner);
+		storage.set(STORAGE_PREFIX, &key(algorithm, &digest), &data);
+		Ok(())
+	}
+
+	fn get(&self, algorithm: HashAlgorithm, digest: H256) -> RpcResult<Option<Bytes>> {
+		let storage = self.storage.read().unwrap_or_else(PoisonError::into_inner);
+		let Some(data) = storage.get(STORAGE_PREFIX, &key(algorithm, &digest)) else {
+			return Ok(None);
+		};
+		if algorithm.hash(&data) != digest.0 {
+			return Err(error(CORRUPTED_PICTURE, "stored picture does not match its digest"));
+		}
+		Ok(Some(data.into()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_rpc_api::DenyUnsafe;
+	use sp_core::offchain::storage::InMemOffchainStorage;
+
+	/// A PNG signature standing in for a picture.
+	const PICTURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+	fn digest(data: &[u8]) -> H256 {
+		H256(HashAlgorithm::Blake2_256.hash(data))
+	}
+
+	fn extensions(deny_unsafe: DenyUnsafe) -> Extensions {
+		let mut ext = Extensions::new();
+		ext.insert(deny_unsafe);
+		ext
+	}
+
+	fn upload(pictures: &ProfilePictures<InMemOffchainStorage>, digest: H256, data: &[u8]) -> RpcResult<()> {
+		pictures.upload(&extensions(DenyUnsafe::No), HashAlgorithm::Blake2_256, digest, data.to_vec().into())
+	}
+
+	#[test]
+	fn pictures_are_served_as_uploaded() {
+		let pictures = ProfilePictures::new(InMemOffchainStorage::default());
+		let unsafe_denied = extensions(DenyUnsafe::Yes);
+		assert!(pictures
+			.upload(&unsafe_denied, HashAlgorithm::Blake2_256, digest(PICTURE), PICTURE.to_vec().into())
+			.is_err());
+		assert_eq!(pictures.get(HashAlgorithm::Blake2_256, digest(PICTURE)).unwrap(), None);
+
+		upload(&pictures, digest(PICTURE), PICTURE).unwrap();
+		let served = pictures.get(HashAlgorithm::Blake2_256, digest(PICTURE)).unwrap();
+		assert_eq!(served, Some(PICTURE.to_vec().into()));
+		// the same digest under another algorithm is another picture
+		assert_eq!(pictures.get(HashAlgorithm::Sha2_256, digest(PICTURE)).unwrap(), None);
+	}
+
+	#[test]
+	fn uploads_are_limited_to_max_picture_size() {
+		let pictures = ProfilePictures::new(InMemOffchainStorage::default());
+		let max = ProfileMaxPictureSize::get() as usize;
+
+		assert_eq!(upload(&pictures, digest(&[]), &[]).unwrap_err().code(), INVALID_PICTURE);
+		let too_large = vec![0; max + 1];
+		assert_eq!(upload(&pictures, digest(&too_large), &too_large).unwrap_err().code(), INVALID_PICTURE);
+		let largest = vec![0; max];
+		upload(&pictures, digest(&largest), &largest).unwrap();
+	}
+
+	#[test]
+	fn uploads_must_match_their_digest() {
+		let pictures = ProfilePictures::new(InMemOffchainStorage::default());
+
+		assert_eq!(upload(&pictures, digest(b"\x89PNG"), PICTURE).unwrap_err().code(), INVALID_PICTURE);
+		let sha2 = pictures.upload(
+			&extensions(DenyUnsafe::No),
+			HashAlgorithm::Sha2_256,
+			digest(PICTURE),
+			PICTURE.to_vec().into(),
+		);
+		assert_eq!(sha2.unwrap_err().code(), INVALID_PICTURE);
+		assert_eq!(pictures.get(HashAlgorithm::Blake2_256, digest(PICTURE)).unwrap(), None);
+	}
+
+	#[test]
+	fn corrupted_pictures_are_not_served() {
+		let pictures = ProfilePictures::new(InMemOffchainStorage::default());
+		upload(&pictures, digest(PICTURE), PICTURE).unwrap();
+
+		// clones of an `InMemOffchainStorage` do not share data, so damage the served one
+		let key = key(HashAlgorithm::Blake2_256, &digest(PICTURE));
+		pictures.storage.write().unwrap().set(STORAGE_PREFIX, &key, b"\x89PNG\r\n\x1a\x0b");
+		let served = pictures.get(HashAlgorithm::Blake2_256, digest(PICTURE));
+		assert_eq!(served.unwrap_err().code(), CORRUPTED_PICTURE);
+	}
+}
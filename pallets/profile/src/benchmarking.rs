@@ -0,0 +1,568 @@
+// pallets/profile/src/benchmarking.rs
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_support::{
+    traits::{ConstU32, EnsureOrigin, Get},
+    BoundedVec,
+};
+use frame_benchmarking::v2::*;
+use frame_system::RawOrigin;
+use scale_info::prelude::string::String;
+use sp_std::{vec, vec::Vec};
+use user::{Date, OnUserCreated, OnUserMoved, OnUserRemoved};
+
+/* -------------------------------------------------------------------- *
+ *  Helpers                                                             *
+ * -------------------------------------------------------------------- */
+
+/// Not in the future even when the chain has no timestamp yet.
+const BIRTH_DATE: Date = Date::new(1969, 12, 31);
+
+/// Registers users through the user pallet's `RegistrarOrigin`, which is what runtimes
+/// using `user::Pallet` as `UserPallet` plug in as `BenchmarkHelper`.
+impl<T: user::Config> BenchmarkHelper<T::AccountId> for user::Pallet<T> {
+    fn register_user(who: &T::AccountId) {
+        let origin = T::RegistrarOrigin::try_successful_origin()
+            .expect("benchmarks need a registrar origin");
+        user::Pallet::<T>::force_create_user(
+            origin,
+            who.clone(),
+            "Nimal".into(),
+            "Perera".into(),
+            "Colombo".into(),
+            BIRTH_DATE,
+        )
+        .expect("benchmark user can be created");
+    }
+}
+
+/// `len` copies of `c` as a `String`.
+fn filler(c: u8, len: u32) -> String {
+    String::from_utf8(vec![c; len as usize]).unwrap()
+}
+
+/// `len` copies of `c`, within the bound `S`.
+fn text<S: Get<u32>>(c: u8, len: u32) -> BoundedVec<u8, S> {
+    vec![c; len as usize].try_into().expect("benchmarks stay within the bound")
+}
+
+/// Whitelisted caller registered as an active user.
+fn registered_caller<T: Config>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    T::BenchmarkHelper::register_user(&caller);
+    caller
+}
+
+/// The largest picture `MaxPictureSize` allows.
+fn largest_picture<T: Config>(digest: u8) -> PictureRef {
+    PictureRef {
+        algorithm: HashAlgorithm::Sha2_256,
+        digest: [digest; 32],
+        mime: ImageMime::Webp,
+        size: T::MaxPictureSize::get(),
+    }
+}
+
+/// Give `owner` a profile with the longest names and texts the bounds allow.
+fn create_longest<T: Config>(owner: &T::AccountId) {
+    let (n, t) = (T::MaxNameLen::get(), T::MaxTextLen::get());
+    Pallet::<T>::create_profile(
+        RawOrigin::Signed(owner.clone()).into(),
+        filler(b'f', n),
+        filler(b'm', n),
+        filler(b'l', n),
+        MaritalStatus::Single,
+        Some(largest_picture::<T>(0)),
+        Gender::Female,
+        BloodType::ABNeg,
+        filler(b'n', t),
+        filler(b'r', t),
+        filler(b'g', t),
+        Province::Western,
+        District::Colombo,
+        filler(b'c', t),
+        filler(b'd', t),
+        MAX_POSTAL_CODE,
+        BIRTH_DATE,
+    )
+    .expect("benchmark profile can be created");
+}
+
+/// An update writing every field, each with a value `create_longest` did not use.
+fn full_update<T: Config>(n: u32, t: u32) -> UserProfileDataUpdateOf<T> {
+    UserProfileDataUpdate {
+        f_name: Some(text(b'F', n)),
+        m_name: Some(text(b'M', n)),
+        l_name: Some(text(b'L', n)),
+        marital_status: Some(MaritalStatus::Married),
+        picture: Some(Some(largest_picture::<T>(1))),
+        gender: Some(Gender::Male),
+        blood_group: Some(BloodType::OPos),
+        nationality: Some(text(b'N', t)),
+        religion: Some(text(b'R', t)),
+        lit_lang: Some(text(b'G', t)),
+        province: Some(Province::Central),
+        district: Some(District::Kandy),
+        city: Some(text(b'C', t)),
+        division: Some(text(b'D', t)),
+        postal_code: Some(10_000),
+        birth_date: Some(Date::new(1970, 1, 1)),
+    }
+}
+
+/// Record `count` revisions for `owner`; with `MaxRevisions` the next one drops the oldest.
+fn fill_history<T: Config>(owner: &T::AccountId, count: u32) {
+    for i in 0..count {
+        let city = text(if i % 2 == 0 { b'x' } else { b'y' }, 1);
+        Pallet::<T>::update_profile(
+            RawOrigin::Signed(owner.clone()).into(),
+            UserProfileDataUpdate { city: Some(city), ..Default::default() },
+        )
+        .expect("benchmark profile can be updated");
+    }
+}
+
+/// The longest ciphertexts for the owner and each of `readers`.
+fn sealed<T: Config>(readers: &[T::AccountId]) -> SealedFieldOf<T> {
+    let shared: Vec<_> = readers.iter().map(|reader| (reader.clone(), text(0, T::MaxCiphertextLen::get()))).collect();
+    SealedField {
+        own   : text(0, T::MaxCiphertextLen::get()),
+        shared: shared.try_into().expect("at most `MaxGrants` readers"),
+    }
+}
+
+/// Encrypt every encryptable field of `owner` with the longest ciphertexts, shared with
+/// `readers`.
+fn encrypt_all<T: Config>(owner: &T::AccountId, readers: &[T::AccountId]) {
+    Pallet::<T>::set_encryption_key(RawOrigin::Signed(owner.clone()).into(), [1; 32])
+        .expect("encryption key can be set");
+    for field in FieldSet::ALL.iter().filter(|f| f.is_encryptable()) {
+        Pallet::<T>::set_encrypted_field(RawOrigin::Signed(owner.clone()).into(), field, sealed::<T>(readers))
+            .expect("field can be encrypted");
+    }
+}
+
+/// Add `count` verifiers to `Verifiers`.
+fn add_verifiers<T: Config>(count: u32) -> Result<Vec<T::AccountId>, BenchmarkError> {
+    let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    let verifiers: Vec<T::AccountId> = (0..count).map(|i| account("verifier", i, 0)).collect();
+    for verifier in &verifiers {
+        Pallet::<T>::add_verifier(origin.clone(), verifier.clone()).expect("verifier can be added");
+    }
+    Ok(verifiers)
+}
+
+/// Have each of `verifiers` judge `fields` of the profile of `owner`.
+fn judge<T: Config>(owner: &T::AccountId, verifiers: &[T::AccountId], fields: FieldSet) {
+    for verifier in verifiers {
+        for field in fields.iter() {
+            Pallet::<T>::judge_field(
+                RawOrigin::Signed(verifier.clone()).into(),
+                owner.clone(),
+                field,
+                Judgement::KnownGood,
+            )
+            .expect("verifier can judge");
+        }
+    }
+}
+
+/// Share every field of the profile of `owner` with `count` accounts.
+fn grant_all<T: Config>(owner: &T::AccountId, count: u32) -> Vec<T::AccountId> {
+    let grantees: Vec<T::AccountId> = (0..count).map(|i| account("grantee", i, 0)).collect();
+    for grantee in &grantees {
+        Pallet::<T>::grant_access(
+            RawOrigin::Signed(owner.clone()).into(),
+            grantee.clone(),
+            all_fields(),
+            T::MaxGrantDuration::get(),
+        )
+        .expect("access can be granted");
+    }
+    grantees
+}
+
+/// Share every field of the profile of `owner` with `count` registered accounts that hold
+/// an encryption key.
+fn readers<T: Config>(owner: &T::AccountId, count: u32) -> Vec<T::AccountId> {
+    let readers = grant_all::<T>(owner, count);
+    for reader in &readers {
+        T::BenchmarkHelper::register_user(reader);
+        Pallet::<T>::set_encryption_key(RawOrigin::Signed(reader.clone()).into(), [2; 32])
+            .expect("encryption key can be set");
+    }
+    readers
+}
+
+fn all_fields() -> BoundedVec<ProfileField, ConstU32<PROFILE_FIELDS>> {
+    FieldSet::ALL.iter().collect::<Vec<_>>().try_into().expect("one entry per field fits")
+}
+
+/// A visibility change for every field.
+fn all_visibility(visibility: Visibility) -> VisibilityChanges {
+    FieldSet::ALL
+        .iter()
+        .map(|field| (field, visibility))
+        .collect::<Vec<_>>()
+        .try_into()
+        .expect("one entry per field fits")
+}
+
+/// Give `owner` a profile with every field encrypted and judged by `MaxVerifiers` verifiers,
+/// and `revisions` revisions.
+fn populated_profile<T: Config>(owner: &T::AccountId, revisions: u32) -> Result<(), BenchmarkError> {
+    create_longest::<T>(owner);
+    fill_history::<T>(owner, revisions);
+    encrypt_all::<T>(owner, &[]);
+    let verifiers = add_verifiers::<T>(T::MaxVerifiers::get())?;
+    judge::<T>(owner, &verifiers, FieldSet::ALL);
+    Ok(())
+}
+
+/// A fully populated profile of `owner` with `revisions` revisions, archived as if its user
+/// record was removed.
+fn archived_profile<T: Config>(owner: &T::AccountId, revisions: u32) -> Result<(), BenchmarkError> {
+    populated_profile::<T>(owner, revisions)?;
+    let profile = Profiles::<T>::take(owner).expect("profile was just created");
+    ArchivedProfiles::<T>::insert(owner, profile);
+    Ok(())
+}
+
+/* -------------------------------------------------------------------- *
+ *  Benchmarks                                                          *
+ * -------------------------------------------------------------------- */
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    /* ===== create_profile ========================================== */
+    #[benchmark]
+    fn create_profile(
+        n: Linear<1, { T::MaxNameLen::get() }>,
+        t: Linear<1, { T::MaxTextLen::get() }>,
+    ) {
+        let caller = registered_caller::<T>();
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Signed(caller.clone()),
+            filler(b'f', n),
+            filler(b'm', n),
+            filler(b'l', n),
+            MaritalStatus::Single,
+            Some(largest_picture::<T>(0)),
+            Gender::Female,
+            BloodType::ABNeg,
+            filler(b'n', t),
+            filler(b'r', t),
+            filler(b'g', t),
+            Province::Western,
+            District::Colombo,
+            filler(b'c', t),
+            filler(b'd', t),
+            MAX_POSTAL_CODE,
+            BIRTH_DATE
+        );
+
+        assert!(Profiles::<T>::contains_key(&caller));
+    }
+
+    /* ===== update_profile ========================================== */
+    #[benchmark]
+    fn update_profile(
+        n: Linear<1, { T::MaxNameLen::get() }>,
+        t: Linear<1, { T::MaxTextLen::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        // worst case: every field written, encrypted and judged, and a revision to drop
+        let caller = registered_caller::<T>();
+        populated_profile::<T>(&caller, T::MaxRevisions::get())?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), full_update::<T>(n, t));
+
+        assert_eq!(EncryptedFields::<T>::iter_prefix(&caller).count(), 0);
+        assert_eq!(Judgements::<T>::iter_prefix(&caller).count(), 0);
+        let (first, next) = RevisionRange::<T>::get(&caller);
+        assert_eq!(next - first, T::MaxRevisions::get());
+        Ok(())
+    }
+
+    /* ===== remove_profile ========================================== */
+    #[benchmark]
+    fn remove_profile(r: Linear<0, { T::MaxRevisions::get() }>) -> Result<(), BenchmarkError> {
+        let caller = registered_caller::<T>();
+        populated_profile::<T>(&caller, r)?;
+        Pallet::<T>::set_visibility(
+            RawOrigin::Signed(caller.clone()).into(),
+            all_visibility(Visibility::Private),
+        )
+        .expect("visibility can be set");
+        grant_all::<T>(&caller, T::MaxGrants::get());
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()));
+
+        assert!(!Profiles::<T>::contains_key(&caller));
+        assert_eq!(Revisions::<T>::iter_prefix(&caller).count(), 0);
+        Ok(())
+    }
+
+    /* ===== set_visibility ========================================== */
+    #[benchmark]
+    fn set_visibility() {
+        // worst case: every field overridden already and changed again
+        let caller = registered_caller::<T>();
+        create_longest::<T>(&caller);
+        Pallet::<T>::set_visibility(
+            RawOrigin::Signed(caller.clone()).into(),
+            all_visibility(Visibility::Private),
+        )
+        .expect("visibility can be set");
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), all_visibility(Visibility::RegistrarsOnly));
+
+        assert_eq!(FieldVisibility::<T>::get(&caller).len() as u32, PROFILE_FIELDS);
+    }
+
+    /* ===== set_default_visibility ================================== */
+    #[benchmark]
+    fn set_default_visibility() -> Result<(), BenchmarkError> {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        Pallet::<T>::set_default_visibility(origin.clone(), all_visibility(Visibility::Private))
+            .expect("default visibility can be set");
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, all_visibility(Visibility::RegistrarsOnly));
+
+        assert_eq!(DefaultVisibility::<T>::get().len() as u32, PROFILE_FIELDS);
+        Ok(())
+    }
+
+    /* ===== grant_access ============================================ */
+    #[benchmark]
+    fn grant_access() {
+        // worst case: the last of a full list narrowed to a plaintext field, which drops its
+        // copies of every encrypted field
+        let caller = registered_caller::<T>();
+        create_longest::<T>(&caller);
+        let readers = readers::<T>(&caller, T::MaxGrants::get());
+        encrypt_all::<T>(&caller, &readers);
+        let grantee = readers.last().expect("`MaxGrants` is not zero").clone();
+        let province: BoundedVec<_, _> = vec![ProfileField::Province].try_into().expect("one field fits");
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), grantee, province, T::MaxGrantDuration::get());
+
+        assert_eq!(Grants::<T>::get(&caller).len() as u32, T::MaxGrants::get());
+        let copies = EncryptedFields::<T>::get(&caller, ProfileField::FirstName).map(|s| s.shared.len());
+        assert_eq!(copies, Some(readers.len() - 1));
+    }
+
+    /* ===== revoke_access =========================================== */
+    #[benchmark]
+    fn revoke_access() {
+        // worst case: the last of a full list, holding copies of every encrypted field
+        let caller = registered_caller::<T>();
+        create_longest::<T>(&caller);
+        let mut grantees = readers::<T>(&caller, T::MaxGrants::get());
+        encrypt_all::<T>(&caller, &grantees);
+        let grantee = grantees.pop().expect("`MaxGrants` is not zero");
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), grantee);
+
+        assert_eq!(Grants::<T>::get(&caller).len(), grantees.len());
+        let copies = EncryptedFields::<T>::get(&caller, ProfileField::FirstName).map(|s| s.shared.len());
+        assert_eq!(copies, Some(grantees.len()));
+    }
+
+    /* ===== set_encryption_key ====================================== */
+    #[benchmark]
+    fn set_encryption_key() {
+        // a grantee without a user record
+        let caller: T::AccountId = account("grantee", 0, 0);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), [2; 32]);
+
+        assert_eq!(EncryptionKeys::<T>::get(&caller), Some([2; 32]));
+    }
+
+    /* ===== set_encrypted_field ===================================== */
+    #[benchmark]
+    fn set_encrypted_field(
+        g: Linear<0, { T::MaxGrants::get() }>,
+        r: Linear<0, { T::MaxRevisions::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        // worst case: the field changed in every revision and judged by `MaxVerifiers`
+        // verifiers
+        let caller = registered_caller::<T>();
+        create_longest::<T>(&caller);
+        fill_history::<T>(&caller, r);
+        let verifiers = add_verifiers::<T>(T::MaxVerifiers::get())?;
+        judge::<T>(&caller, &verifiers, [ProfileField::City].into_iter().collect());
+        let readers = readers::<T>(&caller, g);
+        Pallet::<T>::set_encryption_key(RawOrigin::Signed(caller.clone()).into(), [1; 32])
+            .expect("encryption key can be set");
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), ProfileField::City, sealed::<T>(&readers));
+
+        assert!(EncryptedFields::<T>::contains_key(&caller, ProfileField::City));
+        assert!(!Judgements::<T>::contains_key(&caller, ProfileField::City));
+        Ok(())
+    }
+
+    /* ===== set_history_retention =================================== */
+    #[benchmark]
+    fn set_history_retention() -> Result<(), BenchmarkError> {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let retention = T::MaxGrantDuration::get();
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, Some(retention));
+
+        assert_eq!(HistoryRetention::<T>::get(), Some(retention));
+        Ok(())
+    }
+
+    /* ===== add_verifier ============================================ */
+    #[benchmark]
+    fn add_verifier() -> Result<(), BenchmarkError> {
+        // worst case: the registry is one short of full
+        add_verifiers::<T>(T::MaxVerifiers::get().saturating_sub(1))?;
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let verifier: T::AccountId = account("newcomer", 0, 0);
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, verifier);
+
+        assert_eq!(Verifiers::<T>::get().len() as u32, T::MaxVerifiers::get());
+        Ok(())
+    }
+
+    /* ===== remove_verifier ========================================= */
+    #[benchmark]
+    fn remove_verifier() -> Result<(), BenchmarkError> {
+        // worst case: the last of a full registry
+        let mut verifiers = add_verifiers::<T>(T::MaxVerifiers::get())?;
+        let verifier = verifiers.pop().expect("`MaxVerifiers` is not zero");
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, verifier);
+
+        assert_eq!(Verifiers::<T>::get().len(), verifiers.len());
+        Ok(())
+    }
+
+    /* ===== judge_field ============================================= */
+    #[benchmark]
+    fn judge_field() -> Result<(), BenchmarkError> {
+        // worst case: the last verifier of a full registry replaces its judgement in a full list
+        let owner: T::AccountId = account("owner", 0, 0);
+        T::BenchmarkHelper::register_user(&owner);
+        create_longest::<T>(&owner);
+        let verifiers = add_verifiers::<T>(T::MaxVerifiers::get())?;
+        let city: FieldSet = [ProfileField::City].into_iter().collect();
+        judge::<T>(&owner, &verifiers, city);
+        let caller = verifiers.last().expect("`MaxVerifiers` is not zero").clone();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), owner.clone(), ProfileField::City, Judgement::Reasonable);
+
+        assert_eq!(Judgements::<T>::get(&owner, ProfileField::City).len(), verifiers.len());
+        Ok(())
+    }
+
+    /* ===== purge_archived_profile ================================== */
+    #[benchmark]
+    fn purge_archived_profile(r: Linear<0, { T::MaxRevisions::get() }>) -> Result<(), BenchmarkError> {
+        let caller = registered_caller::<T>();
+        archived_profile::<T>(&caller, r)?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()));
+
+        assert!(!ArchivedProfiles::<T>::contains_key(&caller));
+        assert_eq!(Revisions::<T>::iter_prefix(&caller).count(), 0);
+        Ok(())
+    }
+
+    /* ===== force_purge_archived_profile ============================ */
+    #[benchmark]
+    fn force_purge_archived_profile(r: Linear<0, { T::MaxRevisions::get() }>) -> Result<(), BenchmarkError> {
+        let owner = registered_caller::<T>();
+        archived_profile::<T>(&owner, r)?;
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, owner.clone());
+
+        assert!(!ArchivedProfiles::<T>::contains_key(&owner));
+        assert_eq!(Revisions::<T>::iter_prefix(&owner).count(), 0);
+        Ok(())
+    }
+
+    /* ===== on_user_removed ========================================= */
+    #[benchmark]
+    fn on_user_removed(r: Linear<0, { T::MaxRevisions::get() }>) -> Result<(), BenchmarkError> {
+        let owner = registered_caller::<T>();
+        populated_profile::<T>(&owner, r)?;
+        grant_all::<T>(&owner, T::MaxGrants::get());
+
+        #[block]
+        {
+            <Pallet<T> as OnUserRemoved<T::AccountId>>::on_user_removed(&owner);
+        }
+
+        assert!(!Profiles::<T>::contains_key(&owner));
+        assert!(!Grants::<T>::contains_key(&owner));
+        Ok(())
+    }
+
+    /* ===== on_user_created ========================================= */
+    #[benchmark]
+    fn on_user_created() {
+        let owner = registered_caller::<T>();
+        create_longest::<T>(&owner);
+        let profile = Profiles::<T>::take(&owner).expect("profile was just created");
+        ArchivedProfiles::<T>::insert(&owner, profile);
+
+        #[block]
+        {
+            <Pallet<T> as OnUserCreated<T::AccountId>>::on_user_created(&owner);
+        }
+
+        assert!(Profiles::<T>::contains_key(&owner));
+    }
+
+    /* ===== on_user_moved =========================================== */
+    #[benchmark]
+    fn on_user_moved(r: Linear<0, { T::MaxRevisions::get() }>) -> Result<(), BenchmarkError> {
+        let from = registered_caller::<T>();
+        populated_profile::<T>(&from, r)?;
+        grant_all::<T>(&from, T::MaxGrants::get());
+        let to: T::AccountId = account("recovered", 0, 0);
+
+        #[block]
+        {
+            <Pallet<T> as OnUserMoved<T::AccountId>>::can_move(&from, &to)
+                .expect("the target has no archive");
+            <Pallet<T> as OnUserMoved<T::AccountId>>::on_user_moved(&from, &to);
+        }
+
+        assert!(Profiles::<T>::contains_key(&to));
+        assert_eq!(Revisions::<T>::iter_prefix(&to).count() as u32, r);
+        Ok(())
+    }
+
+    /* ===== wiring for `cargo test --features runtime-benchmarks` ==== */
+    impl_benchmark_test_suite!(
+        Pallet,
+        crate::mock::new_test_ext(),
+        crate::mock::Test
+    );
+}
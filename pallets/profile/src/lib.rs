@@ -3,15 +3,46 @@
 
 pub use pallet::*;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub mod migrations;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+/// Log target of the profile pallet.
+pub const LOG_TARGET: &str = "runtime::profile";
+
+// `create_profile` takes every profile field as its own argument. The pallet macro copies an
+// `allow` on the call only into the dispatch code, not into the `Call::new_call_variant_*`
+// constructor it emits next to the call enum, so the lint is allowed for the whole module.
+#[allow(clippy::too_many_arguments)]
 #[frame_support::pallet]
 pub mod pallet {
     use frame_support::{
         pallet_prelude::*,
-        sp_runtime::BoundedVec,
+        sp_runtime::{
+            traits::{IdentifyAccount, Saturating, Verify, Zero},
+            BoundedBTreeMap, BoundedVec,
+        },
+        traits::{Defensive, UnixTime},
+        CloneNoBound, DefaultNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
+    };
+    use user::{
+        text::{self, CharClass},
+        Date, OnUserCreated, OnUserMoved, OnUserRemoved, UserApi,
     };
-    use user::UserApi;
     use frame_system::pallet_prelude::*;
-    use scale_info::prelude::string::String;
+    use scale_info::prelude::{string::String, vec::Vec};
+    use serde::{Deserialize, Serialize};
+    use crate::{migrations, WeightInfo};
 
     /* -------------------------------------------------
      *  Associated-type & pallet declaration
@@ -19,100 +50,745 @@ pub mod pallet {
     #[pallet::config]
     pub trait Config: frame_system::Config {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Weight information for the dispatchables of this pallet.
+        type WeightInfo: WeightInfo;
+
         type UserPallet: UserApi<Self::AccountId>;
+        /// Clock used to refuse birth dates in the future.
+        type UnixTime: UnixTime;
+        /// Governance origin that sets `DefaultVisibility`.
+        type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Most accounts a profile owner can share fields with at once.
+        #[pallet::constant]
+        type MaxGrants: Get<u32>;
+
+        /// Longest a sharing grant may last, in blocks.
+        #[pallet::constant]
+        type MaxGrantDuration: Get<BlockNumberFor<Self>>;
+
+        /// Longest encrypted field, `CIPHERTEXT_OVERHEAD` included.
+        #[pallet::constant]
+        type MaxCiphertextLen: Get<u32>;
+
+        /// Most revisions kept per profile; recording one more drops the oldest.
+        #[pallet::constant]
+        type MaxRevisions: Get<u32>;
+
+        /// Most accounts in the `Verifiers` registry.
+        #[pallet::constant]
+        type MaxVerifiers: Get<u32>;
+
+        /// When the owning user record is removed, keep the profile in `ArchivedProfiles`
+        /// (restored if the user registers again, until the owner or `AdminOrigin` purges it)
+        /// instead of deleting it.
+        #[pallet::constant]
+        type ArchiveRemovedProfiles: Get<bool>;
+
+        /// Longest first, middle or last name, in bytes of UTF-8.
+        #[pallet::constant]
+        type MaxNameLen: Get<u32>;
+
+        /// Longest nationality, religion, language, city or division, in bytes of UTF-8.
+        #[pallet::constant]
+        type MaxTextLen: Get<u32>;
+
+        /// Largest profile picture, in bytes.
+        #[pallet::constant]
+        type MaxPictureSize: Get<u32>;
+
+        /// Signature of a requester over a `ReadAuth`, checked against its account.
+        type ReadSignature: Parameter + Verify<Signer: IdentifyAccount<AccountId = Self::AccountId>>;
+
+        /// Furthest ahead of the current block a `ReadAuth` may be valid until.
+        #[pallet::constant]
+        type MaxReadAuthAge: Get<BlockNumberFor<Self>>;
+
+        /// Registers the users that benchmarks create profiles for.
+        #[cfg(feature = "runtime-benchmarks")]
+        type BenchmarkHelper: BenchmarkHelper<Self::AccountId>;
+    }
+
+    /// Sets up what the benchmarks need from `UserPallet`.
+    #[cfg(feature = "runtime-benchmarks")]
+    pub trait BenchmarkHelper<AccountId> {
+        /// Register `who` as an active user.
+        fn register_user(who: &AccountId);
     }
 
+    /// The in-code storage version. Bump it together with a new module in `migrations`.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(4);
+
     #[pallet::pallet]
     #[pallet::without_storage_info]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
+    /// Largest page returned by `profiles_page`.
+    pub const MAX_PAGE_LEN: u32 = 100;
+
+    /// Largest five-digit postal code.
+    pub const MAX_POSTAL_CODE: u32 = 99_999;
+
+    /// Ephemeral public key and authentication tag around an encrypted field, see
+    /// `EncryptedFields`.
+    pub const CIPHERTEXT_OVERHEAD: u32 = 32 + 16;
+
     /* -------------------------------------------------
      *  Helper types & enums
      * ------------------------------------------------- */
-    // Adjust lengths to taste once UI limits are known.
-    pub type Str64  = BoundedVec<u8, ConstU32<64>>;
-    pub type Str128 = BoundedVec<u8, ConstU32<128>>;
-
     #[derive(
         Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking
     )]
-    pub enum MaritalStatus { Single, Married, Divorced, Widowed }
+    /// `Withheld` here and in `Gender` and `BloodType` stands for an undisclosed or encrypted
+    /// value. Append new variants after it.
+    pub enum MaritalStatus { Single, Married, Divorced, Widowed, Withheld }
 
     #[derive(
         Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking
     )]
-    pub enum Gender { Male, Female, Other }
+    pub enum Gender { Male, Female, Other, Withheld }
 
     #[derive(
         Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking
     )]
-    pub enum BloodType { APos, ANeg, BPos, BNeg, OPos, ONeg, ABPos, ABNeg }
+    pub enum BloodType { APos, ANeg, BPos, BNeg, OPos, ONeg, ABPos, ABNeg, Withheld }
 
-    // Keep Province / District to what you actually need.
+    /// The nine provinces of Sri Lanka.
+    ///
+    /// Stored in profiles and returned by `ProfileApi`: only append variants, never reorder
+    /// or remove them, so the pinned indices keep decoding.
     #[derive(
         Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking
     )]
-    pub enum Province { Western, Central, Southern, Northern, Eastern,
-                         NorthWestern, NorthCentral, Uva, Sabaragamuwa }
+    pub enum Province {
+        #[codec(index = 0)] Western,
+        #[codec(index = 1)] Central,
+        #[codec(index = 2)] Southern,
+        #[codec(index = 3)] Northern,
+        #[codec(index = 4)] Eastern,
+        #[codec(index = 5)] NorthWestern,
+        #[codec(index = 6)] NorthCentral,
+        #[codec(index = 7)] Uva,
+        #[codec(index = 8)] Sabaragamuwa,
+    }
 
+    /// The 25 districts of Sri Lanka.
+    ///
+    /// Append-only like `Province`. `Colombo` and `Gampaha` were the only variants before the
+    /// others were appended, so stored values keep their meaning without a migration.
     #[derive(
         Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking
     )]
-    pub enum District { Colombo, Gampaha /* … */ }
+    pub enum District {
+        #[codec(index = 0)] Colombo,
+        #[codec(index = 1)] Gampaha,
+        #[codec(index = 2)] Kalutara,
+        #[codec(index = 3)] Kandy,
+        #[codec(index = 4)] Matale,
+        #[codec(index = 5)] NuwaraEliya,
+        #[codec(index = 6)] Galle,
+        #[codec(index = 7)] Matara,
+        #[codec(index = 8)] Hambantota,
+        #[codec(index = 9)] Jaffna,
+        #[codec(index = 10)] Kilinochchi,
+        #[codec(index = 11)] Mannar,
+        #[codec(index = 12)] Vavuniya,
+        #[codec(index = 13)] Mullaitivu,
+        #[codec(index = 14)] Batticaloa,
+        #[codec(index = 15)] Ampara,
+        #[codec(index = 16)] Trincomalee,
+        #[codec(index = 17)] Kurunegala,
+        #[codec(index = 18)] Puttalam,
+        #[codec(index = 19)] Anuradhapura,
+        #[codec(index = 20)] Polonnaruwa,
+        #[codec(index = 21)] Badulla,
+        #[codec(index = 22)] Monaragala,
+        #[codec(index = 23)] Ratnapura,
+        #[codec(index = 24)] Kegalle,
+    }
+
+    impl District {
+        /// The province the district lies in.
+        pub fn province(self) -> Province {
+            use District::*;
+            match self {
+                Colombo | Gampaha | Kalutara => Province::Western,
+                Kandy | Matale | NuwaraEliya => Province::Central,
+                Galle | Matara | Hambantota => Province::Southern,
+                Jaffna | Kilinochchi | Mannar | Vavuniya | Mullaitivu => Province::Northern,
+                Batticaloa | Ampara | Trincomalee => Province::Eastern,
+                Kurunegala | Puttalam => Province::NorthWestern,
+                Anuradhapura | Polonnaruwa => Province::NorthCentral,
+                Badulla | Monaragala => Province::Uva,
+                Ratnapura | Kegalle => Province::Sabaragamuwa,
+            }
+        }
+    }
 
 
     pub type PostalCode = BoundedVec<u8, ConstU32<8>>;
 
+    /// A field of `UserProfileData`. Append-only like `Province`.
+    #[derive(
+        Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd,
+        DecodeWithMemTracking
+    )]
+    pub enum ProfileField {
+        #[codec(index = 0)] FirstName,
+        #[codec(index = 1)] MiddleName,
+        #[codec(index = 2)] LastName,
+        #[codec(index = 3)] MaritalStatus,
+        #[codec(index = 4)] Picture,
+        #[codec(index = 5)] Gender,
+        #[codec(index = 6)] BloodGroup,
+        #[codec(index = 7)] Nationality,
+        #[codec(index = 8)] Religion,
+        #[codec(index = 9)] Language,
+        #[codec(index = 10)] Province,
+        #[codec(index = 11)] District,
+        #[codec(index = 12)] City,
+        #[codec(index = 13)] Division,
+        #[codec(index = 14)] PostalCode,
+        #[codec(index = 15)] BirthDate,
+    }
+
+    impl ProfileField {
+        /// Whether the field may be stored encrypted. Province and district stay plaintext
+        /// for the consistency check.
+        pub fn is_encryptable(self) -> bool {
+            !matches!(self, Self::Province | Self::District)
+        }
+    }
+
+    /// Number of `ProfileField` variants.
+    pub const PROFILE_FIELDS: u32 = 16;
+
+    /// Who may read a profile field through `ProfileApi`. The chain state itself stays public;
+    /// this only governs what the API serves.
+    #[derive(
+        Default, Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq,
+        DecodeWithMemTracking
+    )]
+    pub enum Visibility {
+        /// Anyone.
+        #[default]
+        Public,
+        /// The owner and its linked accounts.
+        Private,
+        /// The owner, its linked accounts and registrars.
+        RegistrarsOnly,
+    }
+
+    /// A set of `ProfileField`s, one bit per field at its codec index.
+    #[derive(
+        Default, Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq,
+        DecodeWithMemTracking
+    )]
+    pub struct FieldSet(pub u32);
+
+    impl FieldSet {
+        /// Every `ProfileField`.
+        pub const ALL: Self = Self((1 << PROFILE_FIELDS) - 1);
+
+        pub fn contains(&self, field: ProfileField) -> bool {
+            self.0 & (1 << field as u32) != 0
+        }
+
+        pub fn insert(&mut self, field: ProfileField) {
+            self.0 |= 1 << field as u32;
+        }
+
+        pub fn remove(&mut self, field: ProfileField) {
+            self.0 &= !(1 << field as u32);
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+
+        /// The fields in the set, by codec index.
+        pub fn iter(self) -> impl Iterator<Item = ProfileField> {
+            (0..PROFILE_FIELDS as u8)
+                .filter_map(|index| ProfileField::decode(&mut &[index][..]).ok())
+                .filter(move |field| self.contains(*field))
+        }
+    }
+
+    impl FromIterator<ProfileField> for FieldSet {
+        fn from_iter<I: IntoIterator<Item = ProfileField>>(fields: I) -> Self {
+            let mut set = Self::default();
+            fields.into_iter().for_each(|field| set.insert(field));
+            set
+        }
+    }
+
+    /// Read access to some profile fields, given by the owner to `grantee` until
+    /// `expires_at` (exclusive).
+    #[derive(Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Eq, PartialEq)]
+    pub struct Grant<AccountId, BlockNumber> {
+        pub grantee   : AccountId,
+        pub fields    : FieldSet,
+        pub expires_at: BlockNumber,
+    }
+
+    pub type GrantOf<T> = Grant<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    /// An encrypted profile field: the owner's copy and one copy per grantee reading it, each
+    /// sealed to that reader's own `EncryptionKeys` entry, see `EncryptedFields`.
+    #[derive(
+        Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebugNoBound, CloneNoBound, EqNoBound, PartialEqNoBound,
+        DecodeWithMemTracking
+    )]
+    #[codec(mel_bound(AccountId: MaxEncodedLen))]
+    #[scale_info(skip_type_params(Len, Readers))]
+    pub struct SealedField<AccountId: Clone + Eq + core::fmt::Debug, Len: Get<u32>, Readers: Get<u32>> {
+        pub own   : BoundedVec<u8, Len>,
+        pub shared: BoundedVec<(AccountId, BoundedVec<u8, Len>), Readers>,
+    }
+
+    pub type SealedFieldOf<T> =
+        SealedField<<T as frame_system::Config>::AccountId, <T as Config>::MaxCiphertextLen, <T as Config>::MaxGrants>;
+
+    /// A verifier's judgement of a profile field. Append-only like `Province`.
+    #[derive(
+        Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking
+    )]
+    pub enum Judgement {
+        /// Checked, but could not be confirmed.
+        #[codec(index = 0)] Unverified,
+        /// Consistent with the evidence seen, short of an official document.
+        #[codec(index = 1)] Reasonable,
+        /// Checked against an official document.
+        #[codec(index = 2)] KnownGood,
+    }
+
+    /// Judgements of the fields of a profile by verifier, see `ProfileApi::get_judgements`.
+    pub type Verification<AccountId> = Vec<(ProfileField, Vec<(AccountId, Judgement)>)>;
+
+    /// A past change of a profile: the fields it changed and their values before it. A changed
+    /// field missing from `previous` was unset.
+    #[derive(Encode, Decode, TypeInfo, RuntimeDebug, Clone, Eq, PartialEq)]
+    pub struct Revision<BlockNumber, Values> {
+        pub block   : BlockNumber,
+        pub changed : FieldSet,
+        pub previous: Values,
+    }
+
+    pub type RevisionOf<T> = Revision<BlockNumberFor<T>, UserProfileDataUpdateOf<T>>;
+
+    /// A revision as one requester may see it, see `ProfileView`.
+    #[derive(Encode, Decode, TypeInfo, RuntimeDebug, Clone, Eq, PartialEq)]
+    pub struct RevisionView<AccountId, BlockNumber> {
+        /// Position in the profile's history, counted from its first revision.
+        pub number  : u32,
+        pub block   : BlockNumber,
+        pub changed : FieldSet,
+        pub previous: ProfileView<AccountId>,
+    }
+
+    /// The x25519 public key of a reader and the encrypted profile fields sealed to it, see
+    /// `EncryptedFields`.
+    pub type EncryptedProfile = ([u8; 32], Vec<(ProfileField, Vec<u8>)>);
+
+    /// Visibility per field; fields missing from the map fall back to the next level.
+    pub type VisibilitySettings = BoundedBTreeMap<ProfileField, Visibility, ConstU32<PROFILE_FIELDS>>;
+
+    /// Visibility changes accepted by `set_visibility` and `set_default_visibility`.
+    pub type VisibilityChanges = BoundedVec<(ProfileField, Visibility), ConstU32<PROFILE_FIELDS>>;
+
+    /// Hash function of a `PictureRef` digest. Append-only like `Province`.
+    #[derive(
+        Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking,
+        Serialize, Deserialize
+    )]
+    #[serde(rename_all = "snake_case")]
+    pub enum HashAlgorithm {
+        #[codec(index = 0)] Blake2_256,
+        #[codec(index = 1)] Sha2_256,
+    }
+
+    impl HashAlgorithm {
+        /// The digest of `data`.
+        pub fn hash(self, data: &[u8]) -> [u8; 32] {
+            match self {
+                Self::Blake2_256 => sp_io::hashing::blake2_256(data),
+                Self::Sha2_256 => sp_io::hashing::sha2_256(data),
+            }
+        }
+    }
+
+    /// Image formats accepted for profile pictures. Append-only like `Province`.
+    #[derive(
+        Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking
+    )]
+    pub enum ImageMime {
+        #[codec(index = 0)] Png,
+        #[codec(index = 1)] Jpeg,
+        #[codec(index = 2)] Webp,
+    }
+
+    impl ImageMime {
+        /// The MIME type, e.g. `image/png`.
+        pub fn as_str(self) -> &'static str {
+            match self {
+                Self::Png => "image/png",
+                Self::Jpeg => "image/jpeg",
+                Self::Webp => "image/webp",
+            }
+        }
+    }
+
+    /// A profile picture by content: the image whose `algorithm` hash is `digest`. The bytes
+    /// live off chain, e.g. in the node's `profilePicture` RPC store, which checks them
+    /// against the digest.
+    #[derive(
+        Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Copy, Eq, PartialEq, DecodeWithMemTracking
+    )]
+    pub struct PictureRef {
+        pub algorithm: HashAlgorithm,
+        pub digest   : [u8; 32],
+        pub mime     : ImageMime,
+        /// Length of the image in bytes, at most `MaxPictureSize`.
+        pub size     : u32,
+    }
+
+    impl PictureRef {
+        /// Whether `data` is the referenced image.
+        pub fn matches(&self, data: &[u8]) -> bool {
+            data.len() == self.size as usize && self.algorithm.hash(data) == self.digest
+        }
+    }
+
     /* -------------------------------------------------
      *  Core data structures
      * ------------------------------------------------- */
-    #[derive(Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Eq, PartialEq)]
-    pub struct UserProfileData {
-        pub f_name      : Str64,
-        pub m_name      : Str64,
-        pub l_name      : Str64,
+    /// Lowering a bound of `Name` or `Text` needs
+    /// `migrations::bounds::TruncateToBounds`; raising one needs nothing.
+    #[derive(Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebugNoBound, CloneNoBound, EqNoBound, PartialEqNoBound)]
+    #[codec(mel_bound())]
+    #[scale_info(skip_type_params(Name, Text))]
+    pub struct UserProfileData<Name: Get<u32>, Text: Get<u32>> {
+        pub f_name      : BoundedVec<u8, Name>,
+        pub m_name      : BoundedVec<u8, Name>,
+        pub l_name      : BoundedVec<u8, Name>,
         pub marital_status: MaritalStatus,
-        pub pf_pic_path : Str128,
+        /// `None` when unset or encrypted, and for profiles `migrations::v3` found with a path.
+        pub picture     : Option<PictureRef>,
         pub gender      : Gender,
         pub blood_group : BloodType,
-        pub nationality : Str64,
-        pub religion    : Str64,
-        pub lit_lang    : Str64,
+        pub nationality : BoundedVec<u8, Text>,
+        pub religion    : BoundedVec<u8, Text>,
+        pub lit_lang    : BoundedVec<u8, Text>,
         pub province    : Province,
         pub district    : District,
-        pub city        : Str64,
-        pub division    : Str64,
+        pub city        : BoundedVec<u8, Text>,
+        pub division    : BoundedVec<u8, Text>,
         pub postal_code : u32,          // 0 == “unset”
-        pub birth_date  : Str64,        // e.g. `YYYY-MM-DD`
+        /// `None` when encrypted, or for profiles whose string birth date could not be parsed
+        /// by `migrations::v2`.
+        pub birth_date  : Option<Date>,
+    }
+
+    #[derive(DefaultNoBound, Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebugNoBound, CloneNoBound, EqNoBound, PartialEqNoBound, DecodeWithMemTracking)]
+    #[codec(mel_bound())]
+    #[scale_info(skip_type_params(Name, Text))]
+    pub struct UserProfileDataUpdate<Name: Get<u32>, Text: Get<u32>> {
+        pub f_name        : Option<BoundedVec<u8, Name>>,
+        pub m_name        : Option<BoundedVec<u8, Name>>,
+        pub l_name        : Option<BoundedVec<u8, Name>>,
+        pub marital_status: Option<MaritalStatus>,
+        /// `Some(None)` removes the picture.
+        pub picture       : Option<Option<PictureRef>>,
+        pub gender        : Option<Gender>,
+        pub blood_group   : Option<BloodType>,
+        pub nationality   : Option<BoundedVec<u8, Text>>,
+        pub religion      : Option<BoundedVec<u8, Text>>,
+        pub lit_lang      : Option<BoundedVec<u8, Text>>,
+        pub province      : Option<Province>,
+        pub district      : Option<District>,
+        pub city          : Option<BoundedVec<u8, Text>>,
+        pub division      : Option<BoundedVec<u8, Text>>,
+        pub postal_code   : Option<u32>,
+        pub birth_date    : Option<Date>,
     }
 
-    #[derive(Default, Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug, Clone, Eq, PartialEq, DecodeWithMemTracking)]
-    pub struct UserProfileDataUpdate {
-        pub f_name        : Option<Str64>,
-        pub m_name        : Option<Str64>,
-        pub l_name        : Option<Str64>,
+    /// A profile as one requester may see it; fields it may not see are `None`.
+    #[derive(Encode, Decode, TypeInfo, RuntimeDebug, Clone, Eq, PartialEq)]
+    pub struct ProfileView<AccountId> {
+        /// The primary account owning the profile.
+        pub account       : AccountId,
+        pub f_name        : Option<String>,
+        pub m_name        : Option<String>,
+        pub l_name        : Option<String>,
         pub marital_status: Option<MaritalStatus>,
-        pub pf_pic_path   : Option<Str128>,
+        pub picture       : Option<PictureRef>,
         pub gender        : Option<Gender>,
         pub blood_group   : Option<BloodType>,
-        pub nationality   : Option<Str64>,
-        pub religion      : Option<Str64>,
-        pub lit_lang      : Option<Str64>,
+        pub nationality   : Option<String>,
+        pub religion      : Option<String>,
+        pub lit_lang      : Option<String>,
         pub province      : Option<Province>,
         pub district      : Option<District>,
-        pub city          : Option<Str64>,
-        pub division      : Option<Str64>,
+        pub city          : Option<String>,
+        pub division      : Option<String>,
         pub postal_code   : Option<u32>,
-        pub birth_date    : Option<Str64>,
+        pub birth_date    : Option<Date>,
+    }
+
+    /// Mixed into every `ReadAuth` signature so it can not stand for anything else.
+    pub const READ_AUTH_CONTEXT: &[u8] = b"profile/read";
+
+    /// A requester's signed statement that it reads profiles until block `valid_until`. Views
+    /// show more than the public fields only to a requester proven this way, see
+    /// `Pallet::authenticated`.
+    #[derive(Encode, Decode, TypeInfo, RuntimeDebug, Clone, Eq, PartialEq)]
+    pub struct ReadAuth<AccountId, BlockNumber, Signature> {
+        pub requester  : AccountId,
+        pub valid_until: BlockNumber,
+        /// Signature of `requester` over `ReadAuth::payload`.
+        pub signature  : Signature,
+    }
+
+    impl<AccountId: Encode, BlockNumber: Encode, Signature> ReadAuth<AccountId, BlockNumber, Signature> {
+        /// The bytes `requester` signs: `(READ_AUTH_CONTEXT, requester, valid_until)`, encoded.
+        pub fn payload(requester: &AccountId, valid_until: &BlockNumber) -> Vec<u8> {
+            (READ_AUTH_CONTEXT, requester, valid_until).encode()
+        }
+    }
+
+    pub type ReadAuthOf<T> =
+        ReadAuth<<T as frame_system::Config>::AccountId, BlockNumberFor<T>, <T as Config>::ReadSignature>;
+
+    impl<Name: Get<u32>, Text: Get<u32>> UserProfileDataUpdate<Name, Text> {
+        /// Byte lengths of the longest name and of the longest other text field supplied,
+        /// `0` when none is. Used for weighing.
+        pub fn lengths(&self) -> (u32, u32) {
+            let names = [&self.f_name, &self.m_name, &self.l_name].map(|f| f.as_ref().map_or(0, |v| v.len() as u32));
+            let texts = [&self.nationality, &self.religion, &self.lit_lang, &self.city, &self.division]
+                .map(|f| f.as_ref().map_or(0, |v| v.len() as u32));
+            (names.into_iter().max().unwrap_or(0), texts.into_iter().max().unwrap_or(0))
+        }
+
+        /// The fields the update sets.
+        pub fn fields(&self) -> FieldSet {
+            [
+                (ProfileField::FirstName, self.f_name.is_some()),
+                (ProfileField::MiddleName, self.m_name.is_some()),
+                (ProfileField::LastName, self.l_name.is_some()),
+                (ProfileField::MaritalStatus, self.marital_status.is_some()),
+                (ProfileField::Picture, self.picture.is_some()),
+                (ProfileField::Gender, self.gender.is_some()),
+                (ProfileField::BloodGroup, self.blood_group.is_some()),
+                (ProfileField::Nationality, self.nationality.is_some()),
+                (ProfileField::Religion, self.religion.is_some()),
+                (ProfileField::Language, self.lit_lang.is_some()),
+                (ProfileField::Province, self.province.is_some()),
+                (ProfileField::District, self.district.is_some()),
+                (ProfileField::City, self.city.is_some()),
+                (ProfileField::Division, self.division.is_some()),
+                (ProfileField::PostalCode, self.postal_code.is_some()),
+                (ProfileField::BirthDate, self.birth_date.is_some()),
+            ]
+            .into_iter()
+            .filter_map(|(field, set)| set.then_some(field))
+            .collect()
+        }
+
+        /// Unset `field`.
+        pub fn forget(&mut self, field: ProfileField) {
+            match field {
+                ProfileField::FirstName => self.f_name = None,
+                ProfileField::MiddleName => self.m_name = None,
+                ProfileField::LastName => self.l_name = None,
+                ProfileField::MaritalStatus => self.marital_status = None,
+                ProfileField::Picture => self.picture = None,
+                ProfileField::Gender => self.gender = None,
+                ProfileField::BloodGroup => self.blood_group = None,
+                ProfileField::Nationality => self.nationality = None,
+                ProfileField::Religion => self.religion = None,
+                ProfileField::Language => self.lit_lang = None,
+                ProfileField::Province => self.province = None,
+                ProfileField::District => self.district = None,
+                ProfileField::City => self.city = None,
+                ProfileField::Division => self.division = None,
+                ProfileField::PostalCode => self.postal_code = None,
+                ProfileField::BirthDate => self.birth_date = None,
+            }
+        }
+    }
+
+    impl<Name: Get<u32>, Text: Get<u32>> UserProfileData<Name, Text> {
+        /// Replace the plaintext of `field` by its `Withheld`, empty or unset value.
+        pub fn withhold(&mut self, field: ProfileField) {
+            match field {
+                ProfileField::FirstName => self.f_name = Default::default(),
+                ProfileField::MiddleName => self.m_name = Default::default(),
+                ProfileField::LastName => self.l_name = Default::default(),
+                ProfileField::MaritalStatus => self.marital_status = MaritalStatus::Withheld,
+                ProfileField::Picture => self.picture = None,
+                ProfileField::Gender => self.gender = Gender::Withheld,
+                ProfileField::BloodGroup => self.blood_group = BloodType::Withheld,
+                ProfileField::Nationality => self.nationality = Default::default(),
+                ProfileField::Religion => self.religion = Default::default(),
+                ProfileField::Language => self.lit_lang = Default::default(),
+                ProfileField::City => self.city = Default::default(),
+                ProfileField::Division => self.division = Default::default(),
+                ProfileField::PostalCode => self.postal_code = 0,
+                ProfileField::BirthDate => self.birth_date = None,
+                ProfileField::Province | ProfileField::District => {},
+            }
+        }
+
+        /// The fields whose value differs from `old`.
+        pub fn changed_from(&self, old: &Self) -> FieldSet {
+            [
+                (ProfileField::FirstName, self.f_name != old.f_name),
+                (ProfileField::MiddleName, self.m_name != old.m_name),
+                (ProfileField::LastName, self.l_name != old.l_name),
+                (ProfileField::MaritalStatus, self.marital_status != old.marital_status),
+                (ProfileField::Picture, self.picture != old.picture),
+                (ProfileField::Gender, self.gender != old.gender),
+                (ProfileField::BloodGroup, self.blood_group != old.blood_group),
+                (ProfileField::Nationality, self.nationality != old.nationality),
+                (ProfileField::Religion, self.religion != old.religion),
+                (ProfileField::Language, self.lit_lang != old.lit_lang),
+                (ProfileField::Province, self.province != old.province),
+                (ProfileField::District, self.district != old.district),
+                (ProfileField::City, self.city != old.city),
+                (ProfileField::Division, self.division != old.division),
+                (ProfileField::PostalCode, self.postal_code != old.postal_code),
+                (ProfileField::BirthDate, self.birth_date != old.birth_date),
+            ]
+            .into_iter()
+            .filter_map(|(field, changed)| changed.then_some(field))
+            .collect()
+        }
+
+        /// An update setting `fields` to their values in this profile.
+        pub fn values(&self, fields: FieldSet) -> UserProfileDataUpdate<Name, Text> {
+            let has = |field| fields.contains(field);
+            UserProfileDataUpdate {
+                f_name        : has(ProfileField::FirstName).then(|| self.f_name.clone()),
+                m_name        : has(ProfileField::MiddleName).then(|| self.m_name.clone()),
+                l_name        : has(ProfileField::LastName).then(|| self.l_name.clone()),
+                marital_status: has(ProfileField::MaritalStatus).then_some(self.marital_status),
+                picture       : has(ProfileField::Picture).then_some(self.picture),
+                gender        : has(ProfileField::Gender).then_some(self.gender),
+                blood_group   : has(ProfileField::BloodGroup).then_some(self.blood_group),
+                nationality   : has(ProfileField::Nationality).then(|| self.nationality.clone()),
+                religion      : has(ProfileField::Religion).then(|| self.religion.clone()),
+                lit_lang      : has(ProfileField::Language).then(|| self.lit_lang.clone()),
+                province      : has(ProfileField::Province).then_some(self.province),
+                district      : has(ProfileField::District).then_some(self.district),
+                city          : has(ProfileField::City).then(|| self.city.clone()),
+                division      : has(ProfileField::Division).then(|| self.division.clone()),
+                postal_code   : has(ProfileField::PostalCode).then_some(self.postal_code),
+                birth_date    : self.birth_date.filter(|_| has(ProfileField::BirthDate)),
+            }
+        }
     }
 
+    pub type UserProfileDataOf<T> =
+        UserProfileData<<T as Config>::MaxNameLen, <T as Config>::MaxTextLen>;
+    pub type UserProfileDataUpdateOf<T> =
+        UserProfileDataUpdate<<T as Config>::MaxNameLen, <T as Config>::MaxTextLen>;
+
     /* -------------------------------------------------
      *  Storage
      * ------------------------------------------------- */
     #[pallet::storage]
     #[pallet::getter(fn profile_of)]
     pub type Profiles<T: Config> =
-        StorageMap<_, Blake2_128, T::AccountId, UserProfileData, OptionQuery>;
+        CountedStorageMap<_, Blake2_128Concat, T::AccountId, UserProfileDataOf<T>, OptionQuery>;
+
+    /// Profiles whose user record was removed. Not served by `ProfileApi`.
+    #[pallet::storage]
+    pub type ArchivedProfiles<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, UserProfileDataOf<T>, OptionQuery>;
+
+    /// Per-profile visibility, overriding `DefaultVisibility` field by field.
+    #[pallet::storage]
+    pub type FieldVisibility<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, VisibilitySettings, ValueQuery>;
+
+    /// Visibility of the fields a profile does not override, set by `AdminOrigin`. Fields
+    /// missing here too are `Public`.
+    #[pallet::storage]
+    pub type DefaultVisibility<T: Config> = StorageValue<_, VisibilitySettings, ValueQuery>;
+
+    /// Sharing grants by profile owner, at most one per grantee. Expired grants stop being
+    /// served right away and are removed in `on_idle`.
+    #[pallet::storage]
+    pub type Grants<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<GrantOf<T>, T::MaxGrants>, ValueQuery>;
+
+    /// x25519 public keys of owners that encrypt fields and of grantees reading them. Each
+    /// account registers its own key; nobody shares a secret key.
+    #[pallet::storage]
+    pub type EncryptionKeys<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, [u8; 32], OptionQuery>;
+
+    /// Encrypted profile fields; the plaintext field holds its `withhold` value meanwhile.
+    ///
+    /// Each copy is sealed by the client to its reader's `EncryptionKeys` entry:
+    /// `ephemeral_public (32) ++ ChaCha20-Poly1305(plaintext) ++ tag (16)`, with key
+    /// `blake2_256(x25519(ephemeral, reader) ++ ephemeral_public ++ reader_public)`, an all-zero
+    /// nonce and the SCALE-encoded `ProfileField` as associated data. The plaintext is the
+    /// SCALE encoding of the field's value, `Vec<u8>` for text fields and `PictureRef` for the
+    /// picture. A grantee's copy is dropped with its grant of the field.
+    #[pallet::storage]
+    pub type EncryptedFields<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Twox64Concat,
+        ProfileField,
+        SealedFieldOf<T>,
+        OptionQuery,
+    >;
+
+    /// Last owner whose grants `on_idle` swept, to resume from in the next block.
+    #[pallet::storage]
+    pub type GrantsCursor<T: Config> = StorageValue<_, T::AccountId, OptionQuery>;
+
+    /// Past changes of each profile by revision number, at most `MaxRevisions` per profile.
+    /// Only `update_profile` records revisions; encrypting a field redacts its past values.
+    #[pallet::storage]
+    pub type Revisions<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Twox64Concat, u32, RevisionOf<T>, OptionQuery>;
+
+    /// Number of the oldest kept and of the next revision of each profile with a history.
+    /// Numbers restart from 0 once every revision is pruned.
+    #[pallet::storage]
+    pub type RevisionRange<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (u32, u32), ValueQuery>;
+
+    /// How many blocks revisions are kept, set by `AdminOrigin`; `None` keeps them until
+    /// `MaxRevisions` newer ones push them out. Older revisions are pruned in `on_idle`.
+    #[pallet::storage]
+    pub type HistoryRetention<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Last profile whose history `on_idle` pruned, to resume from in the next block.
+    #[pallet::storage]
+    pub type HistoryCursor<T: Config> = StorageValue<_, T::AccountId, OptionQuery>;
+
+    /// Accounts allowed to judge profile fields, managed by `AdminOrigin`.
+    #[pallet::storage]
+    pub type Verifiers<T: Config> = StorageValue<_, BoundedVec<T::AccountId, T::MaxVerifiers>, ValueQuery>;
+
+    /// Judgements of each profile field, at most one per verifier. `update_profile` drops those
+    /// of the fields it writes. Judgements of accounts removed from `Verifiers` are ignored and
+    /// dropped when the field is next judged.
+    #[pallet::storage]
+    pub type Judgements<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Twox64Concat,
+        ProfileField,
+        BoundedVec<(T::AccountId, Judgement), T::MaxVerifiers>,
+        ValueQuery,
+    >;
 
     /* -------------------------------------------------
      *  Errors / Events
@@ -121,9 +797,61 @@ pub mod pallet {
     pub enum Error<T> {
         DuplicateProfile,
         ProfileNotFound,
+        /// Not a real calendar date, or a date in the future.
         InvalidBirthDate,
-        StringTooLong,
-        UserNotRegistered
+        /// A name field is not valid UTF-8, longer than its bound, or has characters other
+        /// than letters, spaces, `-`, `'` and `.`.
+        InvalidFirstName,
+        InvalidMiddleName,
+        InvalidLastName,
+        InvalidNationality,
+        InvalidReligion,
+        InvalidLanguage,
+        /// The picture is empty or larger than `MaxPictureSize`.
+        InvalidPicture,
+        /// A place field is not valid UTF-8, longer than its bound, or has control characters.
+        InvalidCity,
+        InvalidDivision,
+        /// Sri Lankan postal codes have at most five digits.
+        InvalidPostalCode,
+        /// The district does not lie in the province.
+        DistrictNotInProvince,
+        /// The owner already shares with `MaxGrants` accounts.
+        TooManyGrants,
+        /// A grant lasts at least one block and at most `MaxGrantDuration`.
+        InvalidGrantDuration,
+        /// A grant names at least one field.
+        NoFieldsGranted,
+        /// Owners always see their own profile.
+        CannotGrantSelf,
+        /// The owner does not share with that account.
+        GrantNotFound,
+        /// The profile owner or a reader of an encrypted field has not registered an
+        /// encryption key.
+        NoEncryptionKey,
+        /// Province and district cannot be encrypted.
+        FieldNotEncryptable,
+        /// Shorter than `CIPHERTEXT_OVERHEAD`.
+        InvalidCiphertext,
+        /// An encrypted field is sealed to an account the owner does not share it with.
+        FieldNotShared,
+        /// An encrypted field is sealed twice to the same reader.
+        DuplicateReader,
+        /// `Verifiers` already holds `MaxVerifiers` accounts.
+        TooManyVerifiers,
+        AlreadyVerifier,
+        /// The account is not in `Verifiers`.
+        NotVerifier,
+        /// Verifiers cannot judge their own profile.
+        CannotJudgeSelf,
+        UserNotRegistered,
+        /// The user record is pending, suspended or deceased.
+        UserNotActive,
+        /// The recovery target holds the archived profile of a removed user, which the move
+        /// would mix with the recovered one.
+        ArchivedProfileExists,
+        /// The call is deprecated and no longer does anything; see its documentation.
+        Deprecated,
     }
 
     #[pallet::event]
@@ -132,62 +860,504 @@ pub mod pallet {
         ProfileCreated { account: T::AccountId },
         ProfileUpdated { account: T::AccountId },
         ProfileRemoved { account: T::AccountId },
-        ProfileDataFetched {
-            account : T::AccountId,
-            f_name      : String,
-            m_name      : String,
-            l_name      : String,
-            marital_status: MaritalStatus,
-            pf_pic_path : String,
-            gender      : Gender,
-            blood_group : BloodType,
-            nationality : String,
-            religion    : String,
-            lit_lang    : String,
-            province    : Province,
-            district    : District,
-            city        : String,
-            division    : String,
-            postal_code : u32,          // 0 == “unset”
-            birth_date  : String,
+        /// The owning user was removed and the profile moved to `ArchivedProfiles`.
+        ProfileArchived { account: T::AccountId },
+        /// The user registered again and the archived profile was restored.
+        ProfileRestored { account: T::AccountId },
+        /// An archived profile was erased with everything kept along with it.
+        ArchivedProfilePurged { account: T::AccountId },
+        /// The owning user was recovered to a new account and the profile moved with it.
+        ProfileMoved { from: T::AccountId, to: T::AccountId },
+        /// The owner changed the visibility of some of its profile fields.
+        VisibilityChanged { account: T::AccountId },
+        /// Governance changed the default visibility of some profile fields.
+        DefaultVisibilityChanged,
+        /// `owner` shares `fields` with `grantee` until `expires_at`, replacing any earlier
+        /// grant to it.
+        AccessGranted {
+            owner: T::AccountId,
+            grantee: T::AccountId,
+            fields: FieldSet,
+            expires_at: BlockNumberFor<T>,
         },
+        /// `owner` stopped sharing with `grantee`.
+        AccessRevoked { owner: T::AccountId, grantee: T::AccountId },
+        /// A grant ran out and was removed.
+        AccessExpired { owner: T::AccountId, grantee: T::AccountId },
+        /// The profile registered or replaced its encryption key.
+        EncryptionKeySet { account: T::AccountId },
+        /// A field is now stored encrypted.
+        FieldEncrypted { account: T::AccountId, field: ProfileField },
+        /// Governance changed how long profile revisions are kept.
+        HistoryRetentionSet { retention: Option<BlockNumberFor<T>> },
+        VerifierAdded { verifier: T::AccountId },
+        VerifierRemoved { verifier: T::AccountId },
+        /// `verifier` judged `field` of the profile of `owner`, replacing its earlier judgement.
+        FieldJudged { owner: T::AccountId, field: ProfileField, verifier: T::AccountId, judgement: Judgement },
+        /// An update of the profile dropped the judgements of `fields`.
+        JudgementsInvalidated { account: T::AccountId, fields: FieldSet },
+    }
+
+    /* -------------------------------------------------
+     *  Hooks
+     * ------------------------------------------------- */
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        /// Carry on the v0 -> v1 re-keying, a bounded number of accounts per block.
+        fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+            migrations::v1::step::<T>(migrations::v1::ACCOUNTS_PER_STEP)
+        }
+
+        /// Remove expired grants, then revisions older than `HistoryRetention`, one profile at a
+        /// time, as far as the block's spare weight allows.
+        fn on_idle(now: BlockNumberFor<T>, limit: Weight) -> Weight {
+            let used = Self::sweep_grants(now, limit);
+            used.saturating_add(Self::prune_history(now, limit.saturating_sub(used)))
+        }
     }
 
     /* -------------------------------------------------
      *  Internal helpers
      * ------------------------------------------------- */
     impl<T: Config> Pallet<T> {
-    /// Very light `YYYY-MM-DD` check
-    fn is_valid_date(date: &str) -> bool {
-            // Cheap ASCII-only test; works as long as you guarantee the input
-            // is plain ASCII (that’s true for the bounded-string you built).
-            date.len() == 10
-                && &date[4..5] == "-"        // YYYY-**-DD
-                && &date[7..8] == "-"        // YYYY-MM-**
-                // all other chars must be digits
-                && date
-                    .chars()
-                    .enumerate()
-                    .all(|(i, c)| matches!(i, 4 | 7) || c.is_ascii_digit())
+    /// Up to `limit` (at most `MAX_PAGE_LEN`) accounts with a profile, in storage order,
+    /// starting after `start_after`.
+    pub fn profiles_page(start_after: Option<T::AccountId>, limit: u32) -> Vec<T::AccountId> {
+            let entries = match start_after {
+                Some(account) => Profiles::<T>::iter_from(Profiles::<T>::hashed_key_for(account)),
+                None => Profiles::<T>::iter(),
+            };
+            entries.map(|(account, _)| account).take(limit.min(MAX_PAGE_LEN) as usize).collect()
+        }
+
+    /// Validate and normalise a text field with `user::text`, reporting `err` on failure.
+    fn text<S: Get<u32>>(raw: &[u8], class: CharClass, err: Error<T>) -> Result<BoundedVec<u8, S>, Error<T>> {
+            text::normalize(raw, class).map_err(|_| err)
+        }
+
+    /// Byte length of the longest of `texts`, for weighing `create_profile`.
+    fn longest(texts: &[&String]) -> u32 {
+            texts.iter().map(|t| t.len() as u32).max().unwrap_or(0)
+        }
+
+    /// Apply `changes` to `settings`; a field listed twice takes its last visibility.
+    fn apply_visibility(settings: &mut VisibilitySettings, changes: VisibilityChanges) {
+            for (field, visibility) in changes {
+                // at most one entry per field, which is what the bound allows
+                let _ = settings.try_insert(field, visibility).defensive_proof("one entry per field fits");
+            }
+        }
+
+    /// The requester `auth` proves: it signed `auth`, and the block `auth` is valid until is
+    /// neither past nor more than `MaxReadAuthAge` ahead. `None` otherwise, which the views
+    /// treat like an anonymous requester.
+    pub fn authenticated(auth: &ReadAuthOf<T>) -> Option<T::AccountId> {
+            let now = frame_system::Pallet::<T>::block_number();
+            let fresh = auth.valid_until >= now
+                && auth.valid_until <= now.saturating_add(T::MaxReadAuthAge::get());
+            let payload = ReadAuthOf::<T>::payload(&auth.requester, &auth.valid_until);
+            (fresh && auth.signature.verify(&payload[..], &auth.requester)).then(|| auth.requester.clone())
+        }
+
+    /// Whether `requester` may read a field of `owner`'s profile with a given visibility.
+    fn may_see(owner: &T::AccountId, requester: Option<&T::AccountId>) -> impl Fn(Visibility) -> bool {
+            let is_owner = requester.is_some_and(|r| T::UserPallet::primary_account(r) == *owner);
+            let is_registrar = requester.is_some_and(T::UserPallet::is_registrar);
+            move |visibility| match visibility {
+                Visibility::Public => true,
+                Visibility::Private => is_owner,
+                Visibility::RegistrarsOnly => is_owner || is_registrar,
+            }
+        }
+
+    /// Fields `owner` currently shares with `requester`.
+    pub fn granted_fields(owner: &T::AccountId, requester: &T::AccountId) -> FieldSet {
+            let now = frame_system::Pallet::<T>::block_number();
+            Grants::<T>::get(owner)
+                .into_iter()
+                .find(|grant| grant.grantee == *requester && grant.expires_at > now)
+                .map_or_else(FieldSet::default, |grant| grant.fields)
+        }
+
+    /// The live profile of `who` (resolved to its primary) with every field `requester`
+    /// may not see left out; `None` requests the public fields only. Shared fields are
+    /// shown whatever their visibility. `requester` is trusted: callers serving outside
+    /// requests must prove it with `authenticated` first.
+    pub fn profile_view(
+            who: &T::AccountId,
+            requester: Option<&T::AccountId>,
+        ) -> Option<ProfileView<T::AccountId>> {
+            let owner = T::UserPallet::primary_account(who);
+            let visible = Self::visible_to(&owner, requester);
+            Self::view(owner, visible)
+        }
+
+    /// Whether `requester` may read a field of `owner`'s profile, by its visibility or a
+    /// grant.
+    fn visible_to(owner: &T::AccountId, requester: Option<&T::AccountId>) -> impl Fn(ProfileField) -> bool {
+            let (own, defaults) = (FieldVisibility::<T>::get(owner), DefaultVisibility::<T>::get());
+            let granted = requester.map_or_else(FieldSet::default, |r| Self::granted_fields(owner, r));
+            let may_see = Self::may_see(owner, requester);
+            move |field| {
+                granted.contains(field)
+                    || may_see(own.get(&field).or(defaults.get(&field)).copied().unwrap_or_default())
+            }
+        }
+
+    /// Up to `limit` (at most `MAX_PAGE_LEN`) revisions of the live profile of `who`
+    /// (resolved to its primary) numbered below `before`, newest first. Fields `requester`
+    /// may not see in `profile_view` are left out of `changed` and `previous`. `requester` is
+    /// trusted like in `profile_view`.
+    pub fn profile_history(
+            who: &T::AccountId,
+            requester: Option<&T::AccountId>,
+            before: Option<u32>,
+            limit: u32,
+        ) -> Vec<RevisionView<T::AccountId, BlockNumberFor<T>>> {
+            let owner = T::UserPallet::primary_account(who);
+            if !Profiles::<T>::contains_key(&owner) {
+                return Vec::new();
+            }
+            let shown = Self::shown_fields(&owner, Self::visible_to(&owner, requester));
+
+            let (first, next) = RevisionRange::<T>::get(&owner);
+            let end = before.map_or(next, |before| before.min(next));
+            (first..end)
+                .rev()
+                .take(limit.min(MAX_PAGE_LEN) as usize)
+                .filter_map(|number| {
+                    let revision = Revisions::<T>::get(&owner, number)?;
+                    let changed = FieldSet(revision.changed.0 & shown.0);
+                    Some(RevisionView {
+                        number,
+                        block: revision.block,
+                        changed,
+                        previous: Self::values_view(owner.clone(), &revision.previous, changed),
+                    })
+                })
+                .collect()
+        }
+
+    /// Only the fields of `owner`'s live profile that it currently shares with `requester`;
+    /// `None` without such a grant. `requester` is trusted like in `profile_view`.
+    pub fn shared_profile(
+            owner: &T::AccountId,
+            requester: &T::AccountId,
+        ) -> Option<ProfileView<T::AccountId>> {
+            let owner = T::UserPallet::primary_account(owner);
+            let granted = Self::granted_fields(&owner, requester);
+            if granted.is_empty() {
+                return None;
+            }
+            Self::view(owner, |field| granted.contains(field))
+        }
+
+    /// The live profile of `owner` with encrypted fields and those `show` refuses left out.
+    fn view(owner: T::AccountId, show: impl Fn(ProfileField) -> bool) -> Option<ProfileView<T::AccountId>> {
+            let profile = Profiles::<T>::get(&owner)?;
+            let shown = Self::shown_fields(&owner, show);
+            Some(Self::values_view(owner, &profile.values(shown), shown))
+        }
+
+    /// The fields `show` accepts, encrypted fields of `owner` excluded.
+    fn shown_fields(owner: &T::AccountId, show: impl Fn(ProfileField) -> bool) -> FieldSet {
+            let encrypted: FieldSet = EncryptedFields::<T>::iter_key_prefix(owner).collect();
+            FieldSet::ALL.iter().filter(|field| !encrypted.contains(*field) && show(*field)).collect()
+        }
+
+    /// The `shown` fields set in `values`, as a view of `owner`'s profile.
+    fn values_view(
+            owner: T::AccountId,
+            values: &UserProfileDataUpdateOf<T>,
+            shown: FieldSet,
+        ) -> ProfileView<T::AccountId> {
+            let show = |field| shown.contains(field);
+            let text = |field, raw: Option<&Vec<u8>>| {
+                raw.filter(|_| show(field)).map(|raw| String::from_utf8_lossy(raw).into_owned())
+            };
+
+            ProfileView {
+                f_name        : text(ProfileField::FirstName, values.f_name.as_deref()),
+                m_name        : text(ProfileField::MiddleName, values.m_name.as_deref()),
+                l_name        : text(ProfileField::LastName, values.l_name.as_deref()),
+                marital_status: values.marital_status.filter(|_| show(ProfileField::MaritalStatus)),
+                picture       : values.picture.flatten().filter(|_| show(ProfileField::Picture)),
+                gender        : values.gender.filter(|_| show(ProfileField::Gender)),
+                blood_group   : values.blood_group.filter(|_| show(ProfileField::BloodGroup)),
+                nationality   : text(ProfileField::Nationality, values.nationality.as_deref()),
+                religion      : text(ProfileField::Religion, values.religion.as_deref()),
+                lit_lang      : text(ProfileField::Language, values.lit_lang.as_deref()),
+                province      : values.province.filter(|_| show(ProfileField::Province)),
+                district      : values.district.filter(|_| show(ProfileField::District)),
+                city          : text(ProfileField::City, values.city.as_deref()),
+                division      : text(ProfileField::Division, values.division.as_deref()),
+                postal_code   : values.postal_code.filter(|_| show(ProfileField::PostalCode)),
+                birth_date    : values.birth_date.filter(|_| show(ProfileField::BirthDate)),
+                account       : owner,
+            }
+        }
+
+    /// The judgements of the live profile of `who` (resolved to its primary) by current
+    /// verifiers, by field; fields nobody judged are left out.
+    pub fn verification(who: &T::AccountId) -> Verification<T::AccountId> {
+            let owner = T::UserPallet::primary_account(who);
+            if !Profiles::<T>::contains_key(&owner) {
+                return Vec::new();
+            }
+            let verifiers = Verifiers::<T>::get();
+            let mut fields: Verification<T::AccountId> = Judgements::<T>::iter_prefix(&owner)
+                .map(|(field, judgements)| {
+                    (field, judgements.into_iter().filter(|(v, _)| verifiers.contains(v)).collect::<Vec<_>>())
+                })
+                .filter(|(_, judgements)| !judgements.is_empty())
+                .collect();
+            fields.sort_by_key(|(field, _)| *field);
+            fields
+        }
+
+    /// The encryption key of `reader` and the copies of the encrypted fields of `owner` sealed
+    /// to it: every field for the owner, the fields shared by a live grant for anyone else.
+    /// `None` if `reader` has no key.
+    pub fn encrypted_fields(owner: &T::AccountId, reader: &T::AccountId) -> Option<EncryptedProfile> {
+            let owner = T::UserPallet::primary_account(owner);
+            let public = EncryptionKeys::<T>::get(T::UserPallet::primary_account(reader))?;
+            let fields = if T::UserPallet::primary_account(reader) == owner {
+                EncryptedFields::<T>::iter_prefix(&owner).map(|(f, sealed)| (f, sealed.own.into_inner())).collect()
+            } else {
+                let granted = Self::granted_fields(&owner, reader);
+                EncryptedFields::<T>::iter_prefix(&owner)
+                    .filter(|(field, _)| granted.contains(*field))
+                    .filter_map(|(field, sealed)| {
+                        let (_, blob) = sealed.shared.into_iter().find(|(r, _)| r == reader)?;
+                        Some((field, blob.into_inner()))
+                    })
+                    .collect()
+            };
+            Some((public, fields))
+        }
+
+    /// Drop the copies of the encrypted fields of `owner` that `keep` refuses, by reader and
+    /// field.
+    fn drop_copies(owner: &T::AccountId, keep: impl Fn(&T::AccountId, ProfileField) -> bool) {
+            let sealed: Vec<_> = EncryptedFields::<T>::iter_prefix(owner).collect();
+            for (field, mut sealed) in sealed {
+                let before = sealed.shared.len();
+                sealed.shared.retain(|(reader, _)| keep(reader, field));
+                if sealed.shared.len() != before {
+                    EncryptedFields::<T>::insert(owner, field, sealed);
+                }
+            }
+        }
+
+    /// Redact `field` from the history of `owner`.
+    pub(crate) fn redact_history(owner: &T::AccountId, field: ProfileField) {
+            let (first, next) = RevisionRange::<T>::get(owner);
+            for number in first..next {
+                let Some(mut revision) = Revisions::<T>::get(owner, number) else { continue };
+                if revision.changed.contains(field) {
+                    revision.changed.remove(field);
+                    revision.previous.forget(field);
+                    Revisions::<T>::insert(owner, number, revision);
+                }
+            }
+        }
+
+    fn clear_encryption(owner: &T::AccountId) {
+            EncryptionKeys::<T>::remove(owner);
+            let _ = EncryptedFields::<T>::clear_prefix(owner, PROFILE_FIELDS, None);
+        }
+
+    fn ensure_district_in(province: Province, district: District) -> Result<(), Error<T>> {
+            ensure!(district.province() == province, Error::<T>::DistrictNotInProvince);
+            Ok(())
+        }
+
+    fn ensure_postal_code(code: u32) -> Result<(), Error<T>> {
+            ensure!(code <= MAX_POSTAL_CODE, Error::<T>::InvalidPostalCode);
+            Ok(())
+        }
+
+    fn ensure_picture(picture: &PictureRef) -> Result<(), Error<T>> {
+            ensure!((1..=T::MaxPictureSize::get()).contains(&picture.size), Error::<T>::InvalidPicture);
+            Ok(())
+        }
+
+    /// Today's date according to `T::UnixTime`.
+    pub fn today() -> Date {
+            Date::from_unix_millis(T::UnixTime::now().as_millis() as u64)
+        }
+
+    /// A real calendar date no later than today.
+    pub fn is_valid_birth_date(date: &Date) -> bool {
+            date.is_valid() && *date <= Self::today()
+        }
+
+    /// Append a revision to the history of `owner`, dropping the oldest beyond `MaxRevisions`.
+    fn record_revision(owner: &T::AccountId, changed: FieldSet, previous: UserProfileDataUpdateOf<T>) {
+            let (mut first, next) = RevisionRange::<T>::get(owner);
+            let block = frame_system::Pallet::<T>::block_number();
+            Revisions::<T>::insert(owner, next, Revision { block, changed, previous });
+            let next = next.saturating_add(1);
+            while next.saturating_sub(first) > T::MaxRevisions::get() {
+                Revisions::<T>::remove(owner, first);
+                first += 1;
+            }
+            RevisionRange::<T>::insert(owner, (first, next));
+        }
+
+    fn clear_history(owner: &T::AccountId) {
+            RevisionRange::<T>::remove(owner);
+            let _ = Revisions::<T>::clear_prefix(owner, T::MaxRevisions::get(), None);
+        }
+
+    /// Remove what is kept along with a profile of `owner`: visibility, encryption, history
+    /// and judgements.
+    fn clear_profile_data(owner: &T::AccountId) {
+            FieldVisibility::<T>::remove(owner);
+            Self::clear_encryption(owner);
+            Self::clear_history(owner);
+            let _ = Judgements::<T>::clear_prefix(owner, PROFILE_FIELDS, None);
+        }
+
+    fn purge_archive(owner: T::AccountId) -> DispatchResult {
+            ensure!(ArchivedProfiles::<T>::take(&owner).is_some(), Error::<T>::ProfileNotFound);
+            Self::clear_profile_data(&owner);
+            Self::deposit_event(Event::ArchivedProfilePurged { account: owner });
+            Ok(())
+        }
+
+    /// Remove expired grants, one owner at a time; the sweep resumes from `GrantsCursor` in
+    /// the next block.
+    fn sweep_grants(now: BlockNumberFor<T>, limit: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            // per owner: read and rewrite its grants, one event per expired grant, read and
+            // rewrite its encrypted fields to drop the expired grantees' copies
+            let fields = u64::from(PROFILE_FIELDS);
+            let per_owner = db.reads_writes(1 + fields, 1 + u64::from(T::MaxGrants::get()) + fields);
+            // read and write the cursor
+            let mut used = db.reads_writes(1, 1);
+            if limit.any_lt(used.saturating_add(per_owner)) {
+                return Weight::zero();
+            }
+
+            let mut owners = match GrantsCursor::<T>::get() {
+                Some(last) => Grants::<T>::iter_from(Grants::<T>::hashed_key_for(last)),
+                None => Grants::<T>::iter(),
+            };
+            let mut last = None;
+            while !limit.any_lt(used.saturating_add(per_owner)) {
+                let Some((owner, mut grants)) = owners.next() else {
+                    last = None;
+                    break;
+                };
+                used.saturating_accrue(per_owner);
+
+                let before = grants.len();
+                grants.retain(|grant| {
+                    let live = grant.expires_at > now;
+                    if !live {
+                        Self::deposit_event(Event::AccessExpired {
+                            owner: owner.clone(),
+                            grantee: grant.grantee.clone(),
+                        });
+                    }
+                    live
+                });
+                if grants.len() != before {
+                    Self::drop_copies(&owner, |reader, _| grants.iter().any(|g| g.grantee == *reader));
+                }
+                if grants.is_empty() {
+                    Grants::<T>::remove(&owner);
+                } else if grants.len() != before {
+                    Grants::<T>::insert(&owner, grants);
+                }
+                last = Some(owner);
+            }
+            GrantsCursor::<T>::set(last);
+            used
+        }
+
+    /// Remove revisions older than `HistoryRetention`, one profile at a time; the pruning
+    /// resumes from `HistoryCursor` in the next block.
+    fn prune_history(now: BlockNumberFor<T>, limit: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            // per profile: read its range, the expired revisions and the first kept one,
+            // remove the expired ones and rewrite the range
+            let revisions = u64::from(T::MaxRevisions::get());
+            let per_profile = db.reads_writes(2 + revisions, 1 + revisions);
+            // read the retention, read and write the cursor
+            let mut used = db.reads_writes(2, 1);
+            if limit.any_lt(used.saturating_add(per_profile)) {
+                return Weight::zero();
+            }
+            let Some(retention) = HistoryRetention::<T>::get() else {
+                return db.reads(1);
+            };
+
+            let oldest_kept = now.saturating_sub(retention);
+            let mut profiles = match HistoryCursor::<T>::get() {
+                Some(last) => RevisionRange::<T>::iter_from(RevisionRange::<T>::hashed_key_for(last)),
+                None => RevisionRange::<T>::iter(),
+            };
+            let mut last = None;
+            while !limit.any_lt(used.saturating_add(per_profile)) {
+                let Some((owner, (mut first, next))) = profiles.next() else {
+                    last = None;
+                    break;
+                };
+                used.saturating_accrue(per_profile);
+
+                let start = first;
+                while first < next && Revisions::<T>::get(&owner, first).is_none_or(|r| r.block < oldest_kept) {
+                    Revisions::<T>::remove(&owner, first);
+                    first += 1;
+                }
+                if first == next {
+                    RevisionRange::<T>::remove(&owner);
+                } else if first != start {
+                    RevisionRange::<T>::insert(&owner, (first, next));
+                }
+                last = Some(owner);
+            }
+            HistoryCursor::<T>::set(last);
+            used
+        }
+    }
+
+    /* -------------------------------------------------
+     *  View functions – free, event-less reads
+     * ------------------------------------------------- */
+    #[pallet::view_functions_experimental]
+    impl<T: Config> Pallet<T> {
+        /// The public fields of the live profile of `who`, or of its primary account if `who`
+        /// is linked; archived profiles are not returned. Anyone can call this, so it shows
+        /// what `profile_view` shows an anonymous requester; `ProfileApi` serves more to a
+        /// requester that signs a `ReadAuth`.
+        pub fn profile_details(who: T::AccountId) -> Option<ProfileView<T::AccountId>> {
+            Self::profile_view(&who, None)
         }
     }
 
     /* -------------------------------------------------
      *  Dispatchables
      * ------------------------------------------------- */
+    /// Every dispatchable acts on the profile of the signer's primary account, so all linked
+    /// wallets of a user manage the same profile.
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         /// Create a fresh profile (fails if one already exists).
         #[pallet::call_index(1)]
-        #[pallet::weight(Weight::default())]
-        #[allow(clippy::too_many_arguments)]
+        #[pallet::weight(T::WeightInfo::create_profile(
+            Pallet::<T>::longest(&[f_name, m_name, l_name]),
+            Pallet::<T>::longest(&[nationality, religion, lit_lang, city, division]),
+        ))]
         pub fn create_profile(
             origin              : OriginFor<T>,
             f_name              : String,
             m_name              : String,
             l_name              : String,
             marital_status      : MaritalStatus,
-            pf_pic_path         : String,
+            picture             : Option<PictureRef>,
             gender              : Gender,
             blood_group         : BloodType,
             nationality         : String,
@@ -198,43 +1368,32 @@ pub mod pallet {
             city                : String,
             division            : String,
             postal_code         : u32,
-            birth_date          : String,
+            birth_date          : Date,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+            let who = T::UserPallet::primary_account(&ensure_signed(origin)?);
 
             ensure!(
                 <T as Config>::UserPallet::user_exists(&who),
                 Error::<T>::UserNotRegistered
             );
+            ensure!(<T as Config>::UserPallet::is_active(&who), Error::<T>::UserNotActive);
 
-            ensure!(!Profiles::<T>::contains_key(&who), Error::<T>::DuplicateProfile);
-
-            let fname: BoundedVec<_, ConstU32<64>> = f_name.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
-
-            let mname: BoundedVec<_, ConstU32<64>> = m_name.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
-
-            let lname: BoundedVec<_, ConstU32<64>> = l_name.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
-
-            let pfpicpath: BoundedVec<_, ConstU32<128>> = pf_pic_path.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
-
-            let n_ationality: BoundedVec<_, ConstU32<64>> = nationality.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
-
-            let r_eligion: BoundedVec<_, ConstU32<64>> = religion.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
-
-            let litlang: BoundedVec<_, ConstU32<64>> = lit_lang.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
-
-            let c_ity: BoundedVec<_, ConstU32<64>> = city.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
+            ensure!(
+                !Profiles::<T>::contains_key(&who) && !migrations::v1::awaits_rekey::<T>(&who),
+                Error::<T>::DuplicateProfile
+            );
 
-            let d_ivision: BoundedVec<_, ConstU32<64>> = division.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
+            let fname = Self::text(f_name.as_bytes(), CharClass::Name, Error::<T>::InvalidFirstName)?;
+            let mname = Self::text(m_name.as_bytes(), CharClass::Name, Error::<T>::InvalidMiddleName)?;
+            let lname = Self::text(l_name.as_bytes(), CharClass::Name, Error::<T>::InvalidLastName)?;
+            let n_ationality = Self::text(nationality.as_bytes(), CharClass::Name, Error::<T>::InvalidNationality)?;
+            let r_eligion = Self::text(religion.as_bytes(), CharClass::Name, Error::<T>::InvalidReligion)?;
+            let litlang = Self::text(lit_lang.as_bytes(), CharClass::Name, Error::<T>::InvalidLanguage)?;
+            let c_ity = Self::text(city.as_bytes(), CharClass::Text, Error::<T>::InvalidCity)?;
+            let d_ivision = Self::text(division.as_bytes(), CharClass::Text, Error::<T>::InvalidDivision)?;
+            Self::ensure_district_in(province, district)?;
+            Self::ensure_postal_code(postal_code)?;
+            picture.as_ref().map_or(Ok(()), Self::ensure_picture)?;
 
             // let postal_code_bv: BoundedVec<u8, ConstU32<8>> =
             //     postal_code
@@ -251,18 +1410,14 @@ pub mod pallet {
             //     .try_into()
             //     .map_err(|_| Error::<T>::StringTooLong)?;
             
-            ensure!(Self::is_valid_date(&birth_date), Error::<T>::InvalidBirthDate);
-
-
-            let birthdate: BoundedVec<_, ConstU32<64>> = birth_date.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::StringTooLong)?;
+            ensure!(Self::is_valid_birth_date(&birth_date), Error::<T>::InvalidBirthDate);
 
             let data = UserProfileData {
                 f_name        : fname,
                 m_name        : mname,
                 l_name        : lname,
                 marital_status,
-                pf_pic_path   : pfpicpath,
+                picture,
                 gender,
                 blood_group,
                 nationality   : n_ationality,
@@ -273,7 +1428,7 @@ pub mod pallet {
                 city          : c_ity,
                 division      : d_ivision,
                 postal_code,
-                birth_date: birthdate,
+                birth_date: Some(birth_date),
             };
 
             Profiles::<T>::insert(&who, data);
@@ -283,45 +1438,81 @@ pub mod pallet {
 
         /// Patch any subset of fields that are `Some(..)`.
         #[pallet::call_index(2)]
-        #[pallet::weight(Weight::default())]
+        #[pallet::weight({
+            let (n, t) = updates.lengths();
+            T::WeightInfo::update_profile(n, t)
+        })]
         pub fn update_profile(
             origin  : OriginFor<T>,
-            updates : UserProfileDataUpdate,
+            updates : UserProfileDataUpdateOf<T>,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+            let who = T::UserPallet::primary_account(&ensure_signed(origin)?);
+            ensure!(<T as Config>::UserPallet::is_active(&who), Error::<T>::UserNotActive);
 
             Profiles::<T>::try_mutate(&who, |maybe_profile| -> DispatchResult {
                 let profile = maybe_profile.as_mut().ok_or(Error::<T>::ProfileNotFound)?;
+                let before = profile.clone();
 
                 macro_rules! replace_opt {
                     ($field:ident, $val:expr) => {
                         if let Some(new_val) = $val { profile.$field = new_val; }
                     };
                 }
-                replace_opt!(f_name,         updates.f_name);
-                replace_opt!(m_name,         updates.m_name);
-                replace_opt!(l_name,         updates.l_name);
+                macro_rules! replace_text {
+                    ($field:ident, $class:expr, $err:ident) => {
+                        if let Some(raw) = &updates.$field {
+                            profile.$field = Self::text(raw, $class, Error::<T>::$err)?;
+                        }
+                    };
+                }
+                replace_text!(f_name,        CharClass::Name, InvalidFirstName);
+                replace_text!(m_name,        CharClass::Name, InvalidMiddleName);
+                replace_text!(l_name,        CharClass::Name, InvalidLastName);
                 replace_opt!(marital_status, updates.marital_status);
-                replace_opt!(pf_pic_path,    updates.pf_pic_path);
+                if let Some(picture) = updates.picture {
+                    picture.as_ref().map_or(Ok(()), Self::ensure_picture)?;
+                    profile.picture = picture;
+                }
                 replace_opt!(gender,         updates.gender);
                 replace_opt!(blood_group,    updates.blood_group);
-                replace_opt!(nationality,    updates.nationality);
-                replace_opt!(religion,       updates.religion);
-                replace_opt!(lit_lang,       updates.lit_lang);
+                replace_text!(nationality,   CharClass::Name, InvalidNationality);
+                replace_text!(religion,      CharClass::Name, InvalidReligion);
+                replace_text!(lit_lang,      CharClass::Name, InvalidLanguage);
                 replace_opt!(province,       updates.province);
                 replace_opt!(district,       updates.district);
-                replace_opt!(city,           updates.city);
-                replace_opt!(division,       updates.division);
-                replace_opt!(postal_code,    updates.postal_code);
+                replace_text!(city,          CharClass::Text, InvalidCity);
+                replace_text!(division,      CharClass::Text, InvalidDivision);
+                if updates.province.is_some() || updates.district.is_some() {
+                    Self::ensure_district_in(profile.province, profile.district)?;
+                }
+                if let Some(code) = updates.postal_code {
+                    Self::ensure_postal_code(code)?;
+                    profile.postal_code = code;
+                }
+                // fields written in plaintext are no longer encrypted
+                let written = updates.fields();
+                for field in EncryptedFields::<T>::iter_key_prefix(&who).filter(|f| written.contains(*f)) {
+                    EncryptedFields::<T>::remove(&who, field);
+                }
+                // nor judged
+                let invalidated: FieldSet =
+                    Judgements::<T>::iter_key_prefix(&who).filter(|f| written.contains(*f)).collect();
+                for field in invalidated.iter() {
+                    Judgements::<T>::remove(&who, field);
+                }
+                if !invalidated.is_empty() {
+                    Self::deposit_event(Event::JudgementsInvalidated { account: who.clone(), fields: invalidated });
+                }
 
                 if let Some(bd) = updates.birth_date {
-
-                    let bd_str = sp_std::str::from_utf8(&bd).map_err(|_| Error::<T>::InvalidBirthDate)?;
-                    
-                    ensure!(Self::is_valid_date(&bd_str), Error::<T>::InvalidBirthDate);
-                    profile.birth_date = bd;
+                    ensure!(Self::is_valid_birth_date(&bd), Error::<T>::InvalidBirthDate);
+                    profile.birth_date = Some(bd);
                 }
 
+                let changed = profile.changed_from(&before);
+                if !changed.is_empty() {
+                    Self::record_revision(&who, changed, before.values(changed));
+                }
                 Ok(())
             })?;
 
@@ -331,49 +1522,341 @@ pub mod pallet {
 
         /// Delete the caller’s profile.
         #[pallet::call_index(3)]
-        #[pallet::weight(Weight::default())]
+        #[pallet::weight(T::WeightInfo::remove_profile(T::MaxRevisions::get()))]
         pub fn remove_profile(origin: OriginFor<T>) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+            let who = T::UserPallet::primary_account(&ensure_signed(origin)?);
+            ensure!(<T as Config>::UserPallet::is_active(&who), Error::<T>::UserNotActive);
             ensure!(Profiles::<T>::contains_key(&who), Error::<T>::ProfileNotFound);
 
             Profiles::<T>::remove(&who);
+            Grants::<T>::remove(&who);
+            Self::clear_profile_data(&who);
             Self::deposit_event(Event::ProfileRemoved { account: who });
             Ok(())
         }
 
+        /// Replaced by the `profile_details` view function; always fails.
         #[pallet::call_index(4)]
-        #[pallet::weight(Weight::default())]
-        pub fn get_my_profile_details(
-            origin: OriginFor<T>,
+        #[pallet::weight(Weight::zero())]
+        #[deprecated(note = "read the profile through the `profile_details` view function; this \
+            call is removed in the next release")]
+        // copied onto the generated dispatch, which still calls it
+        #[allow(deprecated)]
+        pub fn get_my_profile_details(origin: OriginFor<T>) -> DispatchResult {
+            ensure_signed(origin)?;
+            Err(Error::<T>::Deprecated.into())
+        }
+
+        /// Change who may read some fields of the caller's profile through `ProfileApi`.
+        /// Fields not listed keep their visibility.
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::set_visibility())]
+        pub fn set_visibility(origin: OriginFor<T>, changes: VisibilityChanges) -> DispatchResult {
+            let who = T::UserPallet::primary_account(&ensure_signed(origin)?);
+            ensure!(<T as Config>::UserPallet::is_active(&who), Error::<T>::UserNotActive);
+            ensure!(Profiles::<T>::contains_key(&who), Error::<T>::ProfileNotFound);
+
+            FieldVisibility::<T>::mutate(&who, |settings| Self::apply_visibility(settings, changes));
+            Self::deposit_event(Event::VisibilityChanged { account: who });
+            Ok(())
+        }
+
+        /// Change the visibility of fields that profiles do not override.
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::set_default_visibility())]
+        pub fn set_default_visibility(origin: OriginFor<T>, changes: VisibilityChanges) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            DefaultVisibility::<T>::mutate(|settings| Self::apply_visibility(settings, changes));
+            Self::deposit_event(Event::DefaultVisibilityChanged);
+            Ok(())
+        }
+
+        /// Let `grantee` read `fields` of the caller's profile for the next `duration` blocks,
+        /// whatever their visibility. Replaces an earlier grant to `grantee`.
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::grant_access())]
+        pub fn grant_access(
+            origin  : OriginFor<T>,
+            grantee : T::AccountId,
+            fields  : BoundedVec<ProfileField, ConstU32<PROFILE_FIELDS>>,
+            duration: BlockNumberFor<T>,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+            let who = T::UserPallet::primary_account(&ensure_signed(origin)?);
+            ensure!(<T as Config>::UserPallet::is_active(&who), Error::<T>::UserNotActive);
+            ensure!(Profiles::<T>::contains_key(&who), Error::<T>::ProfileNotFound);
+            ensure!(T::UserPallet::primary_account(&grantee) != who, Error::<T>::CannotGrantSelf);
+            ensure!(
+                !duration.is_zero() && duration <= T::MaxGrantDuration::get(),
+                Error::<T>::InvalidGrantDuration
+            );
+            let fields: FieldSet = fields.into_iter().collect();
+            ensure!(!fields.is_empty(), Error::<T>::NoFieldsGranted);
 
-            match Profiles::<T>::get(&who) {
-                Some(p) => {
-                    // Emit the event with human-readable strings
-                    Self::deposit_event(Event::ProfileDataFetched {
-                        account: who,
-                        f_name: String::from_utf8(p.f_name.to_vec()).unwrap_or_default(),
-                        m_name: String::from_utf8(p.m_name.to_vec()).unwrap_or_default(),
-                        l_name: String::from_utf8(p.l_name.to_vec()).unwrap_or_default(),
-                        gender: p.gender,
-                        marital_status: p.marital_status, 
-                        pf_pic_path:  String::from_utf8(p.pf_pic_path.to_vec()).unwrap_or_default(), 
-                        blood_group: p.blood_group, 
-                        nationality:  String::from_utf8(p.nationality.to_vec()).unwrap_or_default(), 
-                        religion:  String::from_utf8(p.religion.to_vec()).unwrap_or_default(), 
-                        lit_lang:  String::from_utf8(p.lit_lang.to_vec()).unwrap_or_default(), 
-                        province: p.province, 
-                        district: p.district, 
-                        city:  String::from_utf8(p.city.to_vec()).unwrap_or_default(), 
-                        division:  String::from_utf8(p.division.to_vec()).unwrap_or_default(), 
-                        postal_code: p.postal_code, 
-                        birth_date:  String::from_utf8(p.birth_date.to_vec()).unwrap_or_default() 
-                    });
+            let expires_at = frame_system::Pallet::<T>::block_number().saturating_add(duration);
+            let grant = Grant { grantee: grantee.clone(), fields, expires_at };
+            Grants::<T>::try_mutate(&who, |grants| match grants.iter_mut().find(|g| g.grantee == grantee) {
+                Some(existing) => {
+                    *existing = grant;
                     Ok(())
                 },
-                None => Err(Error::<T>::ProfileNotFound.into()),
+                None => grants.try_push(grant).map_err(|_| Error::<T>::TooManyGrants),
+            })?;
+            Self::drop_copies(&who, |reader, field| *reader != grantee || fields.contains(field));
+
+            Self::deposit_event(Event::AccessGranted { owner: who, grantee, fields, expires_at });
+            Ok(())
+        }
+
+        /// Register or replace the caller's x25519 public key, which its own encrypted fields
+        /// and the copies others share with it are sealed to. Copies sealed to an earlier key
+        /// must be re-uploaded by their owners. Open to any account, so grantees without an
+        /// active user record can read what is shared with them.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::set_encryption_key())]
+        pub fn set_encryption_key(origin: OriginFor<T>, public: [u8; 32]) -> DispatchResult {
+            let who = T::UserPallet::primary_account(&ensure_signed(origin)?);
+
+            EncryptionKeys::<T>::insert(&who, public);
+            Self::deposit_event(Event::EncryptionKeySet { account: who });
+            Ok(())
+        }
+
+        /// Store `field` of the caller's profile as `sealed` and withhold its plaintext. Every
+        /// copy in `sealed.shared` goes to a grantee currently reading the field, sealed to
+        /// that grantee's own key. Past values of the field are redacted from the history and
+        /// its judgements dropped. Writing the field through `update_profile` makes it
+        /// plaintext again.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::set_encrypted_field(sealed.shared.len() as u32, T::MaxRevisions::get()))]
+        pub fn set_encrypted_field(
+            origin: OriginFor<T>,
+            field : ProfileField,
+            sealed: SealedFieldOf<T>,
+        ) -> DispatchResult {
+            let who = T::UserPallet::primary_account(&ensure_signed(origin)?);
+            ensure!(<T as Config>::UserPallet::is_active(&who), Error::<T>::UserNotActive);
+            ensure!(EncryptionKeys::<T>::contains_key(&who), Error::<T>::NoEncryptionKey);
+            ensure!(field.is_encryptable(), Error::<T>::FieldNotEncryptable);
+            ensure!(
+                core::iter::once(&sealed.own)
+                    .chain(sealed.shared.iter().map(|(_, blob)| blob))
+                    .all(|blob| blob.len() as u32 >= CIPHERTEXT_OVERHEAD),
+                Error::<T>::InvalidCiphertext
+            );
+            for (at, (reader, _)) in sealed.shared.iter().enumerate() {
+                ensure!(
+                    sealed.shared[..at].iter().all(|(earlier, _)| earlier != reader),
+                    Error::<T>::DuplicateReader
+                );
+                ensure!(Self::granted_fields(&who, reader).contains(field), Error::<T>::FieldNotShared);
+                ensure!(
+                    EncryptionKeys::<T>::contains_key(T::UserPallet::primary_account(reader)),
+                    Error::<T>::NoEncryptionKey
+                );
+            }
+
+            Profiles::<T>::try_mutate(&who, |maybe_profile| {
+                maybe_profile.as_mut().map(|p| p.withhold(field)).ok_or(Error::<T>::ProfileNotFound)
+            })?;
+            Self::redact_history(&who, field);
+            if Judgements::<T>::contains_key(&who, field) {
+                Judgements::<T>::remove(&who, field);
+                let fields = core::iter::once(field).collect();
+                Self::deposit_event(Event::JudgementsInvalidated { account: who.clone(), fields });
             }
+            EncryptedFields::<T>::insert(&who, field, sealed);
+            Self::deposit_event(Event::FieldEncrypted { account: who, field });
+            Ok(())
+        }
+
+        /// Keep profile revisions for `retention` blocks, or until `MaxRevisions` newer ones
+        /// push them out if `None`. Revisions past a shortened retention are pruned in
+        /// `on_idle`.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::set_history_retention())]
+        pub fn set_history_retention(
+            origin   : OriginFor<T>,
+            retention: Option<BlockNumberFor<T>>,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            HistoryRetention::<T>::set(retention);
+            Self::deposit_event(Event::HistoryRetentionSet { retention });
+            Ok(())
+        }
+
+        /// Let `verifier` judge profile fields.
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::add_verifier())]
+        pub fn add_verifier(origin: OriginFor<T>, verifier: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            Verifiers::<T>::try_mutate(|verifiers| {
+                ensure!(!verifiers.contains(&verifier), Error::<T>::AlreadyVerifier);
+                verifiers.try_push(verifier.clone()).map_err(|_| Error::<T>::TooManyVerifiers)
+            })?;
+            Self::deposit_event(Event::VerifierAdded { verifier });
+            Ok(())
+        }
+
+        /// Stop `verifier` judging profile fields; its judgements are no longer served.
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::remove_verifier())]
+        pub fn remove_verifier(origin: OriginFor<T>, verifier: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            Verifiers::<T>::try_mutate(|verifiers| {
+                let at = verifiers.iter().position(|v| *v == verifier).ok_or(Error::<T>::NotVerifier)?;
+                verifiers.remove(at);
+                Ok::<_, Error<T>>(())
+            })?;
+            Self::deposit_event(Event::VerifierRemoved { verifier });
+            Ok(())
+        }
+
+        /// Judge `field` of the profile of `owner` (resolved to its primary), replacing the
+        /// caller's earlier judgement of it. Only `Verifiers` may call this.
+        #[pallet::call_index(14)]
+        #[pallet::weight(T::WeightInfo::judge_field())]
+        pub fn judge_field(
+            origin   : OriginFor<T>,
+            owner    : T::AccountId,
+            field    : ProfileField,
+            judgement: Judgement,
+        ) -> DispatchResult {
+            let verifier = ensure_signed(origin)?;
+            let verifiers = Verifiers::<T>::get();
+            ensure!(verifiers.contains(&verifier), Error::<T>::NotVerifier);
+            let owner = T::UserPallet::primary_account(&owner);
+            ensure!(T::UserPallet::primary_account(&verifier) != owner, Error::<T>::CannotJudgeSelf);
+            ensure!(Profiles::<T>::contains_key(&owner), Error::<T>::ProfileNotFound);
+
+            Judgements::<T>::mutate(&owner, field, |judgements| {
+                judgements.retain(|(v, _)| *v != verifier && verifiers.contains(v));
+                // one entry per current verifier, which is what the bound allows
+                let _ = judgements.try_push((verifier.clone(), judgement)).defensive_proof("one entry per verifier fits");
+            });
+            Self::deposit_event(Event::FieldJudged { owner, field, verifier, judgement });
+            Ok(())
+        }
+
+        /// Stop sharing the caller's profile with `grantee`. Suspended owners can still withdraw
+        /// consent.
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::revoke_access())]
+        pub fn revoke_access(origin: OriginFor<T>, grantee: T::AccountId) -> DispatchResult {
+            let who = T::UserPallet::primary_account(&ensure_signed(origin)?);
+
+            Grants::<T>::try_mutate_exists(&who, |maybe_grants| {
+                let grants = maybe_grants.as_mut().ok_or(Error::<T>::GrantNotFound)?;
+                let at = grants.iter().position(|g| g.grantee == grantee).ok_or(Error::<T>::GrantNotFound)?;
+                grants.remove(at);
+                if grants.is_empty() {
+                    *maybe_grants = None;
+                }
+                Ok::<_, Error<T>>(())
+            })?;
+            Self::drop_copies(&who, |reader, _| *reader != grantee);
+
+            Self::deposit_event(Event::AccessRevoked { owner: who, grantee });
+            Ok(())
+        }
+
+        /// Erase the caller's archived profile and everything kept along with it, so that
+        /// registering again starts from scratch.
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::purge_archived_profile(T::MaxRevisions::get()))]
+        pub fn purge_archived_profile(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::purge_archive(who)
+        }
+
+        /// Erase the archived profile of `who` and everything kept along with it.
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::force_purge_archived_profile(T::MaxRevisions::get()))]
+        pub fn force_purge_archived_profile(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::purge_archive(who)
+        }
+    }
+
+    /* -------------------------------------------------
+     *  User lifecycle hooks
+     * ------------------------------------------------- */
+    impl<T: Config> OnUserRemoved<T::AccountId> for Pallet<T> {
+        fn on_user_removed(who: &T::AccountId) {
+            let Some(profile) = Profiles::<T>::take(who) else { return };
+
+            if T::ArchiveRemovedProfiles::get() {
+                ArchivedProfiles::<T>::insert(who, profile);
+                Self::deposit_event(Event::ProfileArchived { account: who.clone() });
+            } else {
+                Self::clear_profile_data(who);
+                Self::deposit_event(Event::ProfileRemoved { account: who.clone() });
+            }
+            // a removed user shares nothing, even if the profile is archived
+            Grants::<T>::remove(who);
+        }
+
+        fn weight() -> Weight {
+            T::WeightInfo::on_user_removed(T::MaxRevisions::get())
+        }
+    }
+
+    impl<T: Config> OnUserCreated<T::AccountId> for Pallet<T> {
+        fn on_user_created(who: &T::AccountId) {
+            if let Some(profile) = ArchivedProfiles::<T>::take(who) {
+                Profiles::<T>::insert(who, profile);
+                Self::deposit_event(Event::ProfileRestored { account: who.clone() });
+            }
+        }
+
+        fn weight() -> Weight {
+            T::WeightInfo::on_user_created()
+        }
+    }
+
+    impl<T: Config> OnUserMoved<T::AccountId> for Pallet<T> {
+        fn can_move(_from: &T::AccountId, to: &T::AccountId) -> DispatchResult {
+            // the archive keeps its visibility, key, ciphertexts and history under `to` too
+            ensure!(!ArchivedProfiles::<T>::contains_key(to), Error::<T>::ArchivedProfileExists);
+            Ok(())
+        }
+
+        fn on_user_moved(from: &T::AccountId, to: &T::AccountId) {
+            if let Some(profile) = Profiles::<T>::take(from) {
+                Profiles::<T>::insert(to, profile);
+                Self::deposit_event(Event::ProfileMoved { from: from.clone(), to: to.clone() });
+            }
+            if let Some(profile) = ArchivedProfiles::<T>::take(from) {
+                ArchivedProfiles::<T>::insert(to, profile);
+            }
+            if FieldVisibility::<T>::contains_key(from) {
+                FieldVisibility::<T>::insert(to, FieldVisibility::<T>::take(from));
+            }
+            if Grants::<T>::contains_key(from) {
+                Grants::<T>::insert(to, Grants::<T>::take(from));
+            }
+            if let Some(public) = EncryptionKeys::<T>::take(from) {
+                EncryptionKeys::<T>::insert(to, public);
+            }
+            for (field, blob) in EncryptedFields::<T>::drain_prefix(from) {
+                EncryptedFields::<T>::insert(to, field, blob);
+            }
+            if RevisionRange::<T>::contains_key(from) {
+                RevisionRange::<T>::insert(to, RevisionRange::<T>::take(from));
+            }
+            for (number, revision) in Revisions::<T>::drain_prefix(from) {
+                Revisions::<T>::insert(to, number, revision);
+            }
+            for (field, judgements) in Judgements::<T>::drain_prefix(from) {
+                Judgements::<T>::insert(to, field, judgements);
+            }
+        }
+
+        fn weight() -> Weight {
+            T::WeightInfo::on_user_moved(T::MaxRevisions::get())
         }
     }
 
@@ -385,10 +1868,23 @@ pub mod pallet {
         use super::*;
         use sp_api::decl_runtime_apis;
         use sp_std::vec::Vec;        // for raw bytes
-        use frame_support::sp_runtime::AccountId32;
+        use frame_support::sp_runtime::{AccountId32, MultiSignature};
+
+        /// `ReadAuth` as signed for the runtime API.
+        pub type ApiReadAuth = ReadAuth<AccountId32, u32, MultiSignature>;
 
         decl_runtime_apis! {
+            /// Version 2 adds the listing calls. Version 3 resolves linked secondary accounts
+            /// to their primary; the returned account is the primary. Version 4 redacts
+            /// `get_user_profile` by field visibility. Version 5 adds `get_shared_profile`,
+            /// version 6 `get_encrypted_fields`; encrypted fields are never in a `ProfileView`.
+            /// Version 7 replaces `ProfileView::pf_pic_path` by the content-addressed `picture`.
+            /// Version 8 adds `get_profile_history`, version 9 `get_judgements`. Version 10
+            /// only trusts a requester that signed an `ApiReadAuth` and serves each reader the
+            /// encrypted fields sealed to its own key.
+            #[api_version(10)]
             pub trait ProfileApi {
+                #[changed_in(4)]
                 fn get_user_profile(acc: AccountId32)
                     -> Option<(
                         AccountId32,
@@ -400,8 +1896,67 @@ pub mod pallet {
                         Province, District,
                         String, String,                // city, division
                         u32,                           // postal_code
-                        String                         // birth_date
+                        String                         // birth_date, `YYYY-MM-DD` or empty
                     )>;
+
+                #[changed_in(10)]
+                fn get_user_profile(acc: AccountId32, requester: Option<AccountId32>)
+                    -> Option<ProfileView<AccountId32>>;
+
+                /// The profile of `acc` with the fields the requester proven by `auth` may not
+                /// see left out. Without a valid `auth` only the public fields are returned.
+                fn get_user_profile(acc: AccountId32, auth: Option<ApiReadAuth>)
+                    -> Option<ProfileView<AccountId32>>;
+
+                #[changed_in(10)]
+                fn get_shared_profile(owner: AccountId32, requester: AccountId32)
+                    -> Option<ProfileView<AccountId32>>;
+
+                /// Only the fields `owner` currently shares with the requester proven by
+                /// `auth`; `None` without a live grant or a valid `auth`.
+                fn get_shared_profile(owner: AccountId32, auth: ApiReadAuth)
+                    -> Option<ProfileView<AccountId32>>;
+
+                #[changed_in(10)]
+                fn get_encrypted_fields(owner: AccountId32) -> Option<EncryptedProfile>;
+
+                /// The x25519 public key of `reader` and the copies of the encrypted fields of
+                /// `owner` sealed to it, as described at `EncryptedFields`; `None` if `reader`
+                /// has no key. Ciphertexts are public chain state, so `reader` is not
+                /// authenticated.
+                fn get_encrypted_fields(owner: AccountId32, reader: AccountId32) -> Option<EncryptedProfile>;
+
+                #[changed_in(10)]
+                fn get_profile_history(
+                    acc: AccountId32,
+                    requester: Option<AccountId32>,
+                    before: Option<u32>,
+                    limit: u32,
+                ) -> Vec<RevisionView<AccountId32, u32>>;
+
+                /// Up to `limit` revisions of the profile of `acc` numbered below `before`,
+                /// newest first, redacted for the requester proven by `auth` like
+                /// `get_user_profile`. Pass the number of the last revision of a page to get
+                /// the next one. Pages are capped at `MAX_PAGE_LEN`.
+                fn get_profile_history(
+                    acc: AccountId32,
+                    auth: Option<ApiReadAuth>,
+                    before: Option<u32>,
+                    limit: u32,
+                ) -> Vec<RevisionView<AccountId32, u32>>;
+
+                /// The judgements of the profile of `acc` by current verifiers, by field in
+                /// `ProfileField` order. Fields without one are left out; every field is
+                /// self-asserted until judged.
+                fn get_judgements(acc: AccountId32) -> Verification<AccountId32>;
+
+                /// Up to `limit` accounts with a profile following `start_after`, in storage
+                /// order. Pass the last account of a page to get the next one. Pages are
+                /// capped at `MAX_PAGE_LEN`.
+                fn list_profiles(start_after: Option<AccountId32>, limit: u32) -> Vec<AccountId32>;
+
+                /// Number of stored profiles, archived ones excluded.
+                fn count_profiles() -> u32;
             }
         }
     }
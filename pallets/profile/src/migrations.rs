@@ -0,0 +1,629 @@
+// pallets/profile/src/migrations.rs
+//! Storage migrations for the profile pallet.
+//!
+//! Same layout as the user pallet: one `vN` module per schema change, each exposing a
+//! `MigrateV{N-1}ToV{N}` alias wrapped in [`VersionedMigration`]. Add the alias to the
+//! runtime's `Migrations` tuple.
+
+use super::*;
+use frame_support::{
+    migrations::VersionedMigration,
+    pallet_prelude::*,
+    storage::{storage_prefix, unhashed, KeyPrefixIterator},
+    traits::{OnRuntimeUpgrade, PalletInfoAccess, UncheckedOnRuntimeUpgrade},
+    Blake2_128, StorageHasher,
+};
+use user::Date;
+use scale_info::prelude::{format, string::String, vec::Vec};
+
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+/// `who` for the log, if the profile was re-keyed already.
+fn owner<T: Config>(who: Option<T::AccountId>) -> String {
+    who.map_or_else(|| "a profile not re-keyed yet".into(), |who| format!("{:?}", who))
+}
+
+/// v0 -> v1: re-key `Profiles` from `Blake2_128` to `Blake2_128Concat` and count it.
+///
+/// Works like `user::migrations::v3`: the old hasher is not reversible, so profiles are
+/// matched against every account in `frame_system::Account`. That map is unbounded, so the
+/// upgrade only opens a cursor and the pallet's `on_initialize` walks [`ACCOUNTS_PER_STEP`]
+/// accounts per block through [`step`]. Once every account is visited, the same step removes
+/// the profiles of accounts the system pallet does not know, [`ACCOUNTS_PER_STEP`] keys per
+/// block.
+///
+/// Later migrations in the same upgrade run before the profiles are re-keyed, so they
+/// translate profiles under either key through [`translate_profiles`].
+pub mod v1 {
+    use super::*;
+
+    /// Accounts of `frame_system::Account` visited per block while re-keying.
+    pub const ACCOUNTS_PER_STEP: u32 = 64;
+
+    /// Progress of the re-keying: `None` before the first step, then the last account visited.
+    /// Absent once it is done.
+    #[frame_support::storage_alias]
+    type RekeyCursor<T: Config> =
+        StorageValue<Pallet<T>, Option<<T as frame_system::Config>::AccountId>, OptionQuery>;
+
+    /// Progress of the removal of profiles that could not be re-keyed: the last raw key
+    /// visited. Absent before the re-keying is done and once the removal is.
+    #[frame_support::storage_alias]
+    type SweepCursor<T: Config> = StorageValue<Pallet<T>, Vec<u8>, OptionQuery>;
+
+    /// The counter of the counted `Profiles`.
+    #[frame_support::storage_alias]
+    type CounterForProfiles<T: Config> = StorageValue<Pallet<T>, u32, ValueQuery>;
+
+    fn map_prefix<T: Config>() -> [u8; 32] {
+        storage_prefix(<Pallet<T> as PalletInfoAccess>::name().as_bytes(), b"Profiles")
+    }
+
+    /// Key of `who` under the v0 layout.
+    fn old_key<T: Config>(who: &T::AccountId) -> Vec<u8> {
+        [&map_prefix::<T>()[..], &who.using_encoded(Blake2_128::hash)[..]].concat()
+    }
+
+    /// Keys of `Profiles` under either layout.
+    fn keys<T: Config>() -> KeyPrefixIterator<Vec<u8>> {
+        let prefix = map_prefix::<T>().to_vec();
+        KeyPrefixIterator::new(prefix.clone(), prefix, |key| Ok(key.to_vec()))
+    }
+
+    /// Number of entries still under the v0 key layout. Walks the whole map; off-chain use only.
+    pub fn old_layout_entries<T: Config>() -> u32 {
+        keys::<T>().filter(|key| key.len() == 16).count() as u32
+    }
+
+    /// Number of profiles under either key layout. Walks the whole map; off-chain use only.
+    pub fn entries<T: Config>() -> u32 {
+        keys::<T>().count() as u32
+    }
+
+    /// Profiles under either key layout that decode as `V`. Walks the whole map; off-chain use
+    /// only.
+    pub fn values<T: Config, V: Decode>() -> impl Iterator<Item = V> {
+        let prefix = map_prefix::<T>();
+        keys::<T>().filter_map(move |key| unhashed::get(&[&prefix[..], &key[..]].concat()))
+    }
+
+    /// Translate every profile from `O` to `V` under either key layout, see
+    /// `user::migrations::v3::translate_either`.
+    pub fn translate_profiles<T: Config, O: Decode, V: Encode>(
+        f: impl FnMut(Option<T::AccountId>, O) -> V,
+    ) -> u64 {
+        user::migrations::v3::translate_either(&map_prefix::<T>(), f)
+    }
+
+    /// The re-keying, or the removal of what it could not re-key, has started and not finished
+    /// yet.
+    pub fn rekey_pending<T: Config>() -> bool {
+        RekeyCursor::<T>::exists() || SweepCursor::<T>::exists()
+    }
+
+    /// `who` still has a profile under the v0 key, waiting to be re-keyed.
+    pub fn awaits_rekey<T: Config>(who: &T::AccountId) -> bool {
+        rekey_pending::<T>() && unhashed::exists(&old_key::<T>(who))
+    }
+
+    /// Re-key the profiles of the next `limit` accounts, or once all are visited remove up to
+    /// `limit` profiles that were not re-keyed, and return the weight consumed.
+    ///
+    /// Values are moved as raw bytes, so this works whatever the profile layout is by the time
+    /// it runs.
+    pub fn step<T: Config>(limit: u32) -> Weight {
+        let Some(cursor) = RekeyCursor::<T>::get() else {
+            return sweep::<T>(limit);
+        };
+        let mut accounts = match &cursor {
+            Some(last) => frame_system::Account::<T>::iter_keys_from(
+                frame_system::Account::<T>::hashed_key_for(last),
+            ),
+            None => frame_system::Account::<T>::iter_keys(),
+        };
+
+        let (mut visited, mut found, mut moved) = (0u64, 0u64, 0u64);
+        let mut last = cursor;
+        let mut done = false;
+        while visited < limit as u64 {
+            let Some(who) = accounts.next() else {
+                done = true;
+                break;
+            };
+            visited += 1;
+            let key = old_key::<T>(&who);
+            if let Some(raw) = unhashed::get_raw(&key) {
+                unhashed::kill(&key);
+                found += 1;
+                let new_key = Profiles::<T>::hashed_key_for(&who);
+                if unhashed::exists(&new_key) {
+                    log::warn!(target: LOG_TARGET, "v1: {:?} has two profiles, kept the new one", who);
+                } else {
+                    unhashed::put_raw(&new_key, &raw);
+                    CounterForProfiles::<T>::mutate(|count| *count += 1);
+                    moved += 1;
+                }
+            }
+            last = Some(who);
+        }
+
+        if done {
+            RekeyCursor::<T>::kill();
+            SweepCursor::<T>::put(map_prefix::<T>().to_vec());
+            log::info!(target: LOG_TARGET, "v1: re-keying done");
+        } else {
+            RekeyCursor::<T>::put(last);
+        }
+        if moved > 0 {
+            log::info!(target: LOG_TARGET, "v1: re-keyed {} profiles", moved);
+        }
+
+        // the cursor; per account: read it and probe the old key; per profile found: probe the
+        // new key and remove the old one; per move: write it and bump the counter
+        T::DbWeight::get().reads_writes(
+            1 + visited.saturating_mul(2) + found + moved,
+            1 + found + moved.saturating_mul(2),
+        )
+    }
+
+    /// Remove up to `limit` profiles left under the v0 key layout and return the weight
+    /// consumed.
+    fn sweep<T: Config>(limit: u32) -> Weight {
+        let Some(last) = SweepCursor::<T>::get() else {
+            // both cursors
+            return T::DbWeight::get().reads(2);
+        };
+        let (last, visited, removed) =
+            user::migrations::v3::sweep_old_keys(&map_prefix::<T>(), last, limit);
+        match last {
+            Some(last) => SweepCursor::<T>::put(last),
+            None => {
+                SweepCursor::<T>::kill();
+                log::info!(target: LOG_TARGET, "v1: removal of profiles without an account done");
+            },
+        }
+        if removed > 0 {
+            log::warn!(target: LOG_TARGET, "v1: removed {} profiles without a known account", removed);
+        }
+
+        // both cursors; per key: read it; per profile removed: remove it
+        T::DbWeight::get().reads_writes(2 + visited, 1 + removed)
+    }
+
+    /// Unversioned body of the v0 -> v1 migration. Use [`MigrateV0ToV1`] instead.
+    pub struct UncheckedMigrateToV1<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            RekeyCursor::<T>::put(None::<T::AccountId>);
+            log::info!(target: LOG_TARGET, "v1: re-keying profiles over the next blocks");
+            T::DbWeight::get().writes(1)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok(old_layout_entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let profiles_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v1: bad pre_upgrade state"))?;
+            ensure!(rekey_pending::<T>(), "v1: re-keying not scheduled");
+            ensure!(old_layout_entries::<T>() == profiles_before, "v1: profiles moved early");
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 0 to 1.
+    pub type MigrateV0ToV1<T> = VersionedMigration<
+        0,
+        1,
+        UncheckedMigrateToV1<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Fit stored profiles into lowered `MaxNameLen`/`MaxTextLen` bounds.
+///
+/// Works like `user::migrations::bounds`: add [`bounds::TruncateToBounds`] to the runtime's
+/// `Migrations` in the release that lowers a bound. Archived profiles are fitted too.
+pub mod bounds {
+    use super::*;
+    use user::migrations::bounds::truncated;
+
+    /// `UserProfileData` with its configurable bounds erased. `B` is the birth date type and
+    /// `P` the picture type, so `v2` and `v3` can read the layouts that stored them as strings.
+    #[derive(Encode, Decode)]
+    pub struct UnboundedProfile<B = Option<Date>, P = Option<PictureRef>> {
+        pub f_name: Vec<u8>,
+        pub m_name: Vec<u8>,
+        pub l_name: Vec<u8>,
+        pub marital_status: MaritalStatus,
+        pub picture: P,
+        pub gender: Gender,
+        pub blood_group: BloodType,
+        pub nationality: Vec<u8>,
+        pub religion: Vec<u8>,
+        pub lit_lang: Vec<u8>,
+        pub province: Province,
+        pub district: District,
+        pub city: Vec<u8>,
+        pub division: Vec<u8>,
+        pub postal_code: u32,
+        pub birth_date: B,
+    }
+
+    impl<B, P> UnboundedProfile<B, P> {
+        /// The same profile with its birth date replaced.
+        pub fn with_birth_date<C>(self, birth_date: C) -> UnboundedProfile<C, P> {
+            self.with(|_| birth_date, |picture| picture)
+        }
+
+        /// The same profile with its picture replaced.
+        pub fn with_picture<Q>(self, picture: Q) -> UnboundedProfile<B, Q> {
+            self.with(|birth_date| birth_date, |_| picture)
+        }
+
+        fn with<C, Q>(self, birth_date: impl FnOnce(B) -> C, picture: impl FnOnce(P) -> Q) -> UnboundedProfile<C, Q> {
+            UnboundedProfile {
+                f_name: self.f_name,
+                m_name: self.m_name,
+                l_name: self.l_name,
+                marital_status: self.marital_status,
+                picture: picture(self.picture),
+                gender: self.gender,
+                blood_group: self.blood_group,
+                nationality: self.nationality,
+                religion: self.religion,
+                lit_lang: self.lit_lang,
+                province: self.province,
+                district: self.district,
+                city: self.city,
+                division: self.division,
+                postal_code: self.postal_code,
+                birth_date: birth_date(self.birth_date),
+            }
+        }
+    }
+
+    pub fn fitted<T: Config>(old: UnboundedProfile) -> UserProfileDataOf<T> {
+        UserProfileData {
+            f_name: truncated(old.f_name),
+            m_name: truncated(old.m_name),
+            l_name: truncated(old.l_name),
+            marital_status: old.marital_status,
+            picture: old.picture,
+            gender: old.gender,
+            blood_group: old.blood_group,
+            nationality: truncated(old.nationality),
+            religion: truncated(old.religion),
+            lit_lang: truncated(old.lit_lang),
+            province: old.province,
+            district: old.district,
+            city: truncated(old.city),
+            division: truncated(old.division),
+            postal_code: old.postal_code,
+            birth_date: old.birth_date,
+        }
+    }
+
+    /// Truncate the text fields of every live and archived profile to the configured bounds.
+    pub struct TruncateToBounds<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for TruncateToBounds<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let (mut records, mut shortened) = (0u64, 0u64);
+            let mut fit = |old: UnboundedProfile| {
+                records += 1;
+                let size = old.encoded_size();
+                let profile = fitted::<T>(old);
+                let cut = profile.encoded_size() < size;
+                shortened += u64::from(cut);
+                (profile, cut)
+            };
+            v1::translate_profiles::<T, UnboundedProfile, _>(|who, old| {
+                let (profile, cut) = fit(old);
+                if cut {
+                    log::warn!(target: LOG_TARGET, "bounds: truncated the profile of {}", owner::<T>(who));
+                }
+                profile
+            });
+            ArchivedProfiles::<T>::translate::<UnboundedProfile, _>(|who, old| {
+                let (profile, cut) = fit(old);
+                if cut {
+                    log::warn!(target: LOG_TARGET, "bounds: truncated the archived profile of {:?}", who);
+                }
+                Some(profile)
+            });
+            log::info!(target: LOG_TARGET, "bounds: truncated {} of {} profiles", shortened, records);
+
+            T::DbWeight::get().reads_writes(records, records)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            // not `count()`: profiles still waiting for the v1 re-keying are not counted yet
+            Ok(v1::entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let profiles_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("bounds: bad pre_upgrade state"))?;
+            ensure!(
+                Profiles::<T>::iter_values().count() as u32 == profiles_before,
+                "bounds: profiles lost"
+            );
+            Ok(())
+        }
+    }
+}
+
+/// v1 -> v2: store `birth_date` as a `user::Date` instead of a `YYYY-MM-DD` string.
+///
+/// Strings that are not a real date no later than today are logged and stored as `None`; the
+/// rest of such a profile is kept.
+pub mod v2 {
+    use super::*;
+    use bounds::UnboundedProfile;
+
+    /// The v1 birth date.
+    pub type OldBirthDate = BoundedVec<u8, ConstU32<64>>;
+
+    /// A v1 profile.
+    pub type OldProfile = UnboundedProfile<OldBirthDate, Vec<u8>>;
+
+    /// The archive as written by this migration, in the layout of [`v3::OldProfile`].
+    #[frame_support::storage_alias]
+    type ArchivedProfiles<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, <T as frame_system::Config>::AccountId, v3::OldProfile, OptionQuery>;
+
+    /// The v1 birth date of a profile, parsed.
+    pub fn converted<T: Config>(old: OldProfile) -> (v3::OldProfile, bool) {
+        let date = Date::parse(&old.birth_date).filter(Pallet::<T>::is_valid_birth_date);
+        (old.with_birth_date(date), date.is_some())
+    }
+
+    /// Unversioned body of the v1 -> v2 migration. Use [`MigrateV1ToV2`] instead.
+    pub struct UncheckedMigrateToV2<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV2<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let (mut records, mut unparsed) = (0u64, 0u64);
+            v1::translate_profiles::<T, OldProfile, _>(|who, old| {
+                records += 1;
+                let raw = old.birth_date.clone();
+                let (profile, parsed) = converted::<T>(old);
+                if !parsed {
+                    unparsed += 1;
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "v2: cleared the unparseable birth date {:?} of {}",
+                        String::from_utf8_lossy(&raw), owner::<T>(who),
+                    );
+                }
+                profile
+            });
+            ArchivedProfiles::<T>::translate::<OldProfile, _>(|who, old| {
+                records += 1;
+                let raw = old.birth_date.clone();
+                let (profile, parsed) = converted::<T>(old);
+                if !parsed {
+                    unparsed += 1;
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "v2: cleared the unparseable birth date {:?} of the archived profile of {:?}",
+                        String::from_utf8_lossy(&raw), who,
+                    );
+                }
+                Some(profile)
+            });
+            log::info!(
+                target: LOG_TARGET,
+                "v2: converted {} birth dates, {} could not be parsed",
+                records, unparsed,
+            );
+
+            T::DbWeight::get().reads_writes(records, records)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok(v1::entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let profiles_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v2: bad pre_upgrade state"))?;
+            ensure!(
+                v1::values::<T, v3::OldProfile>().count() as u32 == profiles_before,
+                "v2: profiles lost"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 1 to 2.
+    pub type MigrateV1ToV2<T> = VersionedMigration<
+        1,
+        2,
+        UncheckedMigrateToV2<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// v2 -> v3: replace the free-form `pf_pic_path` by a content-addressed `PictureRef`.
+///
+/// A path does not say which image it pointed to, so every picture is cleared. Profiles that
+/// had one are logged so their owners can be asked to upload the image and set it again.
+pub mod v3 {
+    use super::*;
+    use bounds::{fitted, UnboundedProfile};
+
+    /// A v2 profile: the birth date already parsed, the picture still a path.
+    pub type OldProfile = UnboundedProfile<Option<Date>, Vec<u8>>;
+
+    /// Unversioned body of the v2 -> v3 migration. Use [`MigrateV2ToV3`] instead.
+    pub struct UncheckedMigrateToV3<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV3<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let (mut records, mut cleared) = (0u64, 0u64);
+            v1::translate_profiles::<T, OldProfile, _>(|who, old| {
+                records += 1;
+                if !old.picture.is_empty() {
+                    cleared += 1;
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "v3: cleared the picture path {:?} of {}",
+                        String::from_utf8_lossy(&old.picture), owner::<T>(who),
+                    );
+                }
+                fitted::<T>(old.with_picture(None))
+            });
+            ArchivedProfiles::<T>::translate::<OldProfile, _>(|who, old| {
+                records += 1;
+                if !old.picture.is_empty() {
+                    cleared += 1;
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "v3: cleared the picture path {:?} of the archived profile of {:?}",
+                        String::from_utf8_lossy(&old.picture), who,
+                    );
+                }
+                Some(fitted::<T>(old.with_picture(None)))
+            });
+            log::info!(target: LOG_TARGET, "v3: cleared {} picture paths of {} profiles", cleared, records);
+
+            T::DbWeight::get().reads_writes(records, records)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok(v1::entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let profiles_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v3: bad pre_upgrade state"))?;
+            ensure!(
+                Profiles::<T>::iter_values().count() as u32 == profiles_before,
+                "v3: profiles lost"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 2 to 3.
+    pub type MigrateV2ToV3<T> = VersionedMigration<
+        2,
+        3,
+        UncheckedMigrateToV3<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// v3 -> v4: seal encrypted fields per reader and purge the plaintext they left behind.
+///
+/// Each v3 field was a single blob sealed to the owner's key, which grantees could only read
+/// by holding the owner's secret. It becomes the owner's copy; grantees get no copy until the
+/// owner re-uploads the field sealed to their own keys. Past values of encrypted fields are
+/// redacted from the history and their judgements dropped, as `set_encrypted_field` now does.
+pub mod v4 {
+    use super::*;
+
+    /// A v3 encrypted field.
+    pub type OldSealed<T> = BoundedVec<u8, <T as Config>::MaxCiphertextLen>;
+
+    /// Unversioned body of the v3 -> v4 migration. Use [`MigrateV3ToV4`] instead.
+    pub struct UncheckedMigrateToV4<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV4<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut encrypted = Vec::new();
+            EncryptedFields::<T>::translate::<OldSealed<T>, _>(|owner, field, own| {
+                encrypted.push((owner, field));
+                Some(SealedField { own, shared: BoundedVec::new() })
+            });
+            let revisions = u64::from(T::MaxRevisions::get());
+            for (owner, field) in &encrypted {
+                Pallet::<T>::redact_history(owner, *field);
+                Judgements::<T>::remove(owner, *field);
+            }
+            let records = encrypted.len() as u64;
+            log::info!(target: LOG_TARGET, "v4: sealed {} encrypted fields to their owners only", records);
+
+            // per field: translate it, read the history range and rewrite every revision,
+            // remove the judgements
+            T::DbWeight::get().reads_writes(records * (2 + revisions), records * (2 + revisions))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let fields = EncryptedFields::<T>::iter_keys().count() as u32;
+            Ok(fields.encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let fields_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v4: bad pre_upgrade state"))?;
+            ensure!(
+                EncryptedFields::<T>::iter_values().count() as u32 == fields_before,
+                "v4: encrypted fields lost"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 3 to 4.
+    pub type MigrateV3ToV4<T> = VersionedMigration<
+        3,
+        4,
+        UncheckedMigrateToV4<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Report profiles whose district does not lie in their province.
+///
+/// Appending districts needs no storage change, but profiles created before
+/// `DistrictNotInProvince` existed may hold a mismatched pair. They stay readable and
+/// updatable; only an update touching `province` or `district` must fix the pair. Add
+/// [`districts::ReportMismatched`] to the runtime's `Migrations` in the release that appends
+/// districts to list them for follow-up, and drop it again in the next one: it is not
+/// versioned and walks every profile, so it would run on each upgrade it stays in.
+pub mod districts {
+    use super::*;
+
+    /// Log every live profile with a mismatched province and district. Writes nothing.
+    pub struct ReportMismatched<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for ReportMismatched<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let (mut records, mut mismatched) = (0u64, 0u64);
+            for (who, profile) in Profiles::<T>::iter() {
+                records += 1;
+                if profile.district.province() != profile.province {
+                    mismatched += 1;
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "districts: {:?} lies outside {:?} in the profile of {:?}",
+                        profile.district, profile.province, who,
+                    );
+                }
+            }
+            log::info!(target: LOG_TARGET, "districts: {} of {} profiles mismatched", mismatched, records);
+
+            T::DbWeight::get().reads(records)
+        }
+    }
+}
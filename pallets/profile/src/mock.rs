@@ -0,0 +1,148 @@
+use crate as profile;
+use frame_support::{
+	derive_impl, ord_parameter_types, parameter_types,
+	traits::{ConstU32, ConstU64},
+	weights::constants::RocksDbWeight,
+};
+use frame_system::{EnsureRoot, EnsureSignedBy};
+use sp_runtime::BuildStorage;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+#[frame_support::runtime]
+mod runtime {
+	// The main runtime
+	#[runtime::runtime]
+	// Runtime Types to be generated
+	#[runtime::derive(
+		RuntimeCall,
+		RuntimeEvent,
+		RuntimeError,
+		RuntimeOrigin,
+		RuntimeFreezeReason,
+		RuntimeHoldReason,
+		RuntimeSlashReason,
+		RuntimeLockId,
+		RuntimeTask,
+		RuntimeViewFunction
+	)]
+	pub struct Test;
+
+	#[runtime::pallet_index(0)]
+	pub type System = frame_system::Pallet<Test>;
+
+	#[runtime::pallet_index(1)]
+	pub type Balances = pallet_balances::Pallet<Test>;
+
+	#[runtime::pallet_index(2)]
+	pub type User = user::Pallet<Test>;
+
+	#[runtime::pallet_index(3)]
+	pub type Profile = profile::Pallet<Test>;
+
+	#[runtime::pallet_index(4)]
+	pub type Timestamp = pallet_timestamp::Pallet<Test>;
+}
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type DbWeight = RocksDbWeight;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+	type AccountStore = System;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+/// Unix time in milliseconds at the start of every test: 2026-10-17 00:00 UTC.
+pub const NOW: u64 = 1_792_195_200_000;
+
+parameter_types! {
+	pub const DepositBase: u64 = 10;
+	pub const DepositPerByte: u64 = 1;
+	pub const MaxGuardians: u32 = 3;
+	pub const RecoveryDelay: u64 = 10;
+	pub const MaxLinkedAccounts: u32 = 2;
+	pub const MaxNameLen: u32 = 64;
+	pub const MaxAddressLen: u32 = 128;
+	pub const MaxTextLen: u32 = 64;
+	pub const MaxPictureSize: u32 = 1024;
+	pub const MaxGrants: u32 = 2;
+	pub const MaxGrantDuration: u64 = 100;
+	pub const MaxCiphertextLen: u32 = 128;
+	pub const MaxRevisions: u32 = 3;
+	pub const MaxVerifiers: u32 = 2;
+	pub static ArchiveRemovedProfiles: bool = false;
+}
+
+ord_parameter_types! {
+	pub const Registrar: u64 = 100;
+}
+
+impl user::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = ();
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RegistrarOrigin = EnsureSignedBy<Registrar, u64>;
+	type UnixTime = Timestamp;
+	type OnUserCreated = Profile;
+	type OnUserRemoved = Profile;
+	type OnUserMoved = Profile;
+	type MaxGuardians = MaxGuardians;
+	type RecoveryDelay = RecoveryDelay;
+	type MaxLinkedAccounts = MaxLinkedAccounts;
+	type MaxNameLen = MaxNameLen;
+	type MaxAddressLen = MaxAddressLen;
+	type DepositBase = DepositBase;
+	type DepositPerByte = DepositPerByte;
+	type MaxRegistrars = ConstU32<2>;
+}
+
+impl profile::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = ();
+	type UserPallet = User;
+	type UnixTime = Timestamp;
+	type AdminOrigin = EnsureRoot<u64>;
+	type MaxGrants = MaxGrants;
+	type MaxGrantDuration = MaxGrantDuration;
+	type MaxCiphertextLen = MaxCiphertextLen;
+	type MaxRevisions = MaxRevisions;
+	type MaxVerifiers = MaxVerifiers;
+	type ArchiveRemovedProfiles = ArchiveRemovedProfiles;
+	type MaxNameLen = MaxNameLen;
+	type MaxTextLen = MaxTextLen;
+	type MaxPictureSize = MaxPictureSize;
+	type ReadSignature = sp_runtime::testing::TestSignature;
+	type MaxReadAuthAge = ConstU64<10>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = User;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1_000), (2, 1_000), (3, 1_000), (7, 1)],
+		..Default::default()
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(NOW);
+	});
+	ext
+}
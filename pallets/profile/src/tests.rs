@@ -0,0 +1,1343 @@
+use crate::{migrations, mock::*, ArchivedProfiles, Error, Event, Profiles};
+use crate::{
+	BloodType, DefaultVisibility, District, EncryptedFields, EncryptionKeys, FieldVisibility,
+	Gender, Grants, GrantsCursor, HashAlgorithm, HistoryCursor, ImageMime, Judgement, Judgements,
+	MaritalStatus, PictureRef, ProfileField, Province, RevisionRange, RevisionView, Revisions,
+	ReadAuthOf, SealedField, SealedFieldOf, UserProfileDataOf, UserProfileDataUpdate, UserProfileDataUpdateOf, Verifiers,
+	Visibility,
+};
+use codec::Encode;
+use sp_runtime::testing::TestSignature;
+use frame_support::{
+	assert_noop, assert_ok,
+	sp_runtime::{DispatchError, DispatchResult},
+	storage::{storage_prefix, unhashed},
+	traits::{GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion},
+	weights::{constants::RocksDbWeight, Weight},
+	Blake2_128, StorageHasher,
+};
+
+fn register_user(who: u64) {
+	assert_ok!(User::create_user(
+		RuntimeOrigin::signed(who),
+		"Nimal".into(),
+		"Perera".into(),
+		"Colombo".into(),
+		user::Date::new(1996, 3, 14),
+	));
+	assert_ok!(User::set_user_status(
+		RuntimeOrigin::signed(Registrar::get()),
+		who,
+		user::UserStatus::Active
+	));
+}
+
+/// A PNG signature standing in for a picture.
+const PICTURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn picture() -> PictureRef {
+	PictureRef {
+		algorithm: HashAlgorithm::Blake2_256,
+		digest: HashAlgorithm::Blake2_256.hash(PICTURE),
+		mime: ImageMime::Png,
+		size: PICTURE.len() as u32,
+	}
+}
+
+fn create_profile(who: u64) {
+	assert_ok!(Profile::create_profile(
+		RuntimeOrigin::signed(who),
+		"Nimal".into(),
+		"".into(),
+		"Perera".into(),
+		MaritalStatus::Single,
+		Some(picture()),
+		Gender::Male,
+		BloodType::OPos,
+		"Sri Lankan".into(),
+		"Buddhism".into(),
+		"Sinhala".into(),
+		Province::Western,
+		District::Colombo,
+		"Colombo".into(),
+		"Colombo 03".into(),
+		300,
+		user::Date::new(1994, 5, 17),
+	));
+}
+
+/// `profile` in the v1 layout, with `birth_date` and the picture path as strings.
+fn v1_profile(profile: &UserProfileDataOf<Test>, birth_date: &[u8]) -> migrations::v2::OldProfile {
+	migrations::bounds::UnboundedProfile {
+		f_name: profile.f_name.to_vec(),
+		m_name: profile.m_name.to_vec(),
+		l_name: profile.l_name.to_vec(),
+		marital_status: profile.marital_status,
+		picture: b"pics/nimal.png".to_vec(),
+		gender: profile.gender,
+		blood_group: profile.blood_group,
+		nationality: profile.nationality.to_vec(),
+		religion: profile.religion.to_vec(),
+		lit_lang: profile.lit_lang.to_vec(),
+		province: profile.province,
+		district: profile.district,
+		city: profile.city.to_vec(),
+		division: profile.division.to_vec(),
+		postal_code: profile.postal_code,
+		birth_date: birth_date.to_vec().try_into().unwrap(),
+	}
+}
+
+#[test]
+fn create_profile_requires_registered_user() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Profile::create_profile(
+				RuntimeOrigin::signed(1),
+				"Nimal".into(),
+				"".into(),
+				"Perera".into(),
+				MaritalStatus::Single,
+				None,
+				Gender::Male,
+				BloodType::OPos,
+				"".into(),
+				"".into(),
+				"".into(),
+				Province::Western,
+				District::Colombo,
+				"".into(),
+				"".into(),
+				0,
+				user::Date::new(1994, 5, 17),
+			),
+			Error::<Test>::UserNotRegistered
+		);
+
+		register_user(1);
+		create_profile(1);
+		assert!(Profiles::<Test>::contains_key(1));
+		System::assert_last_event(Event::ProfileCreated { account: 1 }.into());
+	});
+}
+
+#[test]
+fn update_profile_patches_fields() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+
+		let updates = UserProfileDataUpdate {
+			city: Some(b"Kandy".to_vec().try_into().unwrap()),
+			..Default::default()
+		};
+		assert_ok!(Profile::update_profile(RuntimeOrigin::signed(1), updates));
+
+		let profile = Profiles::<Test>::get(1).unwrap();
+		assert_eq!(profile.city.to_vec(), b"Kandy".to_vec());
+		assert_eq!(profile.f_name.to_vec(), b"Nimal".to_vec());
+	});
+}
+
+#[test]
+fn removing_user_deletes_profile() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+
+		assert!(!Profiles::<Test>::contains_key(1));
+		assert!(!ArchivedProfiles::<Test>::contains_key(1));
+		System::assert_has_event(Event::ProfileRemoved { account: 1 }.into());
+	});
+}
+
+#[test]
+fn force_removing_user_cascades_too() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+
+		assert_ok!(User::force_remove_user(RuntimeOrigin::signed(Registrar::get()), 1));
+
+		assert!(!Profiles::<Test>::contains_key(1));
+		System::assert_has_event(Event::ProfileRemoved { account: 1 }.into());
+	});
+}
+
+#[test]
+fn archived_profile_is_restored_on_re_registration() {
+	new_test_ext().execute_with(|| {
+		ArchiveRemovedProfiles::set(true);
+		register_user(1);
+		create_profile(1);
+		let profile = Profiles::<Test>::get(1).unwrap();
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+		assert!(!Profiles::<Test>::contains_key(1));
+		assert_eq!(ArchivedProfiles::<Test>::get(1), Some(profile.clone()));
+		System::assert_has_event(Event::ProfileArchived { account: 1 }.into());
+
+		register_user(1);
+		assert_eq!(Profiles::<Test>::get(1), Some(profile));
+		assert!(!ArchivedProfiles::<Test>::contains_key(1));
+		System::assert_has_event(Event::ProfileRestored { account: 1 }.into());
+	});
+}
+
+#[test]
+fn archived_profiles_can_be_purged() {
+	new_test_ext().execute_with(|| {
+		ArchiveRemovedProfiles::set(true);
+		for who in [1, 2] {
+			register_user(who);
+			create_profile(who);
+			assert_ok!(Profile::set_encryption_key(RuntimeOrigin::signed(who), [9; 32]));
+			assert_ok!(User::remove_user(RuntimeOrigin::signed(who)));
+		}
+		assert_noop!(Profile::purge_archived_profile(RuntimeOrigin::signed(3)), Error::<Test>::ProfileNotFound);
+		assert_noop!(
+			Profile::force_purge_archived_profile(RuntimeOrigin::signed(1), 2),
+			DispatchError::BadOrigin
+		);
+
+		// by the owner
+		assert_ok!(Profile::purge_archived_profile(RuntimeOrigin::signed(1)));
+		assert!(!ArchivedProfiles::<Test>::contains_key(1));
+		assert!(!EncryptionKeys::<Test>::contains_key(1));
+		System::assert_last_event(Event::ArchivedProfilePurged { account: 1 }.into());
+		// registering again starts from scratch
+		register_user(1);
+		assert!(!Profiles::<Test>::contains_key(1));
+
+		// by the admin
+		assert_ok!(Profile::force_purge_archived_profile(RuntimeOrigin::root(), 2));
+		assert!(!ArchivedProfiles::<Test>::contains_key(2));
+		assert!(!EncryptionKeys::<Test>::contains_key(2));
+	});
+}
+
+#[test]
+fn removing_user_without_profile_is_a_no_op() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+		assert!(!System::events()
+			.iter()
+			.any(|r| matches!(r.event, RuntimeEvent::Profile(_))));
+	});
+}
+
+#[test]
+fn profiles_are_counted_and_paged() {
+	new_test_ext().execute_with(|| {
+		for who in 1..=3 {
+			register_user(who);
+			create_profile(who);
+		}
+		assert_eq!(Profiles::<Test>::count(), 3);
+
+		let first = Profile::profiles_page(None, 2);
+		let rest = Profile::profiles_page(first.last().copied(), 2);
+		let mut all = [first, rest].concat();
+		all.sort();
+		assert_eq!(all, vec![1, 2, 3]);
+
+		// cascaded deletions keep the counter in sync
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(3)));
+		assert_eq!(Profiles::<Test>::count(), 2);
+	});
+}
+
+#[test]
+fn migrate_v0_to_v1_rekeys_known_accounts() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<Profile>();
+		let prefix = storage_prefix(b"Profile", b"Profiles");
+		let old_key = |who: u64| [&prefix[..], &Blake2_128::hash(&who.encode())[..]].concat();
+
+		register_user(1);
+		create_profile(1);
+		let profile = v1_profile(&Profiles::<Test>::take(1).unwrap(), b"1994-05-17");
+		// account 1 has a balance; account 9 does not
+		unhashed::put(&old_key(1), &profile);
+		unhashed::put(&old_key(9), &profile);
+
+		migrations::v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+		// the upgrade only schedules the work
+		assert!(migrations::v1::rekey_pending::<Test>());
+		assert_eq!(migrations::v1::old_layout_entries::<Test>(), 2);
+		assert_eq!(Profile::on_chain_storage_version(), StorageVersion::new(1));
+		// a profile waiting for its new key still counts
+		assert_noop!(
+			Profile::create_profile(
+				RuntimeOrigin::signed(1),
+				"Nimal".into(),
+				"".into(),
+				"Perera".into(),
+				MaritalStatus::Single,
+				None,
+				Gender::Male,
+				BloodType::APos,
+				"Sri Lankan".into(),
+				"Buddhism".into(),
+				"Sinhala".into(),
+				Province::Western,
+				District::Colombo,
+				"Colombo".into(),
+				"Colombo 03".into(),
+				300,
+				user::Date::new(1994, 5, 17),
+			),
+			Error::<Test>::DuplicateProfile
+		);
+
+		// one account per step takes several blocks
+		let mut steps = 0;
+		while migrations::v1::rekey_pending::<Test>() {
+			migrations::v1::step::<Test>(1);
+			steps += 1;
+		}
+		assert!(steps > 1);
+
+		assert_eq!(unhashed::get_raw(&Profiles::<Test>::hashed_key_for(1)), Some(profile.encode()));
+		assert_eq!(Profiles::<Test>::count(), 1);
+		assert!(!unhashed::exists(&old_key(1)));
+		// the unknown account can not be re-keyed and is removed once every account is visited
+		assert!(!unhashed::exists(&old_key(9)));
+		assert_eq!(migrations::v1::old_layout_entries::<Test>(), 0);
+	});
+}
+
+#[test]
+fn migrations_after_v1_translate_profiles_awaiting_rekey() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<Profile>();
+		let prefix = storage_prefix(b"Profile", b"Profiles");
+		let old_key = |who: u64| [&prefix[..], &Blake2_128::hash(&who.encode())[..]].concat();
+		register_user(1);
+		create_profile(1);
+		let profile = Profiles::<Test>::take(1).unwrap();
+		unhashed::put(&old_key(1), &v1_profile(&profile, b"1994-05-17"));
+
+		migrations::v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+		migrations::v2::MigrateV1ToV2::<Test>::on_runtime_upgrade();
+		migrations::v3::MigrateV2ToV3::<Test>::on_runtime_upgrade();
+		while migrations::v1::rekey_pending::<Test>() {
+			migrations::v1::step::<Test>(migrations::v1::ACCOUNTS_PER_STEP);
+		}
+
+		assert_eq!(Profiles::<Test>::get(1), Some(UserProfileDataOf::<Test> { picture: None, ..profile }));
+		assert_eq!(Profile::on_chain_storage_version(), StorageVersion::new(3));
+	});
+}
+
+#[test]
+fn profile_details_view_reads_live_profiles_only() {
+	new_test_ext().execute_with(|| {
+		ArchiveRemovedProfiles::set(true);
+		register_user(1);
+		assert_eq!(Profile::profile_details(1), None);
+
+		create_profile(1);
+		assert_ok!(Profile::set_visibility(
+			RuntimeOrigin::signed(1),
+			vec![(ProfileField::BloodGroup, Visibility::Private)].try_into().unwrap(),
+		));
+		// anyone can call it, so it is redacted like for an anonymous requester
+		let details = Profile::profile_details(1).unwrap();
+		assert_eq!(details, Profile::profile_view(&1, None).unwrap());
+		assert_eq!(details.f_name.as_deref(), Some("Nimal"));
+		assert_eq!(details.blood_group, None);
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+		assert!(ArchivedProfiles::<Test>::contains_key(1));
+		assert_eq!(Profile::profile_details(1), None);
+	});
+}
+
+#[test]
+fn recovery_moves_profile_to_new_account() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		assert_ok!(Profile::update_profile(RuntimeOrigin::signed(1), city(b"Kandy")));
+		let profile = Profiles::<Test>::get(1).unwrap();
+		assert_ok!(User::set_recovery_config(RuntimeOrigin::signed(1), vec![2], 1));
+		assert_ok!(User::vouch_recovery(RuntimeOrigin::signed(2), 1, 7));
+
+		System::set_block_number(1 + RecoveryDelay::get());
+		assert_ok!(User::claim_recovery(RuntimeOrigin::signed(2), 1, 7));
+
+		assert_eq!(Profiles::<Test>::get(7), Some(profile));
+		assert!(!Profiles::<Test>::contains_key(1));
+		assert_eq!(Profiles::<Test>::count(), 1);
+		System::assert_has_event(Event::ProfileMoved { from: 1, to: 7 }.into());
+		assert_eq!(RevisionRange::<Test>::get(7), (0, 1));
+		assert_eq!(Revisions::<Test>::get(7, 0).unwrap().previous, city(b"Colombo"));
+		assert!(!RevisionRange::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn recovery_onto_an_archived_profile_is_rejected() {
+	new_test_ext().execute_with(|| {
+		ArchiveRemovedProfiles::set(true);
+		register_user(3);
+		create_profile(3);
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(3)));
+		let archived = ArchivedProfiles::<Test>::get(3).unwrap();
+
+		register_user(1);
+		create_profile(1);
+		assert_ok!(User::set_recovery_config(RuntimeOrigin::signed(1), vec![2], 1));
+		assert_ok!(User::vouch_recovery(RuntimeOrigin::signed(2), 1, 3));
+		System::set_block_number(1 + RecoveryDelay::get());
+
+		assert_noop!(
+			User::claim_recovery(RuntimeOrigin::signed(2), 1, 3),
+			Error::<Test>::ArchivedProfileExists
+		);
+		assert_eq!(ArchivedProfiles::<Test>::get(3), Some(archived));
+		assert!(Profiles::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn linked_accounts_share_the_primary_profile() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		assert_ok!(User::request_link(RuntimeOrigin::signed(7), 1));
+		assert_ok!(User::accept_link(RuntimeOrigin::signed(1), 7));
+
+		// created through the secondary, stored under the primary
+		create_profile(7);
+		assert!(Profiles::<Test>::contains_key(1));
+		assert!(!Profiles::<Test>::contains_key(7));
+		assert_eq!(Profile::profile_details(7), Profile::profile_details(1));
+
+		assert_noop!(
+			Profile::create_profile(
+				RuntimeOrigin::signed(1),
+				"Nimal".into(),
+				"".into(),
+				"Perera".into(),
+				MaritalStatus::Single,
+				None,
+				Gender::Male,
+				BloodType::OPos,
+				"".into(),
+				"".into(),
+				"".into(),
+				Province::Western,
+				District::Colombo,
+				"".into(),
+				"".into(),
+				300,
+				user::Date::new(1994, 5, 17),
+			),
+			Error::<Test>::DuplicateProfile
+		);
+
+		assert_ok!(Profile::remove_profile(RuntimeOrigin::signed(7)));
+		assert!(!Profiles::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn only_active_users_create_and_update_profiles() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		assert_ok!(User::set_user_status(
+			RuntimeOrigin::signed(Registrar::get()),
+			1,
+			user::UserStatus::Suspended
+		));
+
+		assert_noop!(
+			Profile::update_profile(
+				RuntimeOrigin::signed(1),
+				UserProfileDataUpdate { postal_code: Some(10100), ..Default::default() }
+			),
+			Error::<Test>::UserNotActive
+		);
+
+		// self-registered users wait for a registrar before creating a profile
+		assert_ok!(User::create_user(
+			RuntimeOrigin::signed(2),
+			"Kamal".into(),
+			"Silva".into(),
+			"Kandy".into(),
+			user::Date::new(1990, 1, 1),
+		));
+		assert_noop!(
+			Profile::create_profile(
+				RuntimeOrigin::signed(2),
+				"Kamal".into(),
+				"".into(),
+				"Silva".into(),
+				MaritalStatus::Single,
+				None,
+				Gender::Male,
+				BloodType::OPos,
+				"".into(),
+				"".into(),
+				"".into(),
+				Province::Western,
+				District::Gampaha,
+				"".into(),
+				"".into(),
+				20000,
+				user::Date::new(1990, 1, 1),
+			),
+			Error::<Test>::UserNotActive
+		);
+	});
+}
+
+
+#[test]
+#[allow(deprecated)]
+fn get_my_profile_details_is_a_failing_stub() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		assert_noop!(Profile::get_my_profile_details(RuntimeOrigin::signed(1)), Error::<Test>::Deprecated);
+		assert_eq!(
+			RuntimeCall::Profile(crate::Call::get_my_profile_details {}).encode()[1],
+			4,
+			"keeps its call index"
+		);
+	});
+}
+
+#[test]
+fn suspended_owners_can_only_withdraw() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		register_user(2);
+		assert_ok!(Profile::set_encryption_key(RuntimeOrigin::signed(1), [9; 32]));
+		assert_ok!(grant(1, 2, vec![ProfileField::Religion], 10));
+		assert_ok!(User::set_user_status(
+			RuntimeOrigin::signed(Registrar::get()),
+			1,
+			user::UserStatus::Suspended
+		));
+
+		let owner = || RuntimeOrigin::signed(1);
+		let calls: Vec<Box<dyn Fn() -> DispatchResult>> = vec![
+			Box::new(|| Profile::remove_profile(owner())),
+			Box::new(|| Profile::set_visibility(owner(), Default::default())),
+			Box::new(|| grant(1, 3, vec![ProfileField::Religion], 10)),
+			Box::new(|| {
+				Profile::set_encrypted_field(owner(), ProfileField::Religion, sealed(vec![7u8; 60], vec![]))
+			}),
+		];
+		for call in calls {
+			assert_noop!(call(), Error::<Test>::UserNotActive);
+		}
+
+		// consent can still be withdrawn and keys rotated
+		assert_ok!(Profile::set_encryption_key(owner(), [8; 32]));
+		assert_ok!(Profile::revoke_access(owner(), 2));
+		assert!(Grants::<Test>::get(1).is_empty());
+	});
+}
+
+#[test]
+fn district_must_lie_in_province() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		assert_noop!(
+			Profile::create_profile(
+				RuntimeOrigin::signed(1),
+				"Nimal".into(),
+				"".into(),
+				"Perera".into(),
+				MaritalStatus::Single,
+				None,
+				Gender::Male,
+				BloodType::OPos,
+				"".into(),
+				"".into(),
+				"".into(),
+				Province::Southern,
+				District::Colombo,
+				"".into(),
+				"".into(),
+				0,
+				user::Date::new(1994, 5, 17),
+			),
+			Error::<Test>::DistrictNotInProvince
+		);
+
+		create_profile(1);
+		let update = |province, district| {
+			Profile::update_profile(
+				RuntimeOrigin::signed(1),
+				UserProfileDataUpdate { province, district, ..Default::default() },
+			)
+		};
+		assert_noop!(update(Some(Province::Central), None), Error::<Test>::DistrictNotInProvince);
+		assert_noop!(update(None, Some(District::Jaffna)), Error::<Test>::DistrictNotInProvince);
+		assert_ok!(update(Some(Province::Central), Some(District::NuwaraEliya)));
+		assert_ok!(update(None, Some(District::Kandy)));
+
+		let profile = Profiles::<Test>::get(1).unwrap();
+		assert_eq!((profile.province, profile.district), (Province::Central, District::Kandy));
+	});
+}
+
+#[test]
+fn profile_view_redacts_by_visibility() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		assert_ok!(User::request_link(RuntimeOrigin::signed(7), 1));
+		assert_ok!(User::accept_link(RuntimeOrigin::signed(1), 7));
+		create_profile(1);
+
+		assert_ok!(Profile::set_visibility(
+			RuntimeOrigin::signed(7),
+			vec![
+				(ProfileField::BloodGroup, Visibility::Private),
+				(ProfileField::Religion, Visibility::RegistrarsOnly),
+			]
+			.try_into()
+			.unwrap(),
+		));
+		System::assert_last_event(Event::VisibilityChanged { account: 1 }.into());
+
+		let public = Profile::profile_view(&1, None).unwrap();
+		assert_eq!(public.account, 1);
+		assert_eq!(public.f_name.as_deref(), Some("Nimal"));
+		assert_eq!((public.blood_group, public.religion.as_deref()), (None, None));
+
+		let stranger = Profile::profile_view(&1, Some(&2)).unwrap();
+		assert_eq!(stranger, public);
+
+		let registrar = Profile::profile_view(&1, Some(&Registrar::get())).unwrap();
+		assert_eq!(registrar.blood_group, None);
+		assert_eq!(registrar.religion.as_deref(), Some("Buddhism"));
+
+		// the owner sees everything, from any of its linked accounts
+		for owner in [1, 7] {
+			let own = Profile::profile_view(&7, Some(&owner)).unwrap();
+			assert_eq!(own.blood_group, Some(BloodType::OPos));
+			assert_eq!(own.religion.as_deref(), Some("Buddhism"));
+		}
+	});
+}
+
+#[test]
+fn read_auth_proves_the_requester_while_fresh() {
+	new_test_ext().execute_with(|| {
+		let auth = |requester: u64, signer: u64, valid_until: u64| ReadAuthOf::<Test> {
+			requester,
+			valid_until,
+			signature: TestSignature(signer, ReadAuthOf::<Test>::payload(&requester, &valid_until)),
+		};
+
+		assert_eq!(Profile::authenticated(&auth(2, 2, 5)), Some(2));
+		// signed by someone else
+		assert_eq!(Profile::authenticated(&auth(2, 3, 5)), None);
+		// signed for another block
+		let mut moved = auth(2, 2, 5);
+		moved.valid_until = 6;
+		assert_eq!(Profile::authenticated(&moved), None);
+		// valid further ahead than `MaxReadAuthAge`
+		assert_eq!(Profile::authenticated(&auth(2, 2, 12)), None);
+
+		System::set_block_number(6);
+		assert_eq!(Profile::authenticated(&auth(2, 2, 5)), None);
+	});
+}
+
+#[test]
+fn default_visibility_is_set_by_governance() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		let private_birth_date: crate::VisibilityChanges =
+			vec![(ProfileField::BirthDate, Visibility::Private)].try_into().unwrap();
+
+		assert_noop!(
+			Profile::set_default_visibility(RuntimeOrigin::signed(1), private_birth_date.clone()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Profile::set_default_visibility(RuntimeOrigin::root(), private_birth_date));
+		System::assert_last_event(Event::DefaultVisibilityChanged.into());
+		assert_eq!(DefaultVisibility::<Test>::get().get(&ProfileField::BirthDate), Some(&Visibility::Private));
+		assert_eq!(Profile::profile_view(&1, None).unwrap().birth_date, None);
+
+		// a profile's own setting wins over the default
+		assert_ok!(Profile::set_visibility(
+			RuntimeOrigin::signed(1),
+			vec![(ProfileField::BirthDate, Visibility::Public)].try_into().unwrap(),
+		));
+		assert_eq!(
+			Profile::profile_view(&1, None).unwrap().birth_date,
+			Some(user::Date::new(1994, 5, 17))
+		);
+
+		assert_ok!(Profile::remove_profile(RuntimeOrigin::signed(1)));
+		assert!(!FieldVisibility::<Test>::contains_key(1));
+		assert_noop!(
+			Profile::set_visibility(RuntimeOrigin::signed(1), Default::default()),
+			Error::<Test>::ProfileNotFound
+		);
+	});
+}
+
+fn grant(owner: u64, grantee: u64, fields: Vec<ProfileField>, duration: u64) -> DispatchResult {
+	Profile::grant_access(RuntimeOrigin::signed(owner), grantee, fields.try_into().unwrap(), duration)
+}
+
+/// `own` as the owner's copy of a field and `shared` as the copies of its readers.
+fn sealed(own: Vec<u8>, shared: Vec<(u64, Vec<u8>)>) -> SealedFieldOf<Test> {
+	let shared: Vec<_> = shared.into_iter().map(|(reader, blob)| (reader, blob.try_into().unwrap())).collect();
+	SealedField { own: own.try_into().unwrap(), shared: shared.try_into().unwrap() }
+}
+
+#[test]
+fn shared_profile_serves_granted_fields_until_expiry() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		assert_ok!(Profile::set_visibility(
+			RuntimeOrigin::signed(1),
+			vec![(ProfileField::BloodGroup, Visibility::Private)].try_into().unwrap(),
+		));
+		assert_eq!(Profile::shared_profile(&1, &2), None);
+
+		assert_ok!(grant(1, 2, vec![ProfileField::BloodGroup, ProfileField::BirthDate], 10));
+		let fields = [ProfileField::BloodGroup, ProfileField::BirthDate].into_iter().collect();
+		System::assert_last_event(Event::AccessGranted { owner: 1, grantee: 2, fields, expires_at: 11 }.into());
+
+		let shared = Profile::shared_profile(&1, &2).unwrap();
+		assert_eq!(shared.blood_group, Some(BloodType::OPos));
+		assert_eq!(shared.birth_date, Some(user::Date::new(1994, 5, 17)));
+		assert_eq!((shared.f_name, shared.gender), (None, None));
+		// the grant also lifts the visibility of the field in the full view
+		assert_eq!(Profile::profile_view(&1, Some(&2)).unwrap().blood_group, Some(BloodType::OPos));
+		assert_eq!(Profile::profile_view(&1, Some(&3)).unwrap().blood_group, None);
+
+		System::set_block_number(11);
+		assert_eq!(Profile::shared_profile(&1, &2), None);
+		assert_eq!(Profile::profile_view(&1, Some(&2)).unwrap().blood_group, None);
+	});
+}
+
+#[test]
+fn grants_are_validated_bounded_and_revocable() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		assert_noop!(grant(1, 2, vec![ProfileField::City], 10), Error::<Test>::ProfileNotFound);
+		create_profile(1);
+
+		assert_noop!(grant(1, 1, vec![ProfileField::City], 10), Error::<Test>::CannotGrantSelf);
+		assert_noop!(grant(1, 2, vec![], 10), Error::<Test>::NoFieldsGranted);
+		assert_noop!(grant(1, 2, vec![ProfileField::City], 0), Error::<Test>::InvalidGrantDuration);
+		assert_noop!(grant(1, 2, vec![ProfileField::City], 101), Error::<Test>::InvalidGrantDuration);
+
+		assert_ok!(grant(1, 2, vec![ProfileField::City], 10));
+		assert_ok!(grant(1, 3, vec![ProfileField::City], 10));
+		assert_noop!(grant(1, 4, vec![ProfileField::City], 10), Error::<Test>::TooManyGrants);
+
+		// granting again replaces the grant
+		assert_ok!(grant(1, 2, vec![ProfileField::Religion], 100));
+		assert_eq!(Grants::<Test>::get(1).len(), 2);
+		let shared = Profile::shared_profile(&1, &2).unwrap();
+		assert_eq!((shared.city, shared.religion.as_deref()), (None, Some("Buddhism")));
+
+		assert_ok!(Profile::revoke_access(RuntimeOrigin::signed(1), 2));
+		System::assert_last_event(Event::AccessRevoked { owner: 1, grantee: 2 }.into());
+		assert_eq!(Profile::shared_profile(&1, &2), None);
+		assert_noop!(Profile::revoke_access(RuntimeOrigin::signed(1), 2), Error::<Test>::GrantNotFound);
+
+		assert_ok!(Profile::remove_profile(RuntimeOrigin::signed(1)));
+		assert!(!Grants::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn on_idle_sweeps_expired_grants_within_its_weight() {
+	new_test_ext().execute_with(|| {
+		for owner in [1, 2] {
+			register_user(owner);
+			create_profile(owner);
+			assert_ok!(grant(owner, 3, vec![ProfileField::City], 5));
+		}
+		assert_ok!(grant(1, 4, vec![ProfileField::City], 50));
+
+		// one owner per block: cursor plus one owner's grants, events and encrypted fields
+		let one_owner = RocksDbWeight::get().reads_writes(18, 20);
+		System::set_block_number(6);
+		assert_eq!(Profile::on_idle(6, Weight::zero()), Weight::zero());
+
+		assert_eq!(Profile::on_idle(6, one_owner), one_owner);
+		assert!(GrantsCursor::<Test>::get().is_some());
+		assert_eq!(Profile::on_idle(6, one_owner), one_owner);
+
+		assert_eq!(Grants::<Test>::get(1).into_iter().map(|g| g.grantee).collect::<Vec<_>>(), vec![4]);
+		assert!(!Grants::<Test>::contains_key(2));
+		System::assert_has_event(Event::AccessExpired { owner: 2, grantee: 3 }.into());
+
+		// the next pass finds the end of the map and starts over
+		Profile::on_idle(6, Weight::MAX);
+		assert_eq!(GrantsCursor::<Test>::get(), None);
+	});
+}
+
+fn city(name: &[u8]) -> UserProfileDataUpdateOf<Test> {
+	UserProfileDataUpdate { city: Some(name.to_vec().try_into().unwrap()), ..Default::default() }
+}
+
+#[test]
+fn updates_are_recorded_in_a_bounded_history() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		let update = |updates| Profile::update_profile(RuntimeOrigin::signed(1), updates);
+
+		System::set_block_number(2);
+		assert_ok!(update(city(b"Kandy")));
+		// rewriting a value changes nothing, so records nothing
+		assert_ok!(update(city(b"Kandy")));
+		assert_eq!(RevisionRange::<Test>::get(1), (0, 1));
+		let revision = Revisions::<Test>::get(1, 0).unwrap();
+		assert_eq!(revision.block, 2);
+		assert_eq!(revision.changed, [ProfileField::City].into_iter().collect());
+		assert_eq!(revision.previous, city(b"Colombo"));
+
+		for name in [b"Galle".as_slice(), b"Jaffna", b"Matara"] {
+			assert_ok!(update(city(name)));
+		}
+		// `MaxRevisions` is 3, so the first revision made room
+		assert_eq!(RevisionRange::<Test>::get(1), (1, 4));
+		assert!(!Revisions::<Test>::contains_key(1, 0));
+		assert_eq!(Revisions::<Test>::get(1, 3).unwrap().previous, city(b"Jaffna"));
+
+		assert_ok!(Profile::remove_profile(RuntimeOrigin::signed(1)));
+		assert!(!RevisionRange::<Test>::contains_key(1));
+		assert_eq!(Revisions::<Test>::iter_prefix(1).count(), 0);
+	});
+}
+
+#[test]
+fn profile_history_pages_newest_first_and_redacts() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		register_user(2);
+		create_profile(1);
+		assert_ok!(Profile::set_visibility(
+			RuntimeOrigin::signed(1),
+			vec![(ProfileField::Religion, Visibility::Private)].try_into().unwrap(),
+		));
+		assert_ok!(Profile::update_profile(RuntimeOrigin::signed(1), city(b"Kandy")));
+		assert_ok!(Profile::update_profile(
+			RuntimeOrigin::signed(1),
+			UserProfileDataUpdate {
+				religion: Some(b"Hinduism".to_vec().try_into().unwrap()),
+				postal_code: Some(400),
+				..Default::default()
+			},
+		));
+		let numbers = |page: &[RevisionView<u64, u64>]| page.iter().map(|r| r.number).collect::<Vec<_>>();
+
+		let own = Profile::profile_history(&1, Some(&1), None, 10);
+		assert_eq!(numbers(&own), vec![1, 0]);
+		assert_eq!(own[0].previous.religion.as_deref(), Some("Buddhism"));
+		assert_eq!(own[0].previous.postal_code, Some(300));
+		assert_eq!(own[1].previous.city.as_deref(), Some("Colombo"));
+		assert_eq!(own[1].previous.f_name, None);
+
+		let public = Profile::profile_history(&1, Some(&2), None, 10);
+		assert_eq!(public[0].changed, [ProfileField::PostalCode].into_iter().collect());
+		assert_eq!(public[0].previous.religion, None);
+		assert_eq!(public[0].previous.postal_code, Some(300));
+
+		assert_eq!(numbers(&Profile::profile_history(&1, None, None, 1)), vec![1]);
+		assert_eq!(numbers(&Profile::profile_history(&1, None, Some(1), 10)), vec![0]);
+		assert!(Profile::profile_history(&3, None, None, 10).is_empty());
+	});
+}
+
+#[test]
+fn on_idle_prunes_history_past_retention() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		for (block, name) in [(2, b"Kandy".as_slice()), (8, b"Galle")] {
+			System::set_block_number(block);
+			assert_ok!(Profile::update_profile(RuntimeOrigin::signed(1), city(name)));
+		}
+		// without a retention revisions stay until pushed out
+		Profile::on_idle(100, Weight::MAX);
+		assert_eq!(RevisionRange::<Test>::get(1), (0, 2));
+
+		assert_noop!(
+			Profile::set_history_retention(RuntimeOrigin::signed(1), Some(5)),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Profile::set_history_retention(RuntimeOrigin::root(), Some(5)));
+		System::assert_last_event(Event::HistoryRetentionSet { retention: Some(5) }.into());
+		assert_eq!(Profile::on_idle(10, Weight::zero()), Weight::zero());
+
+		Profile::on_idle(10, Weight::MAX);
+		assert_eq!(RevisionRange::<Test>::get(1), (1, 2));
+		assert!(!Revisions::<Test>::contains_key(1, 0));
+		assert_eq!(HistoryCursor::<Test>::get(), None);
+
+		Profile::on_idle(14, Weight::MAX);
+		assert!(!RevisionRange::<Test>::contains_key(1));
+		assert_eq!(Revisions::<Test>::iter_prefix(1).count(), 0);
+	});
+}
+
+#[test]
+fn verifiers_are_managed_by_admin_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Profile::add_verifier(RuntimeOrigin::signed(1), 5), DispatchError::BadOrigin);
+		assert_ok!(Profile::add_verifier(RuntimeOrigin::root(), 5));
+		System::assert_last_event(Event::VerifierAdded { verifier: 5 }.into());
+		assert_noop!(Profile::add_verifier(RuntimeOrigin::root(), 5), Error::<Test>::AlreadyVerifier);
+		assert_ok!(Profile::add_verifier(RuntimeOrigin::root(), 6));
+		// `MaxVerifiers` is 2
+		assert_noop!(Profile::add_verifier(RuntimeOrigin::root(), 7), Error::<Test>::TooManyVerifiers);
+
+		assert_noop!(Profile::remove_verifier(RuntimeOrigin::signed(5), 5), DispatchError::BadOrigin);
+		assert_ok!(Profile::remove_verifier(RuntimeOrigin::root(), 5));
+		System::assert_last_event(Event::VerifierRemoved { verifier: 5 }.into());
+		assert_noop!(Profile::remove_verifier(RuntimeOrigin::root(), 5), Error::<Test>::NotVerifier);
+		assert_eq!(Verifiers::<Test>::get().into_inner(), vec![6]);
+	});
+}
+
+#[test]
+fn judgements_are_dropped_when_their_field_is_written() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		register_user(2);
+		create_profile(1);
+		let judge = |verifier, owner, field, judgement| {
+			Profile::judge_field(RuntimeOrigin::signed(verifier), owner, field, judgement)
+		};
+
+		assert_noop!(
+			judge(2, 1, ProfileField::City, Judgement::KnownGood),
+			Error::<Test>::NotVerifier
+		);
+		for verifier in [2, 3] {
+			assert_ok!(Profile::add_verifier(RuntimeOrigin::root(), verifier));
+		}
+		assert_noop!(
+			judge(2, 2, ProfileField::City, Judgement::KnownGood),
+			Error::<Test>::CannotJudgeSelf
+		);
+		assert_noop!(
+			judge(2, 4, ProfileField::City, Judgement::KnownGood),
+			Error::<Test>::ProfileNotFound
+		);
+
+		assert_ok!(judge(2, 1, ProfileField::City, Judgement::Reasonable));
+		// a verifier's later judgement replaces its earlier one
+		assert_ok!(judge(2, 1, ProfileField::City, Judgement::KnownGood));
+		System::assert_last_event(
+			Event::FieldJudged {
+				owner: 1,
+				field: ProfileField::City,
+				verifier: 2,
+				judgement: Judgement::KnownGood,
+			}
+			.into(),
+		);
+		assert_ok!(judge(3, 1, ProfileField::City, Judgement::Unverified));
+		assert_ok!(judge(3, 1, ProfileField::Religion, Judgement::Reasonable));
+		assert_eq!(
+			Profile::verification(&1),
+			vec![
+				(ProfileField::Religion, vec![(3, Judgement::Reasonable)]),
+				(ProfileField::City, vec![(2, Judgement::KnownGood), (3, Judgement::Unverified)]),
+			]
+		);
+
+		// judgements of removed verifiers are not served
+		assert_ok!(Profile::remove_verifier(RuntimeOrigin::root(), 3));
+		assert_eq!(
+			Profile::verification(&1),
+			vec![(ProfileField::City, vec![(2, Judgement::KnownGood)])]
+		);
+		assert_ok!(Profile::add_verifier(RuntimeOrigin::root(), 3));
+
+		// writing a field drops its judgements, other fields keep theirs
+		assert_ok!(Profile::update_profile(RuntimeOrigin::signed(1), city(b"Kandy")));
+		System::assert_has_event(
+			Event::JudgementsInvalidated {
+				account: 1,
+				fields: [ProfileField::City].into_iter().collect(),
+			}
+			.into(),
+		);
+		assert!(!Judgements::<Test>::contains_key(1, ProfileField::City));
+		assert_eq!(
+			Profile::verification(&1),
+			vec![(ProfileField::Religion, vec![(3, Judgement::Reasonable)])]
+		);
+
+		assert_ok!(Profile::remove_profile(RuntimeOrigin::signed(1)));
+		assert_eq!(Judgements::<Test>::iter_prefix(1).count(), 0);
+		assert!(Profile::verification(&1).is_empty());
+	});
+}
+
+#[test]
+fn encrypted_fields_replace_their_plaintext() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		let encrypt = |field, blob: Vec<u8>| {
+			Profile::set_encrypted_field(RuntimeOrigin::signed(1), field, sealed(blob, vec![]))
+		};
+		let blob = vec![7u8; 60];
+
+		assert_noop!(encrypt(ProfileField::Religion, blob.clone()), Error::<Test>::NoEncryptionKey);
+		assert_ok!(Profile::set_encryption_key(RuntimeOrigin::signed(1), [9; 32]));
+		System::assert_last_event(Event::EncryptionKeySet { account: 1 }.into());
+		assert_noop!(encrypt(ProfileField::District, blob.clone()), Error::<Test>::FieldNotEncryptable);
+		assert_noop!(encrypt(ProfileField::Religion, vec![7; 47]), Error::<Test>::InvalidCiphertext);
+
+		assert_ok!(encrypt(ProfileField::Religion, blob.clone()));
+		assert_ok!(encrypt(ProfileField::BloodGroup, blob.clone()));
+		System::assert_last_event(
+			Event::FieldEncrypted { account: 1, field: ProfileField::BloodGroup }.into(),
+		);
+		let stored = Profiles::<Test>::get(1).unwrap();
+		assert!(stored.religion.is_empty());
+		assert_eq!(stored.blood_group, BloodType::Withheld);
+
+		// not even the owner gets an encrypted field in plaintext
+		let own = Profile::profile_view(&1, Some(&1)).unwrap();
+		assert_eq!((own.religion, own.blood_group), (None, None));
+		assert_eq!(own.gender, Some(Gender::Male));
+		let (public, fields) = Profile::encrypted_fields(&1, &1).unwrap();
+		assert_eq!(public, [9; 32]);
+		assert_eq!(fields.len(), 2);
+		assert!(fields.contains(&(ProfileField::Religion, blob)));
+
+		// writing a field in plaintext drops its ciphertext
+		assert_ok!(Profile::update_profile(
+			RuntimeOrigin::signed(1),
+			UserProfileDataUpdate { blood_group: Some(BloodType::APos), ..Default::default() },
+		));
+		assert!(!EncryptedFields::<Test>::contains_key(1, ProfileField::BloodGroup));
+		assert!(EncryptedFields::<Test>::contains_key(1, ProfileField::Religion));
+		assert_eq!(Profile::profile_view(&1, None).unwrap().blood_group, Some(BloodType::APos));
+
+		assert_ok!(Profile::remove_profile(RuntimeOrigin::signed(1)));
+		assert!(!EncryptionKeys::<Test>::contains_key(1));
+		assert_eq!(EncryptedFields::<Test>::iter_prefix(1).count(), 0);
+	});
+}
+
+#[test]
+fn encrypted_fields_are_sealed_to_each_reader() {
+	new_test_ext().execute_with(|| {
+		for who in [1, 2, 3] {
+			register_user(who);
+		}
+		create_profile(1);
+		assert_ok!(Profile::set_encryption_key(RuntimeOrigin::signed(1), [9; 32]));
+		let (own, copy) = (vec![7u8; 60], vec![8u8; 60]);
+		let encrypt = |field, shared| {
+			Profile::set_encrypted_field(RuntimeOrigin::signed(1), field, sealed(own.clone(), shared))
+		};
+		let religion = ProfileField::Religion;
+		assert_ok!(grant(1, 2, vec![religion], 10));
+
+		assert_noop!(encrypt(religion, vec![(2, copy.clone())]), Error::<Test>::NoEncryptionKey);
+		// readers register their own key, with or without a profile
+		assert_ok!(Profile::set_encryption_key(RuntimeOrigin::signed(2), [8; 32]));
+		assert_noop!(encrypt(religion, vec![(2, vec![8; 47])]), Error::<Test>::InvalidCiphertext);
+		assert_noop!(encrypt(religion, vec![(3, copy.clone())]), Error::<Test>::FieldNotShared);
+		assert_noop!(encrypt(ProfileField::City, vec![(2, copy.clone())]), Error::<Test>::FieldNotShared);
+		assert_noop!(
+			encrypt(religion, vec![(2, copy.clone()), (2, copy.clone())]),
+			Error::<Test>::DuplicateReader
+		);
+
+		assert_ok!(encrypt(religion, vec![(2, copy.clone())]));
+		assert_eq!(Profile::encrypted_fields(&1, &1), Some(([9; 32], vec![(religion, own.clone())])));
+		assert_eq!(Profile::encrypted_fields(&1, &2), Some(([8; 32], vec![(religion, copy.clone())])));
+		assert_eq!(Profile::encrypted_fields(&1, &3), None);
+		let copies = || EncryptedFields::<Test>::get(1, religion).unwrap().shared.len();
+
+		// narrowing a grant drops the copies of the fields it no longer covers
+		assert_ok!(grant(1, 2, vec![ProfileField::City], 10));
+		assert_eq!(copies(), 0);
+		assert_eq!(Profile::encrypted_fields(&1, &2), Some(([8; 32], vec![])));
+
+		assert_ok!(grant(1, 2, vec![religion], 10));
+		assert_ok!(encrypt(religion, vec![(2, copy.clone())]));
+		assert_ok!(Profile::revoke_access(RuntimeOrigin::signed(1), 2));
+		assert_eq!(copies(), 0);
+
+		assert_ok!(grant(1, 2, vec![religion], 1));
+		assert_ok!(encrypt(religion, vec![(2, copy.clone())]));
+		// an expired grant serves no copy and loses it in the sweep
+		System::set_block_number(2);
+		assert_eq!(Profile::encrypted_fields(&1, &2), Some(([8; 32], vec![])));
+		Profile::on_idle(2, Weight::MAX);
+		assert_eq!(copies(), 0);
+		assert_eq!(EncryptedFields::<Test>::get(1, religion).unwrap().own.to_vec(), own);
+	});
+}
+
+#[test]
+fn encrypting_a_field_purges_its_plaintext() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		register_user(2);
+		create_profile(1);
+		assert_ok!(Profile::update_profile(RuntimeOrigin::signed(1), city(b"Kandy")));
+		assert_ok!(Profile::update_profile(
+			RuntimeOrigin::signed(1),
+			UserProfileDataUpdate {
+				city: Some(b"Galle".to_vec().try_into().unwrap()),
+				religion: Some(b"Hinduism".to_vec().try_into().unwrap()),
+				..Default::default()
+			},
+		));
+		assert_ok!(Profile::add_verifier(RuntimeOrigin::root(), 2));
+		for field in [ProfileField::City, ProfileField::Religion] {
+			assert_ok!(Profile::judge_field(RuntimeOrigin::signed(2), 1, field, Judgement::KnownGood));
+		}
+
+		assert_ok!(Profile::set_encryption_key(RuntimeOrigin::signed(1), [9; 32]));
+		assert_ok!(Profile::set_encrypted_field(
+			RuntimeOrigin::signed(1),
+			ProfileField::City,
+			sealed(vec![7; 60], vec![])
+		));
+
+		// both earlier cities are gone from the history, the earlier religion is kept
+		assert!(Revisions::<Test>::get(1, 0).unwrap().changed.is_empty());
+		assert_eq!(Revisions::<Test>::get(1, 0).unwrap().previous, Default::default());
+		let revision = Revisions::<Test>::get(1, 1).unwrap();
+		assert_eq!(revision.changed, [ProfileField::Religion].into_iter().collect());
+		assert_eq!(revision.previous.city, None);
+		assert_eq!(revision.previous.religion.unwrap().to_vec(), b"Buddhism".to_vec());
+
+		System::assert_has_event(
+			Event::JudgementsInvalidated {
+				account: 1,
+				fields: [ProfileField::City].into_iter().collect(),
+			}
+			.into(),
+		);
+		assert!(!Judgements::<Test>::contains_key(1, ProfileField::City));
+		assert!(Judgements::<Test>::contains_key(1, ProfileField::Religion));
+	});
+}
+
+#[test]
+fn district_indices_are_append_only() {
+	// `Colombo` and `Gampaha` predate the other districts and must keep their encoding
+	assert_eq!(District::Colombo.encode(), vec![0]);
+	assert_eq!(District::Gampaha.encode(), vec![1]);
+	assert_eq!(District::Kegalle.encode(), vec![24]);
+	assert_eq!(Province::Sabaragamuwa.encode(), vec![8]);
+}
+
+#[test]
+fn profile_text_is_validated_and_trimmed() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		let update = |updates: UserProfileDataUpdateOf<Test>| {
+			Profile::update_profile(RuntimeOrigin::signed(1), updates)
+		};
+		let bytes = |raw: &[u8]| raw.to_vec().try_into().unwrap();
+
+		assert_noop!(
+			update(UserProfileDataUpdate { city: Some(bytes(&[0xff, 0xfe])), ..Default::default() }),
+			Error::<Test>::InvalidCity
+		);
+		assert_noop!(
+			update(UserProfileDataUpdate {
+				m_name: Some(b"K2".to_vec().try_into().unwrap()),
+				..Default::default()
+			}),
+			Error::<Test>::InvalidMiddleName
+		);
+		assert_noop!(
+			update(UserProfileDataUpdate { postal_code: Some(100_000), ..Default::default() }),
+			Error::<Test>::InvalidPostalCode
+		);
+
+		assert_ok!(update(UserProfileDataUpdate {
+			division: Some(bytes(b"  Colombo 07 ")),
+			..Default::default()
+		}));
+		assert_eq!(Profiles::<Test>::get(1).unwrap().division.to_vec(), b"Colombo 07".to_vec());
+	});
+}
+
+#[test]
+fn birth_date_must_be_a_real_past_date() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		let set_birth_date = |date| {
+			Profile::update_profile(
+				RuntimeOrigin::signed(1),
+				UserProfileDataUpdate { birth_date: Some(date), ..Default::default() },
+			)
+		};
+
+		for bad in [
+			user::Date::new(2023, 2, 29),
+			user::Date::new(2023, 9, 31),
+			user::Date::new(2023, 13, 1),
+			// the day after `mock::NOW`
+			user::Date::new(2026, 10, 18),
+		] {
+			assert_noop!(set_birth_date(bad), Error::<Test>::InvalidBirthDate);
+		}
+
+		assert_ok!(set_birth_date(user::Date::new(2024, 2, 29)));
+		assert_eq!(Profiles::<Test>::get(1).unwrap().birth_date, Some(user::Date::new(2024, 2, 29)));
+	});
+}
+
+#[test]
+fn migrate_v1_to_v2_parses_birth_dates() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(1).put::<Profile>();
+		register_user(1);
+		create_profile(1);
+		let profile = Profiles::<Test>::get(1).unwrap();
+		unhashed::put(&Profiles::<Test>::hashed_key_for(1), &v1_profile(&profile, b"1994-05-17"));
+		unhashed::put(&Profiles::<Test>::hashed_key_for(2), &v1_profile(&profile, b"2023-99-99"));
+		unhashed::put(&ArchivedProfiles::<Test>::hashed_key_for(3), &v1_profile(&profile, b"2030-01-01"));
+
+		migrations::v2::MigrateV1ToV2::<Test>::on_runtime_upgrade();
+
+		let v2_profile = |key: Vec<u8>| unhashed::get::<migrations::v3::OldProfile>(&key).unwrap();
+		assert_eq!(
+			v2_profile(Profiles::<Test>::hashed_key_for(1)).encode(),
+			v1_profile(&profile, b"").with_birth_date(profile.birth_date).encode()
+		);
+		let unparsed = v2_profile(Profiles::<Test>::hashed_key_for(2));
+		assert_eq!(unparsed.birth_date, None);
+		assert_eq!(unparsed.f_name, profile.f_name.to_vec());
+		assert_eq!(v2_profile(ArchivedProfiles::<Test>::hashed_key_for(3)).birth_date, None);
+		assert_eq!(Profile::on_chain_storage_version(), StorageVersion::new(2));
+	});
+}
+
+#[test]
+fn migrate_v2_to_v3_clears_picture_paths() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(2).put::<Profile>();
+		register_user(1);
+		create_profile(1);
+		let profile = Profiles::<Test>::get(1).unwrap();
+		let v2_profile = || v1_profile(&profile, b"").with_birth_date(profile.birth_date);
+		unhashed::put(&Profiles::<Test>::hashed_key_for(1), &v2_profile());
+		unhashed::put(&Profiles::<Test>::hashed_key_for(2), &v2_profile().with_picture(Vec::<u8>::new()));
+		unhashed::put(&ArchivedProfiles::<Test>::hashed_key_for(3), &v2_profile());
+
+		migrations::v3::MigrateV2ToV3::<Test>::on_runtime_upgrade();
+
+		let cleared = UserProfileDataOf::<Test> { picture: None, ..profile };
+		assert_eq!(Profiles::<Test>::get(1), Some(cleared.clone()));
+		assert_eq!(Profiles::<Test>::get(2), Some(cleared.clone()));
+		assert_eq!(ArchivedProfiles::<Test>::get(3), Some(cleared));
+		assert_eq!(Profile::on_chain_storage_version(), StorageVersion::new(3));
+	});
+}
+
+#[test]
+fn migrate_v3_to_v4_seals_to_owners_and_purges_plaintext() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(3).put::<Profile>();
+		register_user(1);
+		register_user(2);
+		create_profile(1);
+		assert_ok!(Profile::update_profile(RuntimeOrigin::signed(1), city(b"Kandy")));
+		assert_ok!(Profile::add_verifier(RuntimeOrigin::root(), 2));
+		assert_ok!(Profile::judge_field(RuntimeOrigin::signed(2), 1, ProfileField::City, Judgement::KnownGood));
+		// a v3 field: the bare blob sealed to the owner, its plaintext left behind
+		let blob = vec![7u8; 60];
+		unhashed::put(&EncryptedFields::<Test>::hashed_key_for(1, ProfileField::City), &blob);
+
+		migrations::v4::MigrateV3ToV4::<Test>::on_runtime_upgrade();
+
+		assert_eq!(EncryptedFields::<Test>::get(1, ProfileField::City), Some(sealed(blob, vec![])));
+		assert_eq!(Revisions::<Test>::get(1, 0).unwrap().previous.city, None);
+		assert!(!Judgements::<Test>::contains_key(1, ProfileField::City));
+		assert_eq!(Profile::on_chain_storage_version(), StorageVersion::new(4));
+	});
+}
+
+#[test]
+fn pictures_are_bounded_and_removable() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		let set_picture = |picture| {
+			Profile::update_profile(
+				RuntimeOrigin::signed(1),
+				UserProfileDataUpdate { picture: Some(picture), ..Default::default() },
+			)
+		};
+
+		for size in [0, MaxPictureSize::get() + 1] {
+			assert_noop!(set_picture(Some(PictureRef { size, ..picture() })), Error::<Test>::InvalidPicture);
+		}
+		assert!(picture().matches(PICTURE));
+		assert!(!picture().matches(b"\x89PNG"));
+		assert!(!PictureRef { algorithm: HashAlgorithm::Sha2_256, ..picture() }.matches(PICTURE));
+
+		assert_ok!(set_picture(None));
+		assert_eq!(Profiles::<Test>::get(1).unwrap().picture, None);
+	});
+}
+
+#[test]
+fn truncate_to_bounds_fits_long_profiles() {
+	new_test_ext().execute_with(|| {
+		register_user(1);
+		create_profile(1);
+		let profile = Profiles::<Test>::get(1).unwrap();
+		let long = migrations::bounds::UnboundedProfile {
+			f_name: profile.f_name.to_vec(),
+			m_name: "க".repeat(22).into_bytes(),
+			l_name: profile.l_name.to_vec(),
+			marital_status: profile.marital_status,
+			picture: profile.picture,
+			gender: profile.gender,
+			blood_group: profile.blood_group,
+			nationality: profile.nationality.to_vec(),
+			religion: profile.religion.to_vec(),
+			lit_lang: profile.lit_lang.to_vec(),
+			province: profile.province,
+			district: profile.district,
+			city: profile.city.to_vec(),
+			division: profile.division.to_vec(),
+			postal_code: profile.postal_code,
+			birth_date: profile.birth_date,
+		};
+		unhashed::put(&Profiles::<Test>::hashed_key_for(1), &long);
+
+		migrations::bounds::TruncateToBounds::<Test>::on_runtime_upgrade();
+
+		let fitted = Profiles::<Test>::get(1).unwrap();
+		assert_eq!(fitted.m_name.to_vec(), "க".repeat(21).into_bytes());
+		assert_eq!(fitted.f_name, profile.f_name);
+	});
+}
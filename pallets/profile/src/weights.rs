@@ -0,0 +1,215 @@
+//! Weights for `profile`.
+//!
+//! NOT BENCHMARK RESULTS. Until the runtime is benchmarked on reference hardware these are
+//! deliberately conservative formulas over the same components as `benchmarking.rs`: every
+//! call is charged the storage reads and writes it performs in its worst case at `DbWeight`,
+//! a flat [`BASE`] of execution time, [`PER_BYTE`] per byte of text input and [`PER_ITEM`]
+//! per field, reader or revision it walks. Proof size is not charged; the solochain does not
+//! bound it.
+//!
+//! Replace this file with the output of
+//! `solochain-template-node benchmark pallet --chain dev --pallet profile --extrinsic '*'
+//! --steps 50 --repeat 20 --wasm-execution compiled --output pallets/profile/src/weights.rs`
+//! once that can be run.
+
+#![allow(missing_docs)]
+
+use crate::PROFILE_FIELDS;
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, RuntimeDbWeight, Weight},
+};
+use core::marker::PhantomData;
+
+/// Execution time charged to every call, in picoseconds.
+pub const BASE: u64 = 100_000_000;
+/// Execution time charged per byte of text input, in picoseconds.
+pub const PER_BYTE: u64 = 20_000;
+/// Execution time charged per field, reader or revision walked, in picoseconds.
+pub const PER_ITEM: u64 = 5_000_000;
+
+const FIELDS: u64 = PROFILE_FIELDS as u64;
+
+/// `BASE` plus `bytes` times `PER_BYTE`, `items` times `PER_ITEM`, and `reads` and `writes`
+/// at `db`.
+fn formula(db: RuntimeDbWeight, bytes: u32, items: u32, reads: u64, writes: u64) -> Weight {
+    let ref_time = BASE
+        .saturating_add(PER_BYTE.saturating_mul(bytes.into()))
+        .saturating_add(PER_ITEM.saturating_mul(items.into()));
+    Weight::from_parts(ref_time, 0).saturating_add(db.reads_writes(reads, writes))
+}
+
+/// Weight functions needed for `profile`. `n` and `t` are the longest name and text given,
+/// `g` the number of readers a field is sealed to and `r` the number of revisions kept.
+pub trait WeightInfo {
+    /// Reads `User::PrimaryOf`, the user record twice, the re-key cursor, `Profiles` and
+    /// `Timestamp::Now`; writes `Profiles` and one event.
+    fn create_profile(n: u32, t: u32) -> Weight;
+    /// Reads `User::PrimaryOf`, the user record, `Profiles`, `RevisionRange` and every field's
+    /// `EncryptedFields` and `Judgements`; writes those, `Profiles`, the new and the dropped
+    /// revision and two events.
+    fn update_profile(n: u32, t: u32) -> Weight;
+    /// Reads `User::PrimaryOf`, the user record and `Profiles`; clears `Profiles`,
+    /// `FieldVisibility`, `Grants`, `EncryptionKeys`, `RevisionRange`, every field's
+    /// `EncryptedFields` and `Judgements` and `r` revisions, and deposits one event.
+    fn remove_profile(r: u32) -> Weight;
+    /// Reads `User::PrimaryOf`, the user record, `Profiles` and `FieldVisibility`; writes
+    /// `FieldVisibility` and one event.
+    fn set_visibility() -> Weight;
+    /// Reads and writes `DefaultVisibility`, one event.
+    fn set_default_visibility() -> Weight;
+    /// Reads both accounts' `User::PrimaryOf`, the user record, `Profiles`, `Grants` and every
+    /// field's `EncryptedFields`; writes `Grants`, the `EncryptedFields` and one event.
+    fn grant_access() -> Weight;
+    /// Reads `User::PrimaryOf`, `Grants` and every field's `EncryptedFields`; writes `Grants`,
+    /// the `EncryptedFields` and one event.
+    fn revoke_access() -> Weight;
+    /// Reads `User::PrimaryOf`; writes `EncryptionKeys` and one event.
+    fn set_encryption_key() -> Weight;
+    /// Reads `User::PrimaryOf`, the user record, `EncryptionKeys`, `Grants`, `PrimaryOf` and
+    /// `EncryptionKeys` for each of `g` readers, `Profiles`, `RevisionRange`, `r` revisions and
+    /// `Judgements`; writes `Profiles`, the revisions, `Judgements`, `EncryptedFields` and two
+    /// events.
+    fn set_encrypted_field(g: u32, r: u32) -> Weight;
+    /// Writes `HistoryRetention` and one event.
+    fn set_history_retention() -> Weight;
+    /// Reads and writes `Verifiers`, one event.
+    fn add_verifier() -> Weight;
+    /// Reads and writes `Verifiers`, one event.
+    fn remove_verifier() -> Weight;
+    /// Reads `Verifiers`, both accounts' `User::PrimaryOf`, `Profiles` and `Judgements`; writes
+    /// `Judgements` and one event.
+    fn judge_field() -> Weight;
+    /// Takes `ArchivedProfiles`; clears `FieldVisibility`, `EncryptionKeys`, `RevisionRange`,
+    /// every field's `EncryptedFields` and `Judgements` and `r` revisions, and deposits one
+    /// event.
+    fn purge_archived_profile(r: u32) -> Weight;
+    /// As `purge_archived_profile`, behind `AdminOrigin`.
+    fn force_purge_archived_profile(r: u32) -> Weight;
+    /// `OnUserRemoved` without an archive: takes `Profiles` and clears what `remove_profile`
+    /// clears.
+    fn on_user_removed(r: u32) -> Weight;
+    /// `OnUserCreated`: takes `ArchivedProfiles`, writes `Profiles` and one event.
+    fn on_user_created() -> Weight;
+    /// `OnUserMoved`: checks the target's archive, then takes every map of the old account,
+    /// each field's `EncryptedFields` and `Judgements` and `r` revisions, and writes them under
+    /// the new one, with one event.
+    fn on_user_moved(r: u32) -> Weight;
+}
+
+/// The formulas, with storage accesses at the runtime's `DbWeight`.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn create_profile(n: u32, t: u32) -> Weight {
+        formula(T::DbWeight::get(), 3 * n + 5 * t, 0, 8, 2)
+    }
+    fn update_profile(n: u32, t: u32) -> Weight {
+        formula(T::DbWeight::get(), 3 * n + 5 * t, PROFILE_FIELDS, 5 + 2 * FIELDS, 6 + 2 * FIELDS)
+    }
+    fn remove_profile(r: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 4 + 2 * FIELDS + u64::from(r), 6 + 2 * FIELDS + u64::from(r))
+    }
+    fn set_visibility() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 5, 2)
+    }
+    fn set_default_visibility() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1, 2)
+    }
+    fn grant_access() -> Weight {
+        formula(T::DbWeight::get(), 0, PROFILE_FIELDS, 6 + FIELDS, 2 + FIELDS)
+    }
+    fn revoke_access() -> Weight {
+        formula(T::DbWeight::get(), 0, PROFILE_FIELDS, 3 + FIELDS, 2 + FIELDS)
+    }
+    fn set_encryption_key() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1, 2)
+    }
+    fn set_encrypted_field(g: u32, r: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, g * g + r, 7 + 3 * u64::from(g) + u64::from(r), 5 + u64::from(r))
+    }
+    fn set_history_retention() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 0, 2)
+    }
+    fn add_verifier() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1, 2)
+    }
+    fn remove_verifier() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1, 2)
+    }
+    fn judge_field() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 5, 2)
+    }
+    fn purge_archived_profile(r: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 2 + 2 * FIELDS + u64::from(r), 5 + 2 * FIELDS + u64::from(r))
+    }
+    fn force_purge_archived_profile(r: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 2 + 2 * FIELDS + u64::from(r), 5 + 2 * FIELDS + u64::from(r))
+    }
+    fn on_user_removed(r: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1 + 2 * FIELDS + u64::from(r), 6 + 2 * FIELDS + u64::from(r))
+    }
+    fn on_user_created() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1, 3)
+    }
+    fn on_user_moved(r: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 10 + 2 * FIELDS + u64::from(r), 15 + 4 * FIELDS + 2 * u64::from(r))
+    }
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+    fn create_profile(n: u32, t: u32) -> Weight {
+        formula(RocksDbWeight::get(), 3 * n + 5 * t, 0, 8, 2)
+    }
+    fn update_profile(n: u32, t: u32) -> Weight {
+        formula(RocksDbWeight::get(), 3 * n + 5 * t, PROFILE_FIELDS, 5 + 2 * FIELDS, 6 + 2 * FIELDS)
+    }
+    fn remove_profile(r: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 4 + 2 * FIELDS + u64::from(r), 6 + 2 * FIELDS + u64::from(r))
+    }
+    fn set_visibility() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 5, 2)
+    }
+    fn set_default_visibility() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1, 2)
+    }
+    fn grant_access() -> Weight {
+        formula(RocksDbWeight::get(), 0, PROFILE_FIELDS, 6 + FIELDS, 2 + FIELDS)
+    }
+    fn revoke_access() -> Weight {
+        formula(RocksDbWeight::get(), 0, PROFILE_FIELDS, 3 + FIELDS, 2 + FIELDS)
+    }
+    fn set_encryption_key() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1, 2)
+    }
+    fn set_encrypted_field(g: u32, r: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, g * g + r, 7 + 3 * u64::from(g) + u64::from(r), 5 + u64::from(r))
+    }
+    fn set_history_retention() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 0, 2)
+    }
+    fn add_verifier() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1, 2)
+    }
+    fn remove_verifier() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1, 2)
+    }
+    fn judge_field() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 5, 2)
+    }
+    fn purge_archived_profile(r: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 2 + 2 * FIELDS + u64::from(r), 5 + 2 * FIELDS + u64::from(r))
+    }
+    fn force_purge_archived_profile(r: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 2 + 2 * FIELDS + u64::from(r), 5 + 2 * FIELDS + u64::from(r))
+    }
+    fn on_user_removed(r: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1 + 2 * FIELDS + u64::from(r), 6 + 2 * FIELDS + u64::from(r))
+    }
+    fn on_user_created() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1, 3)
+    }
+    fn on_user_moved(r: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 10 + 2 * FIELDS + u64::from(r), 15 + 4 * FIELDS + 2 * u64::from(r))
+    }
+}
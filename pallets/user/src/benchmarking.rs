@@ -1,13 +1,128 @@
-// pallets/foo/src/benchmarking.rs
+// pallets/user/src/benchmarking.rs
 #![cfg(feature = "runtime-benchmarks")]
 
 use super::*;
-use frame_support::{BoundedVec, traits::Get};
-use frame_support::pallet_prelude::ConstU32;
+use frame_support::traits::{fungible::{Inspect, Mutate}, EnsureOrigin, Get};
+use frame_support::pallet_prelude::MaxEncodedLen;
+use frame_support::sp_runtime::Saturating;
 use scale_info::prelude::string::String;
 use frame_benchmarking::v2::*;
 use frame_system::RawOrigin;
-use sp_std::vec;
+use sp_std::{vec, vec::Vec};
+
+/* -------------------------------------------------------------------- *
+ *  Helpers                                                             *
+ * -------------------------------------------------------------------- */
+
+/// Not in the future even when the chain has no timestamp yet.
+const BIRTH_DATE: Date = Date::new(1969, 12, 31);
+
+/// `len` copies of `c` as a `String`.
+fn filler(c: u8, len: u32) -> String {
+    String::from_utf8(vec![c; len as usize]).unwrap()
+}
+
+/// Whitelisted caller with enough free balance for the largest possible deposit.
+fn funded_caller<T: Config>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    fund::<T>(&caller);
+    caller
+}
+
+/// Give `who` enough free balance for the largest possible deposit.
+fn fund<T: Config>(who: &T::AccountId) {
+    let max_bytes: BalanceOf<T> = (UserDetailsOf::<T>::max_encoded_len() as u32).into();
+    let balance = T::Currency::minimum_balance()
+        .saturating_add(T::DepositBase::get())
+        .saturating_add(T::DepositPerByte::get().saturating_mul(max_bytes))
+        .saturating_mul(2u32.into());
+    T::Currency::set_balance(who, balance);
+}
+
+/// A username of the maximum length, distinct per `seed`.
+fn longest_username(seed: u8) -> String {
+    let mut name = filler(b'u', MAX_USERNAME_LEN - 1);
+    name.push((b'a' + seed) as char);
+    name
+}
+
+/// Register `who` with names/address of the given lengths.
+fn register<T: Config>(who: &T::AccountId, f: u32, l: u32, a: u32) {
+    Pallet::<T>::create_user(
+        RawOrigin::Signed(who.clone()).into(),
+        filler(b'F', f),
+        filler(b'L', l),
+        filler(b'A', a),
+        BIRTH_DATE,
+    )
+    .expect("benchmark user can be created");
+}
+
+/// Register `who` with the longest names and address the bounds allow.
+fn register_longest<T: Config>(who: &T::AccountId) {
+    register::<T>(who, T::MaxNameLen::get(), T::MaxNameLen::get(), T::MaxAddressLen::get());
+}
+
+/// `g` distinct guardian accounts, sorted.
+fn guardians<T: Config>(g: u32) -> Vec<T::AccountId> {
+    let mut guardians: Vec<T::AccountId> = (0..g).map(|i| account("guardian", i, 0)).collect();
+    guardians.sort();
+    guardians
+}
+
+/// Make `n` distinct accounts registrars and return them.
+fn registrars<T: Config>(n: u32) -> Vec<T::AccountId> {
+    let registrars: Vec<T::AccountId> = (0..n).map(|i| account("registrar", i, 0)).collect();
+    for registrar in &registrars {
+        Pallet::<T>::add_registrar(RawOrigin::Root.into(), registrar.clone())
+            .expect("registrar can be added");
+    }
+    registrars
+}
+
+/// Register `who` and let all of `g` guardians be needed to recover it.
+fn configure_recovery<T: Config>(who: &T::AccountId, g: u32) {
+    register::<T>(who, 1, 1, 1);
+    Pallet::<T>::set_recovery_config(RawOrigin::Signed(who.clone()).into(), guardians::<T>(g), g)
+        .expect("recovery can be configured");
+}
+
+/// Let any one of `g` guardians recover the registered `lost`, and have each vouch for a
+/// new account of its own, so `g` approved recoveries are in progress. Returns the new
+/// accounts.
+fn rival_recoveries<T: Config>(lost: &T::AccountId, g: u32) -> Vec<T::AccountId> {
+    if g == 0 {
+        return Vec::new();
+    }
+    Pallet::<T>::set_recovery_config(RawOrigin::Signed(lost.clone()).into(), guardians::<T>(g), 1)
+        .expect("recovery can be configured");
+    guardians::<T>(g)
+        .into_iter()
+        .enumerate()
+        .map(|(i, guardian)| {
+            let new_account: T::AccountId = account("new", i as u32, 0);
+            T::Currency::set_balance(&new_account, T::Currency::minimum_balance());
+            Pallet::<T>::vouch_recovery(
+                RawOrigin::Signed(guardian).into(),
+                lost.clone(),
+                new_account.clone(),
+            )
+            .expect("guardian can vouch");
+            new_account
+        })
+        .collect()
+}
+
+/// Link `l` secondary accounts to the record of `primary`.
+fn link_accounts<T: Config>(primary: &T::AccountId, l: u32) {
+    for i in 0..l {
+        let secondary: T::AccountId = account("secondary", i, 0);
+        Pallet::<T>::request_link(RawOrigin::Signed(secondary.clone()).into(), primary.clone())
+            .expect("secondary can request a link");
+        Pallet::<T>::accept_link(RawOrigin::Signed(primary.clone()).into(), secondary)
+            .expect("primary can accept the link");
+    }
+}
 
 /* -------------------------------------------------------------------- *
  *  Benchmarks                                                          *
@@ -18,52 +133,390 @@ mod benchmarks {
 
     /* ===== create_user ============================================= */
     #[benchmark]
-    fn create_user() {
-        // — setup — ----------------------------------------------------------
-        let caller: T::AccountId = whitelisted_caller();
+    fn create_user(
+        f: Linear<1, { T::MaxNameLen::get() }>,
+        l: Linear<1, { T::MaxNameLen::get() }>,
+        a: Linear<1, { T::MaxAddressLen::get() }>,
+    ) {
+        let caller = funded_caller::<T>();
 
-        // worst-case input (64-byte names, max u32 age, etc.)
-        let fname   = String::from_utf8(vec![b'F'; 64]).unwrap();      // "FFFFFFFF…"
-        let lname   = String::from_utf8(vec![b'L'; 64]).unwrap();
-        let address = String::from_utf8(vec![b'A'; 128]).unwrap();
-        let age: u32 = u32::MAX;
-
-        // — extrinsic under test — ------------------------------------------
-        #[extrinsic_call]      // <-- attribute sits *above* the placeholder _
+        #[extrinsic_call]
         _(
             RawOrigin::Signed(caller.clone()),
-            fname.clone(),
-            lname.clone(),
-            address.clone(),
-            age
+            filler(b'F', f),
+            filler(b'L', l),
+            filler(b'A', a),
+            BIRTH_DATE
         );
 
-        // — post-condition — -------------------------------------------------
         assert!(UserDetailsStorage::<T>::contains_key(&caller));
     }
 
-    /* ===== get_my_user_details ===================================== */
-    // #[benchmark]
-    // fn get_my_user_details() {
-        
-    // }
-
     /* ===== update_user ============================================= */
-    // #[benchmark]
-    // fn update_user() {
-        
-    // }
+    #[benchmark]
+    fn update_user(
+        f: Linear<1, { T::MaxNameLen::get() }>,
+        l: Linear<1, { T::MaxNameLen::get() }>,
+        a: Linear<1, { T::MaxAddressLen::get() }>,
+    ) {
+        // start from the smallest record so the deposit has to grow
+        let caller = funded_caller::<T>();
+        register::<T>(&caller, 1, 1, 1);
+
+        // every field supplied and different from the stored one
+        let updates = UserDetailsUpdate {
+            fname: Some(filler(b'f', f)),
+            lname: Some(filler(b'l', l)),
+            address: Some(filler(b'a', a)),
+            birth_date: Some(Date::new(1970, 1, 1)),
+        };
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), updates);
+
+        let details = UserDetailsStorage::<T>::get(&caller).unwrap();
+        assert_eq!(details.address.len() as u32, a);
+    }
 
     /* ===== remove_user ============================================= */
-    // #[benchmark]
-    // fn remove_user() {
-        
-    // }
+    #[benchmark]
+    fn remove_user(
+        l: Linear<0, { T::MaxLinkedAccounts::get() }>,
+        g: Linear<0, { T::MaxGuardians::get() }>,
+    ) {
+        let caller = funded_caller::<T>();
+        register_longest::<T>(&caller);
+        link_accounts::<T>(&caller, l);
+        rival_recoveries::<T>(&caller, g);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()));
+
+        assert!(!UserDetailsStorage::<T>::contains_key(&caller));
+    }
+
+    /* ===== force_create_user ======================================= */
+    #[benchmark]
+    fn force_create_user(
+        f: Linear<1, { T::MaxNameLen::get() }>,
+        l: Linear<1, { T::MaxNameLen::get() }>,
+        a: Linear<1, { T::MaxAddressLen::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let origin = T::RegistrarOrigin::try_successful_origin()
+            .map_err(|_| BenchmarkError::Weightless)?;
+        let who: T::AccountId = account("citizen", 0, 0);
+
+        #[extrinsic_call]
+        _(
+            origin as T::RuntimeOrigin,
+            who.clone(),
+            filler(b'F', f),
+            filler(b'L', l),
+            filler(b'A', a),
+            BIRTH_DATE
+        );
+
+        assert!(UserDetailsStorage::<T>::contains_key(&who));
+        Ok(())
+    }
+
+    /* ===== force_update_user ======================================= */
+    #[benchmark]
+    fn force_update_user(
+        f: Linear<1, { T::MaxNameLen::get() }>,
+        l: Linear<1, { T::MaxNameLen::get() }>,
+        a: Linear<1, { T::MaxAddressLen::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let origin = T::RegistrarOrigin::try_successful_origin()
+            .map_err(|_| BenchmarkError::Weightless)?;
+        let who = funded_caller::<T>();
+        register::<T>(&who, 1, 1, 1);
+
+        let updates = UserDetailsUpdate {
+            fname: Some(filler(b'f', f)),
+            lname: Some(filler(b'l', l)),
+            address: Some(filler(b'a', a)),
+            birth_date: Some(Date::new(1970, 1, 1)),
+        };
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, who.clone(), updates);
+
+        let details = UserDetailsStorage::<T>::get(&who).unwrap();
+        assert_eq!(details.address.len() as u32, a);
+        Ok(())
+    }
+
+    /* ===== force_remove_user ======================================= */
+    #[benchmark]
+    fn force_remove_user(
+        l: Linear<0, { T::MaxLinkedAccounts::get() }>,
+        g: Linear<0, { T::MaxGuardians::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let origin = T::RegistrarOrigin::try_successful_origin()
+            .map_err(|_| BenchmarkError::Weightless)?;
+        // worst case: a self-registered record with a deposit to release
+        let who = funded_caller::<T>();
+        register_longest::<T>(&who);
+        link_accounts::<T>(&who, l);
+        rival_recoveries::<T>(&who, g);
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, who.clone());
+
+        assert!(!UserDetailsStorage::<T>::contains_key(&who));
+        Ok(())
+    }
+
+    /* ===== claim_username ========================================== */
+    #[benchmark]
+    fn claim_username() {
+        let caller = funded_caller::<T>();
+        register::<T>(&caller, 1, 1, 1);
+        let username = longest_username(0);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), username);
+
+        assert!(UsernameOf::<T>::contains_key(&caller));
+    }
+
+    /* ===== release_username ======================================== */
+    #[benchmark]
+    fn release_username() {
+        let caller = funded_caller::<T>();
+        register::<T>(&caller, 1, 1, 1);
+        Pallet::<T>::claim_username(RawOrigin::Signed(caller.clone()).into(), longest_username(0))
+            .expect("username can be claimed");
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()));
+
+        assert!(!UsernameOf::<T>::contains_key(&caller));
+    }
+
+    /* ===== transfer_username ======================================= */
+    #[benchmark]
+    fn transfer_username() {
+        let caller = funded_caller::<T>();
+        register::<T>(&caller, 1, 1, 1);
+        Pallet::<T>::claim_username(RawOrigin::Signed(caller.clone()).into(), longest_username(0))
+            .expect("username can be claimed");
+        let dest: T::AccountId = account("dest", 0, 0);
+        fund::<T>(&dest);
+        register::<T>(&dest, 1, 1, 1);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), dest.clone());
+
+        assert!(UsernameOf::<T>::contains_key(&dest));
+    }
+
+    /* ===== reserve_username ======================================== */
+    #[benchmark]
+    fn reserve_username() {
+        #[extrinsic_call]
+        _(RawOrigin::Root, longest_username(0));
+
+        assert_eq!(ReservedUsernames::<T>::iter_keys().count(), 1);
+    }
+
+    /* ===== unreserve_username ====================================== */
+    #[benchmark]
+    fn unreserve_username() {
+        Pallet::<T>::reserve_username(RawOrigin::Root.into(), longest_username(0))
+            .expect("username can be reserved");
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, longest_username(0));
+
+        assert_eq!(ReservedUsernames::<T>::iter_keys().count(), 0);
+    }
+
+    /* ===== set_recovery_config ===================================== */
+    #[benchmark]
+    fn set_recovery_config(g: Linear<1, { T::MaxGuardians::get() }>) {
+        let caller = funded_caller::<T>();
+        register::<T>(&caller, 1, 1, 1);
+        let guardians = guardians::<T>(g);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), guardians, g);
+
+        assert!(RecoveryConfigs::<T>::contains_key(&caller));
+    }
+
+    /* ===== remove_recovery_config ================================== */
+    #[benchmark]
+    fn remove_recovery_config() {
+        let caller = funded_caller::<T>();
+        configure_recovery::<T>(&caller, T::MaxGuardians::get());
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()));
+
+        assert!(!RecoveryConfigs::<T>::contains_key(&caller));
+    }
+
+    /* ===== vouch_recovery ========================================== */
+    #[benchmark]
+    fn vouch_recovery(g: Linear<1, { T::MaxGuardians::get() }>) {
+        // worst case: the last vouch, which reaches the threshold and is withdrawn from a
+        // rival recovery
+        let lost = funded_caller::<T>();
+        configure_recovery::<T>(&lost, g);
+        let guardians = guardians::<T>(g);
+        let rival: T::AccountId = account("rival", 0, 0);
+        Pallet::<T>::vouch_recovery(RawOrigin::Signed(guardians[0].clone()).into(), lost.clone(), rival)
+            .expect("guardian can vouch");
+        let new_account: T::AccountId = account("new", 0, 0);
+        for guardian in &guardians[1..] {
+            Pallet::<T>::vouch_recovery(
+                RawOrigin::Signed(guardian.clone()).into(),
+                lost.clone(),
+                new_account.clone(),
+            )
+            .expect("guardian can vouch");
+        }
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(guardians[0].clone()), lost.clone(), new_account.clone());
+
+        assert!(ActiveRecoveries::<T>::get(&lost, &new_account).unwrap().claimable_at.is_some());
+        assert_eq!(ActiveRecoveries::<T>::iter_prefix(&lost).count(), 1);
+    }
+
+    /* ===== cancel_recovery ========================================= */
+    #[benchmark]
+    fn cancel_recovery(g: Linear<1, { T::MaxGuardians::get() }>) {
+        let lost = funded_caller::<T>();
+        register::<T>(&lost, 1, 1, 1);
+        rival_recoveries::<T>(&lost, g);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(lost.clone()));
+
+        assert_eq!(ActiveRecoveries::<T>::iter_prefix(&lost).count(), 0);
+    }
+
+    /* ===== claim_recovery ========================================== */
+    #[benchmark]
+    fn claim_recovery(
+        l: Linear<0, { T::MaxLinkedAccounts::get() }>,
+        g: Linear<1, { T::MaxGuardians::get() }>,
+    ) {
+        // worst case: a deposit to move, a username to re-point and rival recoveries to end
+        let lost = funded_caller::<T>();
+        register::<T>(&lost, 1, 1, 1);
+        let new_account = rival_recoveries::<T>(&lost, g)[0].clone();
+        Pallet::<T>::claim_username(RawOrigin::Signed(lost.clone()).into(), longest_username(0))
+            .expect("username can be claimed");
+        link_accounts::<T>(&lost, l);
+        frame_system::Pallet::<T>::set_block_number(
+            frame_system::Pallet::<T>::block_number().saturating_add(T::RecoveryDelay::get()),
+        );
+        let caller: T::AccountId = account("submitter", 0, 0);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), lost.clone(), new_account.clone());
+
+        assert!(UserDetailsStorage::<T>::contains_key(&new_account));
+        assert_eq!(ActiveRecoveries::<T>::iter_prefix(&lost).count(), 0);
+        assert!(!UserDetailsStorage::<T>::contains_key(&lost));
+    }
+
+    /* ===== request_link ============================================ */
+    #[benchmark]
+    fn request_link() {
+        let primary = funded_caller::<T>();
+        register::<T>(&primary, 1, 1, 1);
+        let secondary: T::AccountId = account("secondary", 0, 0);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(secondary.clone()), primary.clone());
+
+        assert_eq!(PendingLinks::<T>::get(&secondary), Some(primary));
+    }
+
+    /* ===== accept_link ============================================= */
+    #[benchmark]
+    fn accept_link() {
+        // worst case: the list is one short of full
+        let primary = funded_caller::<T>();
+        register::<T>(&primary, 1, 1, 1);
+        link_accounts::<T>(&primary, T::MaxLinkedAccounts::get().saturating_sub(1));
+        let secondary: T::AccountId = account("newcomer", 0, 0);
+        Pallet::<T>::request_link(RawOrigin::Signed(secondary.clone()).into(), primary.clone())
+            .expect("secondary can request a link");
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(primary.clone()), secondary.clone());
+
+        assert_eq!(PrimaryOf::<T>::get(&secondary), Some(primary));
+    }
+
+    /* ===== unlink ================================================== */
+    #[benchmark]
+    fn unlink() {
+        // worst case: the secondary unlinks itself from a full list
+        let primary = funded_caller::<T>();
+        register::<T>(&primary, 1, 1, 1);
+        link_accounts::<T>(&primary, T::MaxLinkedAccounts::get());
+        let secondary: T::AccountId = account("secondary", 0, 0);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(secondary.clone()), primary);
+
+        assert!(!PrimaryOf::<T>::contains_key(&secondary));
+    }
+
+    /* ===== set_user_status ========================================= */
+    #[benchmark]
+    fn set_user_status() -> Result<(), BenchmarkError> {
+        let origin = T::RegistrarOrigin::try_successful_origin()
+            .map_err(|_| BenchmarkError::Weightless)?;
+        let who = funded_caller::<T>();
+        register_longest::<T>(&who);
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, who.clone(), UserStatus::Active);
+
+        assert_eq!(Pallet::<T>::user_status(who), Some(UserStatus::Active));
+        Ok(())
+    }
+
+    /* ===== add_registrar =========================================== */
+    #[benchmark]
+    fn add_registrar() -> Result<(), BenchmarkError> {
+        // worst case: the list is one short of full
+        let max = T::MaxRegistrars::get();
+        registrars::<T>(max.saturating_sub(1));
+        let registrar: T::AccountId = account("newcomer", 0, 0);
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, registrar);
+
+        assert_eq!(Registrars::<T>::get().len() as u32, max);
+        Ok(())
+    }
+
+    /* ===== remove_registrar ======================================== */
+    #[benchmark]
+    fn remove_registrar() -> Result<(), BenchmarkError> {
+        let max = T::MaxRegistrars::get();
+        let registrar = registrars::<T>(max).pop().ok_or(BenchmarkError::Weightless)?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, registrar);
+
+        assert_eq!(Registrars::<T>::get().len() as u32, max.saturating_sub(1));
+        Ok(())
+    }
 
     /* ===== wiring for `cargo test --features runtime-benchmarks` ==== */
     impl_benchmark_test_suite!(
         Pallet,
-        crate::mock::test_ext(),
+        crate::mock::new_test_ext(),
         crate::mock::Test
     );
 }
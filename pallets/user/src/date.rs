@@ -0,0 +1,107 @@
+// pallets/user/src/date.rs
+//! Compact proleptic-Gregorian calendar date.
+
+use codec::{Decode, DecodeWithMemTracking, Encode, MaxEncodedLen};
+use frame_support::pallet_prelude::RuntimeDebug;
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+
+const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// A calendar date. Field order makes the derived `Ord` chronological.
+#[derive(
+    Clone, Copy, Encode, Decode, DecodeWithMemTracking, TypeInfo, MaxEncodedLen,
+    PartialEq, Eq, PartialOrd, Ord, RuntimeDebug, Serialize, Deserialize
+)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub const fn new(year: u16, month: u8, day: u8) -> Self {
+        Self { year, month, day }
+    }
+
+    pub fn is_leap_year(year: u16) -> bool {
+        (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+    }
+
+    /// Number of days in `month` of `year`, `0` for an out-of-range month.
+    pub fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            _ => 0,
+        }
+    }
+
+    /// Month and day ranges are correct for the year, leap years included.
+    pub fn is_valid(&self) -> bool {
+        self.year >= 1
+            && (1..=12).contains(&self.month)
+            && self.day >= 1
+            && self.day <= Self::days_in_month(self.year, self.month)
+    }
+
+    /// Parse an ISO 8601 `YYYY-MM-DD` date, `None` unless it is well-formed and valid.
+    pub fn parse(text: &[u8]) -> Option<Self> {
+        fn number(digits: &[u8]) -> Option<u16> {
+            digits.iter().try_fold(0u16, |n, d| {
+                d.is_ascii_digit().then(|| n * 10 + u16::from(d - b'0'))
+            })
+        }
+        let [y0, y1, y2, y3, b'-', m0, m1, b'-', d0, d1] = *text else { return None };
+        let date = Self {
+            year: number(&[y0, y1, y2, y3])?,
+            month: number(&[m0, m1])? as u8,
+            day: number(&[d0, d1])? as u8,
+        };
+        date.is_valid().then_some(date)
+    }
+
+    /// The UTC date of a unix timestamp in milliseconds.
+    pub fn from_unix_millis(millis: u64) -> Self {
+        // Howard Hinnant's `civil_from_days`, restricted to dates from 1970 onwards.
+        let z = millis / MILLIS_PER_DAY + 719_468;
+        let era = z / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (yoe + era * 400 + u64::from(month <= 2)) as u16;
+        Self { year, month, day }
+    }
+
+    /// Whole years elapsed from `self` to `today`; `0` if `today` is earlier.
+    pub fn years_until(&self, today: &Date) -> u32 {
+        if today < self {
+            return 0;
+        }
+        let years = u32::from(today.year - self.year);
+        if (today.month, today.day) < (self.month, self.day) {
+            years - 1
+        } else {
+            years
+        }
+    }
+
+    /// The same day `years` earlier, moving 29 February to the 28th when needed.
+    pub fn years_before(&self, years: u32) -> Self {
+        let year = u32::from(self.year).saturating_sub(years).max(1) as u16;
+        let day = self.day.min(Self::days_in_month(year, self.month));
+        Self { year, month: self.month, day }
+    }
+}
+
+/// Formats as ISO 8601 `YYYY-MM-DD`, the inverse of [`Date::parse`].
+impl core::fmt::Display for Date {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
@@ -1,83 +1,399 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-pub type Balance = u128;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 
-//#[cfg(feature = "runtime-benchmarks")]
-//mod benchmarking;
+#[cfg(test)]
+mod mock;
 
-// #[cfg(test)]
-// mod mock;
+#[cfg(test)]
+mod tests;
+
+pub mod date;
+pub use date::Date;
+
+pub mod text;
+
+pub mod migrations;
 
 pub use pallet::*;
-// // pub mod weights;
-// use weights::WeightInfo;
+pub mod weights;
+pub use weights::WeightInfo;
+
+/// Log target of the user pallet.
+pub const LOG_TARGET: &str = "runtime::user";
 
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
 
-    use log::info;
     use scale_info::prelude::string::String;
     use scale_info::prelude::vec::Vec;
-    use frame_system::ensure_signed;
-    use frame_support::sp_runtime::BoundedVec;
+    use frame_system::{ensure_root, ensure_signed};
+    use frame_support::sp_runtime::{traits::Zero, BoundedVec, Saturating};
     use frame_support::{pallet_prelude::{
         OptionQuery, 
-        StorageValue, 
-        StorageMap, 
         ValueQuery,
-        Weight,
+        StorageValue,
+        StorageMap, 
+        StorageDoubleMap,
+        CountedStorageMap, 
         DispatchResult,
+        DispatchError,
         Encode,
         Decode,
+        DecodeWithMemTracking,
         TypeInfo,
         MaxEncodedLen,
         RuntimeDebug,
         ConstU32,
         IsType,
+        Get,
+        Weight,
+        StorageVersion,
         ensure
-    }, Blake2_128};
-    use frame_system::pallet_prelude::OriginFor;
-    use frame_system::Origin;
+    }, Blake2_128Concat, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound};
+    use frame_support::traits::{
+        fungible::{Inspect, InspectHold, Mutate, MutateHold},
+        tokens::{Fortitude, Precision, Restriction},
+        EnsureOrigin,
+        Hooks,
+        SortedMembers,
+        UnixTime,
+    };
+    use frame_system::pallet_prelude::{BlockNumberFor, OriginFor};
+    use crate::text::{self, CharClass, TextError};
+
+    /// Balance type of the currency used for user deposits.
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
-        //type WeightInfo: WeightInfo;
+
+        /// Weight information for the dispatchables of this pallet.
+        type WeightInfo: WeightInfo;
+
+        /// Currency in which the user record deposit is held.
+        type Currency: Mutate<Self::AccountId>
+            + MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>;
+
+        /// The overarching hold reason.
+        type RuntimeHoldReason: From<HoldReason>;
+
+        /// Origin allowed to create, correct and remove user records on behalf of any account.
+        /// The success value is the registrar recorded in the `UserDataForce*` events. Runtimes
+        /// let the accounts in `Registrars` pass it with `EnsureSignedBy<Pallet<T>, _>`.
+        type RegistrarOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+        /// Source of the current time, used to derive ages from birth dates.
+        type UnixTime: UnixTime;
+
+        /// Called after a user record has been created, by the owner or by a registrar.
+        type OnUserCreated: OnUserCreated<Self::AccountId>;
+
+        /// Called after a user record has been removed, so dependent pallets can clean up.
+        type OnUserRemoved: OnUserRemoved<Self::AccountId>;
+
+        /// Called after a user record has been recovered to a new account, so dependent
+        /// pallets can move their records along.
+        type OnUserMoved: OnUserMoved<Self::AccountId>;
+
+        /// Largest number of guardians in a recovery configuration.
+        #[pallet::constant]
+        type MaxGuardians: Get<u32>;
+
+        /// Blocks between a recovery reaching its threshold and it becoming claimable. The
+        /// old owner can cancel during this time.
+        #[pallet::constant]
+        type RecoveryDelay: Get<BlockNumberFor<Self>>;
+
+        /// Largest number of secondary accounts linked to one user record.
+        #[pallet::constant]
+        type MaxLinkedAccounts: Get<u32>;
+
+        /// Longest first or last name, in bytes of UTF-8. Lowering it needs
+        /// `migrations::bounds::TruncateToBounds`.
+        #[pallet::constant]
+        type MaxNameLen: Get<u32>;
+
+        /// Longest address, in bytes of UTF-8. Lowering it needs
+        /// `migrations::bounds::TruncateToBounds`.
+        #[pallet::constant]
+        type MaxAddressLen: Get<u32>;
+
+        /// Flat part of the deposit held for every user record.
+        #[pallet::constant]
+        type DepositBase: Get<BalanceOf<Self>>;
+
+        /// Deposit held per byte of the encoded `UserDetails`.
+        #[pallet::constant]
+        type DepositPerByte: Get<BalanceOf<Self>>;
+
+        /// Largest number of accounts in `Registrars`.
+        #[pallet::constant]
+        type MaxRegistrars: Get<u32>;
+    }
+
+    /// Reasons for which this pallet places funds on hold.
+    #[pallet::composite_enum]
+    pub enum HoldReason {
+        /// Storage deposit for a `UserDetailsStorage` entry.
+        UserDeposit,
+    }
+
+    /// Lifecycle of a user record. Only registrars change it.
+    #[derive(Clone, Copy, Encode, Decode, DecodeWithMemTracking, TypeInfo, MaxEncodedLen, PartialEq, Eq, RuntimeDebug)]
+    pub enum UserStatus {
+        /// Self-registered, waiting for a registrar to confirm the identity.
+        Pending,
+        /// Confirmed. Dependent pallets only serve active users.
+        Active,
+        /// Frozen by a registrar, e.g. a suspected fraudulent registration. The owner can
+        /// neither change nor remove the record, so it stays as evidence.
+        Suspended,
+        /// The holder died. Final, and locked like `Suspended`.
+        Deceased,
+    }
+
+    impl UserStatus {
+        /// Whether a registrar may move a record from `self` to `next`. `Deceased` is final
+        /// and no record goes back to `Pending`.
+        pub fn can_become(self, next: Self) -> bool {
+            match (self, next) {
+                (Self::Deceased, _) | (_, Self::Pending) => false,
+                (from, to) => from != to,
+            }
+        }
+
+        /// The owner may no longer change or remove the record.
+        pub fn is_locked(self) -> bool {
+            matches!(self, Self::Suspended | Self::Deceased)
+        }
     }
 
     // custom struct 
+    #[derive(CloneNoBound, Encode, Decode, TypeInfo, MaxEncodedLen, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound)]
+    #[codec(mel_bound())]
+    #[scale_info(skip_type_params(MaxNameLen, MaxAddressLen))]
+    pub struct UserDetails<MaxNameLen: Get<u32>, MaxAddressLen: Get<u32>> {
+        pub fname: BoundedVec<u8, MaxNameLen>,
+        pub lname: BoundedVec<u8, MaxNameLen>,
+        pub address: BoundedVec<u8, MaxAddressLen>,
+        pub birth_date: Date,
+        pub status: UserStatus,
+    }
+
+    pub type UserDetailsOf<T> = UserDetails<<T as Config>::MaxNameLen, <T as Config>::MaxAddressLen>;
+
+    /// Patch for `UserDetails`: only the fields that are `Some(..)` are replaced.
+    #[derive(Default, Clone, Encode, Decode, DecodeWithMemTracking, TypeInfo, PartialEq, Eq, RuntimeDebug)]
+    pub struct UserDetailsUpdate {
+        pub fname: Option<String>,
+        pub lname: Option<String>,
+        pub address: Option<String>,
+        pub birth_date: Option<Date>,
+    }
+
+    impl UserDetailsUpdate {
+        /// Byte lengths of the string fields, `0` when not supplied. Used for weighing.
+        pub fn lengths(&self) -> (u32, u32, u32) {
+            let len = |f: &Option<String>| f.as_ref().map_or(0, |s| s.len() as u32);
+            (len(&self.fname), len(&self.lname), len(&self.address))
+        }
+    }
+
+    /// A field of `UserDetails`, as reported in update events.
+    #[derive(Clone, Copy, Encode, Decode, DecodeWithMemTracking, TypeInfo, MaxEncodedLen, PartialEq, Eq, RuntimeDebug)]
+    pub enum UserField {
+        FirstName,
+        LastName,
+        Address,
+        BirthDate,
+    }
+
+    /// Accounts allowed to vouch for moving a user record to a new key.
     #[derive(Clone, Encode, Decode, TypeInfo, MaxEncodedLen, PartialEq, Eq, RuntimeDebug)]
-    pub struct UserDetails {
-        pub fname: BoundedVec<u8, ConstU32<64>>,
-        pub lname: BoundedVec<u8, ConstU32<64>>,
-        pub address: BoundedVec<u8, ConstU32<128>>,
-        pub age: u32,
+    #[scale_info(skip_type_params(MaxGuardians))]
+    pub struct RecoveryConfig<AccountId, MaxGuardians: Get<u32>> {
+        /// Sorted, without duplicates.
+        pub guardians: BoundedVec<AccountId, MaxGuardians>,
+        /// Vouches needed before the recovery delay starts.
+        pub threshold: u32,
     }
 
-    // storage
-    #[pallet::storage]
-    pub type TotalIssuance<T: Config> = StorageValue<_, Balance, ValueQuery>;
+    /// A recovery of a lost account to one new account in progress.
+    #[derive(Clone, Encode, Decode, TypeInfo, MaxEncodedLen, PartialEq, Eq, RuntimeDebug)]
+    #[scale_info(skip_type_params(MaxGuardians))]
+    pub struct ActiveRecovery<AccountId, BlockNumber, MaxGuardians: Get<u32>> {
+        /// Guardians that vouched so far.
+        pub vouchers: BoundedVec<AccountId, MaxGuardians>,
+        /// Block from which the recovery can be claimed, set once the threshold is reached.
+        pub claimable_at: Option<BlockNumber>,
+    }
 
-    #[pallet::storage]
-    pub type Balances<T: Config> = StorageMap<_,  Blake2_128, T::AccountId, Balance, ValueQuery>;
+    pub type RecoveryConfigOf<T> =
+        RecoveryConfig<<T as frame_system::Config>::AccountId, <T as Config>::MaxGuardians>;
+    pub type ActiveRecoveryOf<T> = ActiveRecovery<
+        <T as frame_system::Config>::AccountId,
+        BlockNumberFor<T>,
+        <T as Config>::MaxGuardians,
+    >;
+
+    /// Longest username, in bytes.
+    pub const MAX_USERNAME_LEN: u32 = 32;
 
+    /// Shortest username, in bytes.
+    pub const MIN_USERNAME_LEN: u32 = 3;
+
+    /// A normalised username: lowercase ASCII letters, digits and `_`.
+    pub type Username = BoundedVec<u8, ConstU32<MAX_USERNAME_LEN>>;
+
+    /// Largest page returned by `users_page`.
+    pub const MAX_PAGE_LEN: u32 = 100;
+
+    // storage
     #[pallet::storage]
-    pub type UserDetailsStorage<T: Config> = StorageMap<
+    pub type UserDetailsStorage<T: Config> = CountedStorageMap<
     _, 
-        Blake2_128, 
+        Blake2_128Concat, 
         T::AccountId, 
-        UserDetails, 
+        UserDetailsOf<T>, 
         OptionQuery
     >;
 
+    /// Owner of every claimed username.
+    #[pallet::storage]
+    pub type UsernameOwner<T: Config> =
+        StorageMap<_, Blake2_128Concat, Username, T::AccountId, OptionQuery>;
+
+    /// Reverse lookup: the username held by an account.
+    #[pallet::storage]
+    pub type UsernameOf<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Username, OptionQuery>;
+
+    /// Usernames that can not be claimed, managed by root.
+    #[pallet::storage]
+    pub type ReservedUsernames<T: Config> =
+        StorageMap<_, Blake2_128Concat, Username, (), OptionQuery>;
+
+    /// Recovery configuration of each user that set one.
+    #[pallet::storage]
+    pub type RecoveryConfigs<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, RecoveryConfigOf<T>, OptionQuery>;
+
+    /// Recoveries in progress, keyed by the lost account and the account its records would
+    /// move to. Each guardian backs at most one of the recoveries of a lost account.
+    #[pallet::storage]
+    pub type ActiveRecoveries<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        T::AccountId,
+        ActiveRecoveryOf<T>,
+        OptionQuery,
+    >;
+
+    /// Secondary accounts linked to each primary account, i.e. the one holding the record.
+    #[pallet::storage]
+    pub type LinkedAccounts<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<T::AccountId, T::MaxLinkedAccounts>,
+        OptionQuery,
+    >;
+
+    /// Reverse lookup: the primary account a secondary account is linked to.
+    #[pallet::storage]
+    pub type PrimaryOf<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId, OptionQuery>;
+
+    /// Link requests signed by a secondary account, waiting for the primary to accept.
+    #[pallet::storage]
+    pub type PendingLinks<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId, OptionQuery>;
+
+    /// Registrar accounts, sorted, managed by root. Served as `SortedMembers` so runtimes can
+    /// back `RegistrarOrigin` with them.
+    #[pallet::storage]
+    pub type Registrars<T: Config> = StorageValue<_, BoundedVec<T::AccountId, T::MaxRegistrars>, ValueQuery>;
+
     // error
     #[pallet::error]
     pub enum Error<T> {
         FirstNameTooLong,
         LastNameTooLong,
         AddressTooLong,
-        NoUserDataFound
+        /// The first name has control characters or characters other than letters, spaces,
+        /// `-`, `'` and `.`.
+        InvalidFirstName,
+        /// The last name has control characters or characters other than letters, spaces,
+        /// `-`, `'` and `.`.
+        InvalidLastName,
+        /// The address has control characters.
+        InvalidAddress,
+        /// The birth date is not a calendar date or lies in the future.
+        InvalidBirthDate,
+        NoUserDataFound,
+        /// The account already has a user record or is linked to one.
+        DuplicateUser,
+        /// The username is longer than `MAX_USERNAME_LEN` bytes.
+        UsernameTooLong,
+        /// The username is shorter than `MIN_USERNAME_LEN` bytes or has characters other than
+        /// ASCII letters, digits and `_`.
+        InvalidUsername,
+        /// A registrar can not move the record to this status.
+        InvalidStatusTransition,
+        /// The record is suspended or its holder deceased; only a registrar can change it.
+        RecordLocked,
+        /// Another account holds the username.
+        UsernameTaken,
+        /// The username is reserved.
+        UsernameReserved,
+        /// The username is not reserved.
+        UsernameNotReserved,
+        /// The account already holds a username.
+        UsernameAlreadySet,
+        /// The account holds no username.
+        NoUsername,
+        /// Guardians must be between one and `MaxGuardians` distinct accounts other than the
+        /// user.
+        InvalidGuardians,
+        /// The threshold must be between one and the number of guardians.
+        InvalidThreshold,
+        /// The account has no recovery configuration.
+        NoRecoveryConfig,
+        /// The caller is not a guardian of the lost account.
+        NotGuardian,
+        /// The guardian already vouched for this recovery.
+        AlreadyVouched,
+        /// A recovery of the account is in progress.
+        RecoveryInProgress,
+        /// A recovery to a different account is in progress.
+        RecoveryTargetMismatch,
+        /// No recovery of the account is in progress.
+        NoActiveRecovery,
+        /// The recovery lacks vouches or its delay has not passed.
+        RecoveryNotClaimable,
+        /// An account can not be linked to itself.
+        CannotLinkSelf,
+        /// The secondary account did not request a link to the caller.
+        NoPendingLink,
+        /// The record already has `MaxLinkedAccounts` linked accounts.
+        TooManyLinkedAccounts,
+        /// The two accounts are not linked.
+        NotLinked,
+        /// The call is deprecated and no longer does anything; see its documentation.
+        Deprecated,
+        /// The account is in `Registrars` already.
+        AlreadyRegistrar,
+        /// The account is not in `Registrars`.
+        NotRegistrar,
+        /// `Registrars` holds `MaxRegistrars` accounts already.
+        TooManyRegistrars,
     } 
 
     // event
@@ -86,19 +402,107 @@ pub mod pallet {
     pub enum Event<T: Config> {
         UserDataCreated {
             account: T::AccountId,
+            deposit: BalanceOf<T>,
         },
         UserDataRemoved {
             account: T::AccountId,
+            released: BalanceOf<T>,
         },
         UserDataUpdated {
             account: T::AccountId,
+            deposit: BalanceOf<T>,
+            changed: Vec<UserField>,
         },
-        UserDataFetched {
+        /// A registrar created a record for `account`; no deposit is held for it.
+        UserDataForceCreated {
+            registrar: T::AccountId,
             account: T::AccountId,
-            fname: String,
-            lname: String,
-            address: String,
-            age: u32,
+        },
+        /// A registrar patched the record of `account`; its deposit is left untouched.
+        UserDataForceUpdated {
+            registrar: T::AccountId,
+            account: T::AccountId,
+            changed: Vec<UserField>,
+        },
+        /// A registrar removed the record of `account` and released its deposit.
+        UserDataForceRemoved {
+            registrar: T::AccountId,
+            account: T::AccountId,
+            released: BalanceOf<T>,
+        },
+        /// A registrar moved the record of `account` from status `from` to `to`.
+        UserStatusChanged {
+            registrar: T::AccountId,
+            account: T::AccountId,
+            from: UserStatus,
+            to: UserStatus,
+        },
+        /// `account` claimed `username`.
+        UsernameClaimed {
+            account: T::AccountId,
+            username: Username,
+        },
+        /// `account` gave up `username`, either explicitly or by removing its record.
+        UsernameReleased {
+            account: T::AccountId,
+            username: Username,
+        },
+        /// `username` moved from `from` to `to`.
+        UsernameTransferred {
+            from: T::AccountId,
+            to: T::AccountId,
+            username: Username,
+        },
+        /// `username` can no longer be claimed.
+        UsernameReserved { username: Username },
+        /// `username` can be claimed again.
+        UsernameUnreserved { username: Username },
+        /// `registrar` was added to `Registrars`.
+        RegistrarAdded { registrar: T::AccountId },
+        /// `registrar` was removed from `Registrars`.
+        RegistrarRemoved { registrar: T::AccountId },
+        /// `account` set who can recover it.
+        RecoveryConfigured {
+            account: T::AccountId,
+            guardians: u32,
+            threshold: u32,
+        },
+        /// `account` removed its recovery configuration.
+        RecoveryConfigRemoved { account: T::AccountId },
+        /// `guardian` vouched for moving `lost` to `new_account`.
+        RecoveryVouched {
+            lost: T::AccountId,
+            guardian: T::AccountId,
+            new_account: T::AccountId,
+        },
+        /// The recovery of `lost` reached its threshold and can be claimed from `claimable_at`.
+        RecoveryApproved {
+            lost: T::AccountId,
+            new_account: T::AccountId,
+            claimable_at: BlockNumberFor<T>,
+        },
+        /// The owner of `lost` cancelled its recovery.
+        RecoveryCancelled { lost: T::AccountId },
+        /// The records of `lost` moved to `new_account`.
+        UserRecovered {
+            lost: T::AccountId,
+            new_account: T::AccountId,
+        },
+        /// `secondary` asked to be linked to the record of `primary`.
+        LinkRequested {
+            primary: T::AccountId,
+            secondary: T::AccountId,
+        },
+        /// `secondary` now resolves to the record of `primary`.
+        AccountLinked {
+            primary: T::AccountId,
+            secondary: T::AccountId,
+        },
+        /// `secondary` no longer resolves to the record of `primary`, or its pending request
+        /// was withdrawn.
+        AccountUnlinked {
+            primary: T::AccountId,
+            secondary: T::AccountId,
         },
     }
 
@@ -106,150 +510,984 @@ pub mod pallet {
     //     account: who.clone(),
     // });
 
+    /// The in-code storage version. Bump it together with a new module in `migrations`.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(5);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        /// Carry on the v2 -> v3 re-keying, a bounded number of accounts per block.
+        fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+            migrations::v3::step::<T>(migrations::v3::ACCOUNTS_PER_STEP)
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Deposit required to store `details`: `DepositBase + DepositPerByte * encoded_size`.
+        pub fn deposit_for(details: &UserDetailsOf<T>) -> BalanceOf<T> {
+            let bytes: BalanceOf<T> = (details.encoded_size() as u32).into();
+            T::DepositBase::get().saturating_add(T::DepositPerByte::get().saturating_mul(bytes))
+        }
+
+        /// Today's date according to `T::UnixTime`.
+        pub fn today() -> Date {
+            Date::from_unix_millis(T::UnixTime::now().as_millis() as u64)
+        }
+
+        /// Age in whole years of the holder of `details`, as of today.
+        pub fn age(details: &UserDetailsOf<T>) -> u32 {
+            details.birth_date.years_until(&Self::today())
+        }
+
+        /// A birth date must be a real calendar date and not lie in the future.
+        fn ensure_birth_date(birth_date: &Date) -> DispatchResult {
+            ensure!(
+                birth_date.is_valid() && *birth_date <= Self::today(),
+                Error::<T>::InvalidBirthDate
+            );
+            Ok(())
+        }
+
+        /// Normalise a text field with `text::normalize`, reporting `too_long` or `invalid`.
+        fn text_field<S: Get<u32>>(
+            raw: &str,
+            class: CharClass,
+            too_long: Error<T>,
+            invalid: Error<T>,
+        ) -> Result<BoundedVec<u8, S>, Error<T>> {
+            text::normalize(raw.as_bytes(), class).map_err(|e| match e {
+                TextError::TooLong => too_long,
+                _ => invalid,
+            })
+        }
+
+        fn first_name(raw: &str) -> Result<BoundedVec<u8, T::MaxNameLen>, Error<T>> {
+            Self::text_field(raw, CharClass::Name, Error::<T>::FirstNameTooLong, Error::<T>::InvalidFirstName)
+        }
+
+        fn last_name(raw: &str) -> Result<BoundedVec<u8, T::MaxNameLen>, Error<T>> {
+            Self::text_field(raw, CharClass::Name, Error::<T>::LastNameTooLong, Error::<T>::InvalidLastName)
+        }
+
+        fn address(raw: &str) -> Result<BoundedVec<u8, T::MaxAddressLen>, Error<T>> {
+            Self::text_field(raw, CharClass::Text, Error::<T>::AddressTooLong, Error::<T>::InvalidAddress)
+        }
+
+        /// Validate and normalise the raw strings and build a `UserDetails`.
+        fn bounded_details(
+            fname: String,
+            lname: String,
+            address: String,
+            birth_date: Date,
+            status: UserStatus,
+        ) -> Result<UserDetailsOf<T>, DispatchError> {
+            let fname_bounded = Self::first_name(&fname)?;
+            let lname_bounded = Self::last_name(&lname)?;
+            let address_bounded = Self::address(&address)?;
+
+            Self::ensure_birth_date(&birth_date)?;
+
+            Ok(UserDetails {
+                fname: fname_bounded,
+                lname: lname_bounded,
+                address: address_bounded,
+                birth_date,
+                status,
+            })
+        }
+
+        /// Apply `updates` to the stored record of `who`, validating each supplied field.
+        /// Returns the new record and the fields whose value actually changed.
+        fn patched_details(
+            who: &T::AccountId,
+            updates: UserDetailsUpdate,
+        ) -> Result<(UserDetailsOf<T>, Vec<UserField>), DispatchError> {
+            let mut details = UserDetailsStorage::<T>::get(who).ok_or(Error::<T>::NoUserDataFound)?;
+            let mut changed = Vec::new();
+
+            if let Some(fname) = updates.fname {
+                let fname = Self::first_name(&fname)?;
+                if fname != details.fname { details.fname = fname; changed.push(UserField::FirstName); }
+            }
+            if let Some(lname) = updates.lname {
+                let lname = Self::last_name(&lname)?;
+                if lname != details.lname { details.lname = lname; changed.push(UserField::LastName); }
+            }
+            if let Some(address) = updates.address {
+                let address = Self::address(&address)?;
+                if address != details.address { details.address = address; changed.push(UserField::Address); }
+            }
+            if let Some(birth_date) = updates.birth_date {
+                Self::ensure_birth_date(&birth_date)?;
+                if birth_date != details.birth_date {
+                    details.birth_date = birth_date;
+                    changed.push(UserField::BirthDate);
+                }
+            }
+
+            Ok((details, changed))
+        }
+
+        /// Up to `limit` (at most `MAX_PAGE_LEN`) accounts with a user record, in storage
+        /// order, starting after `start_after`.
+        pub fn users_page(start_after: Option<T::AccountId>, limit: u32) -> Vec<T::AccountId> {
+            let entries = match start_after {
+                Some(account) => UserDetailsStorage::<T>::iter_from(
+                    UserDetailsStorage::<T>::hashed_key_for(account),
+                ),
+                None => UserDetailsStorage::<T>::iter(),
+            };
+            entries.map(|(account, _)| account).take(limit.min(MAX_PAGE_LEN) as usize).collect()
+        }
+
+        /// The account holding the record `who` resolves to: its primary if `who` is a linked
+        /// secondary account, `who` itself otherwise.
+        pub fn primary_account(who: &T::AccountId) -> T::AccountId {
+            PrimaryOf::<T>::get(who).unwrap_or_else(|| who.clone())
+        }
+
+        /// `who` holds a user record or is linked to one.
+        fn is_registered(who: &T::AccountId) -> bool {
+            UserDetailsStorage::<T>::contains_key(who)
+                || PrimaryOf::<T>::contains_key(who)
+                || migrations::v3::awaits_rekey::<T>(who)
+        }
+
+        /// Lowercase `raw` and check it is a well-formed username.
+        pub fn normalize_username(raw: &[u8]) -> Result<Username, Error<T>> {
+            ensure!(raw.len() as u32 <= MAX_USERNAME_LEN, Error::<T>::UsernameTooLong);
+            ensure!(raw.len() as u32 >= MIN_USERNAME_LEN, Error::<T>::InvalidUsername);
+            ensure!(
+                raw.iter().all(|c| c.is_ascii_alphanumeric() || *c == b'_'),
+                Error::<T>::InvalidUsername
+            );
+
+            Ok(Username::truncate_from(raw.to_ascii_lowercase()))
+        }
+
+        /// Drop the username of `who`, if any.
+        fn release_username_of(who: &T::AccountId) -> Option<Username> {
+            let username = UsernameOf::<T>::take(who)?;
+            UsernameOwner::<T>::remove(&username);
+            Some(username)
+        }
+
+        /// A recovery of `who` is in progress.
+        fn is_being_recovered(who: &T::AccountId) -> bool {
+            ActiveRecoveries::<T>::iter_key_prefix(who).next().is_some()
+        }
+
+        /// Take the vouch of `guardian` back from the recovery of `lost` it backs, if any. A
+        /// recovery left without vouchers ends; one left below `threshold` is no longer
+        /// claimable.
+        fn withdraw_vouch(lost: &T::AccountId, guardian: &T::AccountId, threshold: u32) {
+            let backed = ActiveRecoveries::<T>::iter_prefix(lost)
+                .find(|(_, recovery)| recovery.vouchers.binary_search(guardian).is_ok());
+            let Some((new_account, mut recovery)) = backed else { return };
+
+            recovery.vouchers.retain(|voucher| voucher != guardian);
+            if recovery.vouchers.is_empty() {
+                ActiveRecoveries::<T>::remove(lost, &new_account);
+                return;
+            }
+            if (recovery.vouchers.len() as u32) < threshold {
+                recovery.claimable_at = None;
+            }
+            ActiveRecoveries::<T>::insert(lost, &new_account, recovery);
+        }
+
+        /// Remove the record of `who` and release whatever deposit is held for it.
+        fn do_remove(who: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
+            ensure!(UserDetailsStorage::<T>::contains_key(who), Error::<T>::NoUserDataFound);
+
+            UserDetailsStorage::<T>::remove(who);
+            RecoveryConfigs::<T>::remove(who);
+            let _ = ActiveRecoveries::<T>::clear_prefix(who, T::MaxGuardians::get(), None);
+            for secondary in LinkedAccounts::<T>::take(who).unwrap_or_default() {
+                PrimaryOf::<T>::remove(&secondary);
+                Self::deposit_event(Event::AccountUnlinked { primary: who.clone(), secondary });
+            }
+            if let Some(username) = Self::release_username_of(who) {
+                Self::deposit_event(Event::UsernameReleased { account: who.clone(), username });
+            }
+
+            let released = T::Currency::release_all(
+                &HoldReason::UserDeposit.into(),
+                who,
+                Precision::BestEffort,
+            )?;
+
+            T::OnUserRemoved::on_user_removed(who);
+            Ok(released)
+        }
+
+        /// Move every record of `lost` to `new_account`, deposit included, and tell dependent
+        /// pallets to do the same.
+        fn do_move(lost: &T::AccountId, new_account: &T::AccountId) -> DispatchResult {
+            let details = UserDetailsStorage::<T>::take(lost).ok_or(Error::<T>::NoUserDataFound)?;
+            UserDetailsStorage::<T>::insert(new_account, details);
+
+            if let Some(username) = UsernameOf::<T>::take(lost) {
+                UsernameOwner::<T>::insert(&username, new_account);
+                UsernameOf::<T>::insert(new_account, username);
+            }
+            RecoveryConfigs::<T>::remove(lost);
+
+            // the secondary accounts belong to the same person and follow the record
+            if let Some(linked) = LinkedAccounts::<T>::take(lost) {
+                for secondary in &linked {
+                    PrimaryOf::<T>::insert(secondary, new_account);
+                }
+                LinkedAccounts::<T>::insert(new_account, linked);
+            }
+
+            let reason = HoldReason::UserDeposit.into();
+            let held = T::Currency::balance_on_hold(&reason, lost);
+            if !held.is_zero() {
+                T::Currency::transfer_on_hold(
+                    &reason,
+                    lost,
+                    new_account,
+                    held,
+                    Precision::BestEffort,
+                    Restriction::OnHold,
+                    Fortitude::Force,
+                )?;
+            }
+
+            T::OnUserMoved::on_user_moved(lost, new_account);
+            Ok(())
+        }
+
+        /// Bring the amount held for `who` to exactly the deposit required by `details`,
+        /// holding or releasing the difference.
+        fn set_deposit(who: &T::AccountId, details: &UserDetailsOf<T>) -> Result<BalanceOf<T>, DispatchError> {
+            let deposit = Self::deposit_for(details);
+            T::Currency::set_on_hold(&HoldReason::UserDeposit.into(), who, deposit)?;
+            Ok(deposit)
+        }
+    }
+
+    /// Read-only queries. Unlike the old fetch extrinsic they cost nothing and leave no trace
+    /// in events.
+    #[pallet::view_functions_experimental]
+    impl<T: Config> Pallet<T> {
+        /// The user record of `who`, or of its primary account if `who` is linked.
+        pub fn user_details(who: T::AccountId) -> Option<UserDetailsOf<T>> {
+            UserDetailsStorage::<T>::get(Self::primary_account(&who))
+        }
+
+        /// Current age in whole years of `who`.
+        pub fn user_age(who: T::AccountId) -> Option<u32> {
+            <Self as UserApi<T::AccountId>>::age_of(&who)
+        }
+
+        /// Lifecycle status of the record `who` resolves to.
+        pub fn user_status(who: T::AccountId) -> Option<UserStatus> {
+            UserDetailsStorage::<T>::get(Self::primary_account(&who)).map(|d| d.status)
+        }
+
+        /// The username held by `who`.
+        pub fn username_of(who: T::AccountId) -> Option<Username> {
+            UsernameOf::<T>::get(who)
+        }
+
+        /// The secondary accounts linked to the record of `who`.
+        pub fn linked_accounts(who: T::AccountId) -> Vec<T::AccountId> {
+            LinkedAccounts::<T>::get(Self::primary_account(&who)).unwrap_or_default().into_inner()
+        }
+    }
+
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         /// An unsafe mint that can be called by anyone. Not a great idea.
         
         #[pallet::call_index(1)]
-        #[pallet::weight(Weight::default())]
+        #[pallet::weight(T::WeightInfo::create_user(
+            fname.len() as u32,
+            lname.len() as u32,
+            address.len() as u32,
+        ).saturating_add(T::OnUserCreated::weight()))]
         pub fn create_user(
             origin: OriginFor<T>,
             fname: String,
             lname: String,
             address: String,
-            age: u32,
+            birth_date: Date,
         ) -> DispatchResult {
             
             let who = ensure_signed(origin)?;
 
-            let fname_bounded: BoundedVec<_, ConstU32<64>> = fname.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::FirstNameTooLong)?;
-
-            let lname_bounded: BoundedVec<_, ConstU32<64>> = lname.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::LastNameTooLong)?;
-
-            let address_bounded: BoundedVec<_, ConstU32<128>> = address.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::AddressTooLong)?;
-
-            let details = UserDetails {
-                fname: fname_bounded,
-                lname: lname_bounded,
-                address: address_bounded,
-                age,
-            };
+            ensure!(!Self::is_registered(&who), Error::<T>::DuplicateUser);
+            // a registrar confirms the identity with `set_user_status`
+            let details =
+                Self::bounded_details(fname, lname, address, birth_date, UserStatus::Pending)?;
 
+            let deposit = Self::set_deposit(&who, &details)?;
             UserDetailsStorage::<T>::insert(&who, details);
+            T::OnUserCreated::on_user_created(&who);
 
             Self::deposit_event(Event::UserDataCreated {
                 account: who,
+                deposit,
             });
 
             Ok(())
         }
 
-        /// Transfer `amount` from `origin` to `dest`.
+        /// Replaced by the `user_details` view function; always fails.
         #[pallet::call_index(2)]
-        #[pallet::weight(Weight::default())]
-        pub fn get_my_user_details(
+        #[pallet::weight(Weight::zero())]
+        #[deprecated(note = "read the record through the `user_details` view function; this call \
+            is removed in the next release")]
+        // copied onto the generated dispatch, which still calls it
+        #[allow(deprecated)]
+        pub fn get_my_user_details(origin: OriginFor<T>) -> DispatchResult {
+            ensure_signed(origin)?;
+            Err(Error::<T>::Deprecated.into())
+        }
+
+        /// Patch the caller's record; only the fields that are `Some(..)` are replaced.
+        #[pallet::call_index(3)]
+        #[pallet::weight({
+            let (f, l, a) = updates.lengths();
+            T::WeightInfo::update_user(f, l, a)
+        })]
+        pub fn update_user(
             origin: OriginFor<T>,
+            updates: UserDetailsUpdate,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            match UserDetailsStorage::<T>::get(&who) {
-
-                Some(details) => {
-                    Self::deposit_event(Event::UserDataFetched {
-                        account: who,
-                        fname: String::from_utf8(details.fname.to_vec()).unwrap_or_default(),
-                        lname: String::from_utf8(details.lname.to_vec()).unwrap_or_default(),
-                        address: String::from_utf8(details.address.to_vec()).unwrap_or_default(),
-                        age: details.age,
-                    });
-                    Ok(())
-                },
-                None => Err(Error::<T>::NoUserDataFound.into()),
+            // Validate supplied fields against the existing record
+            let (updated, changed) = Self::patched_details(&who, updates)?;
+            ensure!(!updated.status.is_locked(), Error::<T>::RecordLocked);
+
+            // Top up or refund the deposit to match the new record size
+            let deposit = Self::set_deposit(&who, &updated)?;
+            UserDetailsStorage::<T>::insert(&who, updated);
+
+            Self::deposit_event(Event::UserDataUpdated { account: who, deposit, changed });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(4)]
+        #[pallet::weight(
+            T::WeightInfo::remove_user(T::MaxLinkedAccounts::get(), T::MaxGuardians::get())
+                .saturating_add(T::OnUserRemoved::weight())
+        )]
+        pub fn remove_user(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            if let Some(details) = UserDetailsStorage::<T>::get(&who) {
+                ensure!(!details.status.is_locked(), Error::<T>::RecordLocked);
             }
+
+            // Remove the entry and give the storage deposit back
+            let released = Self::do_remove(&who)?;
+
+            // Optional: emit a removal event
+            Self::deposit_event(Event::UserDataRemoved { account: who.clone(), released }); // Or define a `UserDataRemoved` event
+
+            Ok(())
         }
 
-        #[pallet::call_index(3)]
-        #[pallet::weight(Weight::default())]
-        pub fn update_user(
+        /// Create a record for `who` on their behalf. Used to enroll citizens that have no
+        /// funds yet, so no deposit is held.
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::force_create_user(
+            fname.len() as u32,
+            lname.len() as u32,
+            address.len() as u32,
+        ).saturating_add(T::OnUserCreated::weight()))]
+        pub fn force_create_user(
             origin: OriginFor<T>,
+            who: T::AccountId,
             fname: String,
             lname: String,
             address: String,
-            age: u32,
+            birth_date: Date,
+        ) -> DispatchResult {
+            let registrar = T::RegistrarOrigin::ensure_origin(origin)?;
+
+            ensure!(!Self::is_registered(&who), Error::<T>::DuplicateUser);
+            // enrolled by a registrar, so already confirmed
+            let details =
+                Self::bounded_details(fname, lname, address, birth_date, UserStatus::Active)?;
+            UserDetailsStorage::<T>::insert(&who, details);
+            T::OnUserCreated::on_user_created(&who);
+
+            Self::deposit_event(Event::UserDataForceCreated { registrar, account: who });
+
+            Ok(())
+        }
+
+        /// Patch the record of `who`. The held deposit is not adjusted; it is recomputed the
+        /// next time the owner updates the record themselves.
+        #[pallet::call_index(6)]
+        #[pallet::weight({
+            let (f, l, a) = updates.lengths();
+            T::WeightInfo::force_update_user(f, l, a)
+        })]
+        pub fn force_update_user(
+            origin: OriginFor<T>,
+            who: T::AccountId,
+            updates: UserDetailsUpdate,
+        ) -> DispatchResult {
+            let registrar = T::RegistrarOrigin::ensure_origin(origin)?;
+
+            let (updated, changed) = Self::patched_details(&who, updates)?;
+            UserDetailsStorage::<T>::insert(&who, updated);
+
+            Self::deposit_event(Event::UserDataForceUpdated { registrar, account: who, changed });
+
+            Ok(())
+        }
+
+        /// Remove the record of `who`, e.g. a fraudulent registration. Any deposit is
+        /// returned to `who`.
+        #[pallet::call_index(7)]
+        #[pallet::weight(
+            T::WeightInfo::force_remove_user(T::MaxLinkedAccounts::get(), T::MaxGuardians::get())
+                .saturating_add(T::OnUserRemoved::weight())
+        )]
+        pub fn force_remove_user(
+            origin: OriginFor<T>,
+            who: T::AccountId,
         ) -> DispatchResult {
+            let registrar = T::RegistrarOrigin::ensure_origin(origin)?;
+
+            let released = Self::do_remove(&who)?;
+
+            Self::deposit_event(Event::UserDataForceRemoved { registrar, account: who, released });
+
+            Ok(())
+        }
+
+        /// Claim `username` for the caller. It is matched case-insensitively and stored in
+        /// lowercase. The caller needs a user record and must not hold a username already.
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::claim_username())]
+        pub fn claim_username(origin: OriginFor<T>, username: String) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let username = Self::normalize_username(username.as_bytes())?;
+            ensure!(UserDetailsStorage::<T>::contains_key(&who), Error::<T>::NoUserDataFound);
+            ensure!(!UsernameOf::<T>::contains_key(&who), Error::<T>::UsernameAlreadySet);
+            ensure!(!ReservedUsernames::<T>::contains_key(&username), Error::<T>::UsernameReserved);
+            ensure!(!UsernameOwner::<T>::contains_key(&username), Error::<T>::UsernameTaken);
+
+            UsernameOwner::<T>::insert(&username, &who);
+            UsernameOf::<T>::insert(&who, &username);
+
+            Self::deposit_event(Event::UsernameClaimed { account: who, username });
+
+            Ok(())
+        }
+
+        /// Give up the caller's username so anyone can claim it.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::release_username())]
+        pub fn release_username(origin: OriginFor<T>) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            // Convert to bounded vectors with validation
-            let fname_bounded: BoundedVec<_, ConstU32<64>> = fname.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::FirstNameTooLong)?;
+            let username = Self::release_username_of(&who).ok_or(Error::<T>::NoUsername)?;
+
+            Self::deposit_event(Event::UsernameReleased { account: who, username });
+
+            Ok(())
+        }
+
+        /// Hand the caller's username to `dest`, which needs a user record and no username.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::transfer_username())]
+        pub fn transfer_username(origin: OriginFor<T>, dest: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
 
-            let lname_bounded: BoundedVec<_, ConstU32<64>> = lname.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::LastNameTooLong)?;
+            ensure!(UserDetailsStorage::<T>::contains_key(&dest), Error::<T>::NoUserDataFound);
+            ensure!(!UsernameOf::<T>::contains_key(&dest), Error::<T>::UsernameAlreadySet);
+            let username = UsernameOf::<T>::take(&who).ok_or(Error::<T>::NoUsername)?;
 
-            let address_bounded: BoundedVec<_, ConstU32<128>> = address.clone().into_bytes()
-                .try_into().map_err(|_| Error::<T>::AddressTooLong)?;
+            UsernameOwner::<T>::insert(&username, &dest);
+            UsernameOf::<T>::insert(&dest, &username);
+
+            Self::deposit_event(Event::UsernameTransferred { from: who, to: dest, username });
+
+            Ok(())
+        }
+
+        /// Stop `username` from being claimed. An account already holding it keeps it.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::reserve_username())]
+        pub fn reserve_username(origin: OriginFor<T>, username: String) -> DispatchResult {
+            ensure_root(origin)?;
+
+            let username = Self::normalize_username(username.as_bytes())?;
+            ReservedUsernames::<T>::insert(&username, ());
+
+            Self::deposit_event(Event::UsernameReserved { username });
+
+            Ok(())
+        }
+
+        /// Allow `username` to be claimed again.
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::unreserve_username())]
+        pub fn unreserve_username(origin: OriginFor<T>, username: String) -> DispatchResult {
+            ensure_root(origin)?;
+
+            let username = Self::normalize_username(username.as_bytes())?;
+            ReservedUsernames::<T>::take(&username).ok_or(Error::<T>::UsernameNotReserved)?;
+
+            Self::deposit_event(Event::UsernameUnreserved { username });
+
+            Ok(())
+        }
+
+        /// Let `threshold` of `guardians` move the caller's records to a new account. Replaces
+        /// any previous configuration; not allowed while a recovery is in progress.
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::set_recovery_config(guardians.len() as u32))]
+        pub fn set_recovery_config(
+            origin: OriginFor<T>,
+            guardians: Vec<T::AccountId>,
+            threshold: u32,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
 
-            // Check if user data exists
             ensure!(UserDetailsStorage::<T>::contains_key(&who), Error::<T>::NoUserDataFound);
+            ensure!(!Self::is_being_recovered(&who), Error::<T>::RecoveryInProgress);
 
-            // Overwrite data
-            let updated = UserDetails {
-                fname: fname_bounded,
-                lname: lname_bounded,
-                address: address_bounded,
-                age,
+            let mut guardians = guardians;
+            let count = guardians.len();
+            guardians.sort();
+            guardians.dedup();
+            ensure!(
+                count > 0 && guardians.len() == count && !guardians.contains(&who),
+                Error::<T>::InvalidGuardians
+            );
+            let guardians: BoundedVec<_, T::MaxGuardians> =
+                guardians.try_into().map_err(|_| Error::<T>::InvalidGuardians)?;
+            ensure!(
+                threshold >= 1 && threshold as usize <= guardians.len(),
+                Error::<T>::InvalidThreshold
+            );
+
+            let count = guardians.len() as u32;
+            RecoveryConfigs::<T>::insert(&who, RecoveryConfig { guardians, threshold });
+
+            Self::deposit_event(Event::RecoveryConfigured { account: who, guardians: count, threshold });
+
+            Ok(())
+        }
+
+        /// Drop the caller's recovery configuration; not allowed while a recovery is in progress.
+        #[pallet::call_index(14)]
+        #[pallet::weight(T::WeightInfo::remove_recovery_config())]
+        pub fn remove_recovery_config(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(!Self::is_being_recovered(&who), Error::<T>::RecoveryInProgress);
+            RecoveryConfigs::<T>::take(&who).ok_or(Error::<T>::NoRecoveryConfig)?;
+
+            Self::deposit_event(Event::RecoveryConfigRemoved { account: who });
+
+            Ok(())
+        }
+
+        /// As a guardian of `lost`, vouch for moving its records to `new_account`. The first
+        /// vouch for a new account starts a recovery to it; once `threshold` guardians agree,
+        /// it can be claimed after `RecoveryDelay` blocks. A guardian backs one recovery of
+        /// `lost` at a time: vouching for another new account withdraws its earlier vouch, so
+        /// a mistaken or malicious target never blocks the others.
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::vouch_recovery(T::MaxGuardians::get()))]
+        pub fn vouch_recovery(
+            origin: OriginFor<T>,
+            lost: T::AccountId,
+            new_account: T::AccountId,
+        ) -> DispatchResult {
+            let guardian = ensure_signed(origin)?;
+
+            let config = RecoveryConfigs::<T>::get(&lost).ok_or(Error::<T>::NoRecoveryConfig)?;
+            ensure!(config.guardians.binary_search(&guardian).is_ok(), Error::<T>::NotGuardian);
+
+            let mut recovery = match ActiveRecoveries::<T>::get(&lost, &new_account) {
+                Some(recovery) => recovery,
+                None => {
+                    ensure!(new_account != lost, Error::<T>::RecoveryTargetMismatch);
+                    ensure!(!Self::is_registered(&new_account), Error::<T>::DuplicateUser);
+                    ActiveRecovery { vouchers: BoundedVec::default(), claimable_at: None }
+                },
             };
 
-            UserDetailsStorage::<T>::insert(&who, updated);
+            let pos = recovery
+                .vouchers
+                .binary_search(&guardian)
+                .err()
+                .ok_or(Error::<T>::AlreadyVouched)?;
+            // at most one vouch per guardian, so this stays within `MaxGuardians`
+            recovery
+                .vouchers
+                .try_insert(pos, guardian.clone())
+                .map_err(|_| Error::<T>::InvalidGuardians)?;
+            Self::withdraw_vouch(&lost, &guardian, config.threshold);
+
+            Self::deposit_event(Event::RecoveryVouched {
+                lost: lost.clone(),
+                guardian,
+                new_account: new_account.clone(),
+            });
+
+            if recovery.claimable_at.is_none() && recovery.vouchers.len() as u32 >= config.threshold {
+                let claimable_at = frame_system::Pallet::<T>::block_number()
+                    .saturating_add(T::RecoveryDelay::get());
+                recovery.claimable_at = Some(claimable_at);
+                Self::deposit_event(Event::RecoveryApproved {
+                    lost: lost.clone(),
+                    new_account: new_account.clone(),
+                    claimable_at,
+                });
+            }
 
-            // Emit updated event if needed (reuse or create new)
-            Self::deposit_event(Event::UserDataUpdated { account: who });
+            ActiveRecoveries::<T>::insert(&lost, &new_account, recovery);
 
             Ok(())
         }
 
-        #[pallet::call_index(4)]
-        #[pallet::weight(Weight::default())]
-        pub fn remove_user(origin: OriginFor<T>) -> DispatchResult {
+        /// As the owner of a lost account that is not actually lost, stop every recovery of it.
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::cancel_recovery(T::MaxGuardians::get()))]
+        pub fn cancel_recovery(origin: OriginFor<T>) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            // Check if data exists before attempting to remove
-            ensure!(UserDetailsStorage::<T>::contains_key(&who), Error::<T>::NoUserDataFound);
+            ensure!(Self::is_being_recovered(&who), Error::<T>::NoActiveRecovery);
+            let _ = ActiveRecoveries::<T>::clear_prefix(&who, T::MaxGuardians::get(), None);
 
-            // Remove the entry
-            UserDetailsStorage::<T>::remove(&who);
+            Self::deposit_event(Event::RecoveryCancelled { lost: who });
 
-            // Optional: emit a removal event
-            Self::deposit_event(Event::UserDataRemoved { account: who.clone() }); // Or define a `UserDataRemoved` event
+            Ok(())
+        }
+
+        /// Move the records of `lost` to `new_account` once the recovery to it is claimable,
+        /// ending every other recovery of `lost`. Any signed account may submit this. The
+        /// deposit moves with the record, so the new account must already exist, i.e. hold at
+        /// least the existential deposit.
+        #[pallet::call_index(17)]
+        #[pallet::weight(
+            T::WeightInfo::claim_recovery(T::MaxLinkedAccounts::get(), T::MaxGuardians::get())
+                .saturating_add(T::OnUserMoved::weight())
+        )]
+        pub fn claim_recovery(
+            origin: OriginFor<T>,
+            lost: T::AccountId,
+            new_account: T::AccountId,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let recovery =
+                ActiveRecoveries::<T>::get(&lost, &new_account).ok_or(Error::<T>::NoActiveRecovery)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(
+                recovery.claimable_at.is_some_and(|at| at <= now),
+                Error::<T>::RecoveryNotClaimable
+            );
+            ensure!(!Self::is_registered(&new_account), Error::<T>::DuplicateUser);
+            T::OnUserMoved::can_move(&lost, &new_account)?;
+
+            let _ = ActiveRecoveries::<T>::clear_prefix(&lost, T::MaxGuardians::get(), None);
+            Self::do_move(&lost, &new_account)?;
+
+            Self::deposit_event(Event::UserRecovered { lost, new_account });
+
+            Ok(())
+        }
+
+        /// As an account without a record of its own, ask to be linked to the record of
+        /// `primary`. The link is made once `primary` accepts; a new request replaces the
+        /// previous one.
+        #[pallet::call_index(18)]
+        #[pallet::weight(T::WeightInfo::request_link())]
+        pub fn request_link(origin: OriginFor<T>, primary: T::AccountId) -> DispatchResult {
+            let secondary = ensure_signed(origin)?;
+
+            ensure!(secondary != primary, Error::<T>::CannotLinkSelf);
+            ensure!(!Self::is_registered(&secondary), Error::<T>::DuplicateUser);
+            ensure!(UserDetailsStorage::<T>::contains_key(&primary), Error::<T>::NoUserDataFound);
+
+            PendingLinks::<T>::insert(&secondary, &primary);
+
+            Self::deposit_event(Event::LinkRequested { primary, secondary });
+
+            Ok(())
+        }
+
+        /// Accept the link request of `secondary`, after which it resolves to the caller's
+        /// record.
+        #[pallet::call_index(19)]
+        #[pallet::weight(T::WeightInfo::accept_link())]
+        pub fn accept_link(origin: OriginFor<T>, secondary: T::AccountId) -> DispatchResult {
+            let primary = ensure_signed(origin)?;
+
+            ensure!(
+                PendingLinks::<T>::get(&secondary).as_ref() == Some(&primary),
+                Error::<T>::NoPendingLink
+            );
+            ensure!(UserDetailsStorage::<T>::contains_key(&primary), Error::<T>::NoUserDataFound);
+            // the secondary may have registered since it asked
+            ensure!(!Self::is_registered(&secondary), Error::<T>::DuplicateUser);
+
+            LinkedAccounts::<T>::try_mutate(&primary, |linked| {
+                linked
+                    .get_or_insert_with(BoundedVec::default)
+                    .try_push(secondary.clone())
+                    .map_err(|_| Error::<T>::TooManyLinkedAccounts)
+            })?;
+            PrimaryOf::<T>::insert(&secondary, &primary);
+            PendingLinks::<T>::remove(&secondary);
+
+            Self::deposit_event(Event::AccountLinked { primary, secondary });
+
+            Ok(())
+        }
+
+        /// Undo a link from either side: a primary passes the secondary to drop, a secondary
+        /// passes its primary. A secondary can also withdraw a pending request this way.
+        #[pallet::call_index(20)]
+        #[pallet::weight(T::WeightInfo::unlink())]
+        pub fn unlink(origin: OriginFor<T>, other: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (primary, secondary) = if PrimaryOf::<T>::get(&who).as_ref() == Some(&other) {
+                (other, who)
+            } else if PrimaryOf::<T>::get(&other).as_ref() == Some(&who) {
+                (who, other)
+            } else if PendingLinks::<T>::get(&who).as_ref() == Some(&other) {
+                PendingLinks::<T>::remove(&who);
+                Self::deposit_event(Event::AccountUnlinked { primary: other, secondary: who });
+                return Ok(());
+            } else {
+                return Err(Error::<T>::NotLinked.into());
+            };
+
+            PrimaryOf::<T>::remove(&secondary);
+            LinkedAccounts::<T>::mutate_exists(&primary, |linked| {
+                if let Some(list) = linked {
+                    list.retain(|a| *a != secondary);
+                    if list.is_empty() {
+                        *linked = None;
+                    }
+                }
+            });
+
+            Self::deposit_event(Event::AccountUnlinked { primary, secondary });
+
+            Ok(())
+        }
+
+        /// Move the record of `who` to `status`: confirm a pending registration, suspend or
+        /// reinstate a record, or mark its holder deceased.
+        #[pallet::call_index(21)]
+        #[pallet::weight(T::WeightInfo::set_user_status())]
+        pub fn set_user_status(
+            origin: OriginFor<T>,
+            who: T::AccountId,
+            status: UserStatus,
+        ) -> DispatchResult {
+            let registrar = T::RegistrarOrigin::ensure_origin(origin)?;
+
+            let from = UserDetailsStorage::<T>::try_mutate(&who, |maybe| {
+                let details = maybe.as_mut().ok_or(Error::<T>::NoUserDataFound)?;
+                let from = details.status;
+                ensure!(from.can_become(status), Error::<T>::InvalidStatusTransition);
+                details.status = status;
+                Ok::<_, DispatchError>(from)
+            })?;
+
+            Self::deposit_event(Event::UserStatusChanged { registrar, account: who, from, to: status });
+
+            Ok(())
+        }
+
+        /// Make `registrar` a registrar: it passes `RegistrarOrigin` where the runtime backs
+        /// that with `Registrars`, and reads `RegistrarsOnly` profile fields.
+        #[pallet::call_index(22)]
+        #[pallet::weight(T::WeightInfo::add_registrar(T::MaxRegistrars::get()))]
+        pub fn add_registrar(origin: OriginFor<T>, registrar: T::AccountId) -> DispatchResult {
+            ensure_root(origin)?;
+
+            Registrars::<T>::try_mutate(|registrars| {
+                let at = registrars.binary_search(&registrar).err().ok_or(Error::<T>::AlreadyRegistrar)?;
+                registrars.try_insert(at, registrar.clone()).map_err(|_| Error::<T>::TooManyRegistrars)
+            })?;
+
+            Self::deposit_event(Event::RegistrarAdded { registrar });
+
+            Ok(())
+        }
+
+        /// Stop `registrar` being a registrar. Records it enrolled are kept.
+        #[pallet::call_index(23)]
+        #[pallet::weight(T::WeightInfo::remove_registrar(T::MaxRegistrars::get()))]
+        pub fn remove_registrar(origin: OriginFor<T>, registrar: T::AccountId) -> DispatchResult {
+            ensure_root(origin)?;
+
+            Registrars::<T>::try_mutate(|registrars| {
+                let at = registrars.binary_search(&registrar).map_err(|_| Error::<T>::NotRegistrar)?;
+                registrars.remove(at);
+                Ok::<_, Error<T>>(())
+            })?;
+
+            Self::deposit_event(Event::RegistrarRemoved { registrar });
 
             Ok(())
         }
     }
 
     // pallets/user/src/lib.rs  
+    /// Lookups resolve linked secondary accounts to their primary account.
     pub trait UserApi<AccountId> {
         fn user_exists(who: &AccountId) -> bool;
         fn full_name(who: &AccountId) -> Option<(Vec<u8>, Vec<u8>)>;
+        /// Current age in whole years, derived from the stored birth date.
+        fn age_of(who: &AccountId) -> Option<u32>;
+        /// Owner of `username`, matched case-insensitively.
+        fn account_by_username(username: &[u8]) -> Option<AccountId>;
+        /// The account holding the record `who` resolves to; `who` itself unless it is a
+        /// linked secondary account.
+        fn primary_account(who: &AccountId) -> AccountId;
+        /// The record `who` resolves to exists and is `Active`.
+        fn is_active(who: &AccountId) -> bool;
+        /// A signed origin of `who` passes `RegistrarOrigin`.
+        fn is_registrar(who: &AccountId) -> bool;
+    }
+
+    /// Hook run after a user record is created.
+    pub trait OnUserCreated<AccountId> {
+        fn on_user_created(who: &AccountId);
+
+        /// Upper bound of the weight consumed by `on_user_created`.
+        fn weight() -> Weight;
+    }
+
+    #[impl_trait_for_tuples::impl_for_tuples(8)]
+    impl<AccountId> OnUserCreated<AccountId> for Tuple {
+        fn on_user_created(who: &AccountId) {
+            for_tuples!( #( Tuple::on_user_created(who); )* );
+        }
+
+        fn weight() -> Weight {
+            let mut weight = Weight::zero();
+            for_tuples!( #( weight = weight.saturating_add(Tuple::weight()); )* );
+            weight
+        }
+    }
+
+    /// Hook run after a user record moved to a new account through recovery. Implementors
+    /// must not fail; the record already lives under `to` when this is called. Anything that
+    /// would make the move lose data has to be rejected up front by `can_move`.
+    pub trait OnUserMoved<AccountId> {
+        /// Checked before anything moves; an error aborts the recovery.
+        fn can_move(_from: &AccountId, _to: &AccountId) -> DispatchResult {
+            Ok(())
+        }
+
+        fn on_user_moved(from: &AccountId, to: &AccountId);
+
+        /// Upper bound of the weight consumed by `on_user_moved`.
+        fn weight() -> Weight;
+    }
+
+    #[impl_trait_for_tuples::impl_for_tuples(8)]
+    impl<AccountId> OnUserMoved<AccountId> for Tuple {
+        fn can_move(from: &AccountId, to: &AccountId) -> DispatchResult {
+            for_tuples!( #( Tuple::can_move(from, to)?; )* );
+            Ok(())
+        }
+
+        fn on_user_moved(from: &AccountId, to: &AccountId) {
+            for_tuples!( #( Tuple::on_user_moved(from, to); )* );
+        }
+
+        fn weight() -> Weight {
+            let mut weight = Weight::zero();
+            for_tuples!( #( weight = weight.saturating_add(Tuple::weight()); )* );
+            weight
+        }
+    }
+
+    /// Hook run after a user record is removed. Implementors must not fail; the record is
+    /// already gone when this is called.
+    pub trait OnUserRemoved<AccountId> {
+        fn on_user_removed(who: &AccountId);
+
+        /// Upper bound of the weight consumed by `on_user_removed`.
+        fn weight() -> Weight;
+    }
+
+    #[impl_trait_for_tuples::impl_for_tuples(8)]
+    impl<AccountId> OnUserRemoved<AccountId> for Tuple {
+        fn on_user_removed(who: &AccountId) {
+            for_tuples!( #( Tuple::on_user_removed(who); )* );
+        }
+
+        fn weight() -> Weight {
+            let mut weight = Weight::zero();
+            for_tuples!( #( weight = weight.saturating_add(Tuple::weight()); )* );
+            weight
+        }
     }
 
     // implementation for the pallet itself
     impl<T: Config> UserApi<T::AccountId> for Pallet<T> {
         fn user_exists(who: &T::AccountId) -> bool {
-            UserDetailsStorage::<T>::contains_key(who)
+            Self::is_registered(who)
         }
         fn full_name(who: &T::AccountId) -> Option<(Vec<u8>, Vec<u8>)> {
-            UserDetailsStorage::<T>::get(who)
+            UserDetailsStorage::<T>::get(Self::primary_account(who))
                 .map(|d| (
                     d.fname.to_vec(), 
                     d.lname.to_vec(),
                 ))
         }
+        fn age_of(who: &T::AccountId) -> Option<u32> {
+            UserDetailsStorage::<T>::get(Self::primary_account(who)).map(|d| Self::age(&d))
+        }
+        fn account_by_username(username: &[u8]) -> Option<T::AccountId> {
+            Self::normalize_username(username).ok().and_then(UsernameOwner::<T>::get)
+        }
+        fn primary_account(who: &T::AccountId) -> T::AccountId {
+            Self::primary_account(who)
+        }
+        fn is_active(who: &T::AccountId) -> bool {
+            UserDetailsStorage::<T>::get(Self::primary_account(who))
+                .is_some_and(|d| d.status == UserStatus::Active)
+        }
+        fn is_registrar(who: &T::AccountId) -> bool {
+            T::RegistrarOrigin::try_origin(frame_system::RawOrigin::Signed(who.clone()).into()).is_ok()
+        }
+    }
+
+    impl<T: Config> SortedMembers<T::AccountId> for Pallet<T> {
+        fn sorted_members() -> Vec<T::AccountId> {
+            Registrars::<T>::get().into_inner()
+        }
+
+        fn contains(who: &T::AccountId) -> bool {
+            Registrars::<T>::get().binary_search(who).is_ok()
+        }
+
+        #[cfg(feature = "runtime-benchmarks")]
+        fn add(who: &T::AccountId) {
+            Registrars::<T>::mutate(|registrars| {
+                if let Err(at) = registrars.binary_search(who) {
+                    let _ = registrars.try_insert(at, who.clone());
+                }
+            });
+        }
     }
 
 }
@@ -257,17 +1495,37 @@ pub mod pallet {
 
 #[cfg(feature = "runtime-api")]
 pub mod runtime_api {
-    use super::*;
-    use sp_std::prelude::*;
-    use sp_runtime::traits::Convert;
     use sp_api::decl_runtime_apis;
-    use scale_info::prelude::string::String;
+    use scale_info::prelude::{string::String, vec::Vec};
 
     use frame_support::sp_runtime::AccountId32;
 
     decl_runtime_apis! {
+        /// Version 2 adds `age_of`; the `u32` in `get_user_details` is now derived from the
+        /// stored birth date. Version 3 adds `account_by_username`, version 4 the listing calls.
+        /// Version 5 resolves linked secondary accounts to their primary and adds
+        /// `primary_account`.
+        #[api_version(5)]
         pub trait UserDetailsApi {
             fn get_user_details(account: AccountId32) -> Option<(String, String, String, u32)>;
+
+            /// Current age in whole years of `account`, `None` if it has no record.
+            fn age_of(account: AccountId32) -> Option<u32>;
+
+            /// Owner of `username`, matched case-insensitively.
+            fn account_by_username(username: Vec<u8>) -> Option<AccountId32>;
+
+            /// Up to `limit` registered accounts following `start_after`, in storage order.
+            /// Pass the last account of a page to get the next one. Pages are capped at
+            /// `MAX_PAGE_LEN`.
+            fn list_users(start_after: Option<AccountId32>, limit: u32) -> Vec<AccountId32>;
+
+            /// Number of registered users.
+            fn count_users() -> u32;
+
+            /// The account holding the record `account` resolves to; `account` itself unless
+            /// it is a linked secondary account.
+            fn primary_account(account: AccountId32) -> AccountId32;
         }
     }
 }
\ No newline at end of file
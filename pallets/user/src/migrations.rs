@@ -0,0 +1,614 @@
+// pallets/user/src/migrations.rs
+//! Storage migrations for the user pallet.
+//!
+//! Every schema change gets its own `vN` module holding an `UncheckedOnRuntimeUpgrade` and a
+//! `MigrateV{N-1}ToV{N}` alias wrapping it in [`VersionedMigration`], which takes care of the
+//! on-chain `StorageVersion` check and bump. Add the alias to the runtime's `Migrations` tuple.
+
+use super::*;
+use frame_support::{
+    migrations::VersionedMigration,
+    pallet_prelude::*,
+    storage::{storage_prefix, unhashed, KeyPrefixIterator},
+    traits::{OnRuntimeUpgrade, PalletInfoAccess, UncheckedOnRuntimeUpgrade},
+    Blake2_128, StorageHasher,
+};
+
+use scale_info::prelude::vec::Vec;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+/// v0 -> v1: drop the never-used `TotalIssuance` and `Balances` storage items.
+pub mod v1 {
+    use super::*;
+
+    /// Storage items removed in v1.
+    pub const REMOVED_ITEMS: [&[u8]; 2] = [b"TotalIssuance", b"Balances"];
+
+    fn removed_prefixes<T: Config>() -> impl Iterator<Item = [u8; 32]> {
+        let pallet = <Pallet<T> as PalletInfoAccess>::name();
+        REMOVED_ITEMS.into_iter().map(move |item| storage_prefix(pallet.as_bytes(), item))
+    }
+
+    /// Unversioned body of the v0 -> v1 migration. Use [`MigrateV0ToV1`] instead.
+    pub struct UncheckedMigrateToV1<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut removed = 0u64;
+            for prefix in removed_prefixes::<T>() {
+                removed = removed
+                    .saturating_add(unhashed::clear_prefix(&prefix, None, None).unique as u64);
+            }
+            log::info!(target: LOG_TARGET, "v1: removed {} stale keys", removed);
+
+            T::DbWeight::get().writes(removed)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok(v3::entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let users_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v1: bad pre_upgrade state"))?;
+            ensure!(v3::entries::<T>() == users_before, "v1: user records changed");
+            for prefix in removed_prefixes::<T>() {
+                ensure!(!unhashed::contains_prefixed_key(&prefix), "v1: stale keys left behind");
+            }
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 0 to 1.
+    pub type MigrateV0ToV1<T> = VersionedMigration<
+        0,
+        1,
+        UncheckedMigrateToV1<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// v1 -> v2: replace the stored `age` with a birth date.
+///
+/// An age alone does not pin down a birthday, so the birth date is estimated as today's date
+/// (per `T::UnixTime` at the upgrade block) minus `age` years. Holders can correct it with
+/// `update_user`.
+pub mod v2 {
+    use super::*;
+    use frame_support::sp_runtime::BoundedVec;
+
+    /// The map as written by this migration, in the layout of [`v4::OldUserDetails`].
+    #[frame_support::storage_alias]
+    type UserDetailsStorage<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128,
+        <T as frame_system::Config>::AccountId,
+        v4::OldUserDetails,
+        OptionQuery,
+    >;
+
+    /// `UserDetails` as stored up to v1.
+    #[derive(Encode, Decode, TypeInfo, MaxEncodedLen, RuntimeDebug)]
+    pub struct OldUserDetails {
+        pub fname: BoundedVec<u8, ConstU32<64>>,
+        pub lname: BoundedVec<u8, ConstU32<64>>,
+        pub address: BoundedVec<u8, ConstU32<128>>,
+        pub age: u32,
+    }
+
+    /// Unversioned body of the v1 -> v2 migration. Use [`MigrateV1ToV2`] instead.
+    pub struct UncheckedMigrateToV2<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV2<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let today = Pallet::<T>::today();
+            let mut translated = 0u64;
+            UserDetailsStorage::<T>::translate_values::<OldUserDetails, _>(|old| {
+                translated += 1;
+                Some(v4::OldUserDetails {
+                    fname: old.fname,
+                    lname: old.lname,
+                    address: old.address,
+                    birth_date: today.years_before(old.age),
+                })
+            });
+            log::info!(target: LOG_TARGET, "v2: estimated birth dates of {} users", translated);
+
+            // one extra read for the current time
+            T::DbWeight::get().reads_writes(translated.saturating_add(1), translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok(v3::entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let users_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v2: bad pre_upgrade state"))?;
+            let mut users_after = 0u32;
+            for details in UserDetailsStorage::<T>::iter_values() {
+                ensure!(details.birth_date.is_valid(), "v2: invalid birth date");
+                users_after += 1;
+            }
+            ensure!(users_after == users_before, "v2: user records lost");
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 1 to 2.
+    pub type MigrateV1ToV2<T> = VersionedMigration<
+        1,
+        2,
+        UncheckedMigrateToV2<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// v2 -> v3: re-key `UserDetailsStorage` from `Blake2_128` to `Blake2_128Concat` and count it.
+///
+/// The old hasher is not reversible, so records are matched against every account in
+/// `frame_system::Account`. That map is unbounded, so the upgrade only opens a cursor and the
+/// pallet's `on_initialize` walks [`ACCOUNTS_PER_STEP`] accounts per block through [`step`].
+/// Records of accounts the system pallet does not know (registrar enrolments that were never
+/// funded) can not be re-keyed. Once every account has been visited, `step` walks the map
+/// itself and deletes them; such users have to be enrolled again.
+pub mod v3 {
+    use super::*;
+
+    /// Accounts of `frame_system::Account` visited per block while re-keying.
+    pub const ACCOUNTS_PER_STEP: u32 = 64;
+
+    /// Progress of the re-keying: `None` before the first step, then the last account visited.
+    /// Absent once it is done.
+    #[frame_support::storage_alias]
+    type RekeyCursor<T: Config> =
+        StorageValue<Pallet<T>, Option<<T as frame_system::Config>::AccountId>, OptionQuery>;
+
+    /// Progress of the removal of records that could not be re-keyed: the last raw key
+    /// visited. Absent before the re-keying is done and once the removal is.
+    #[frame_support::storage_alias]
+    type SweepCursor<T: Config> = StorageValue<Pallet<T>, Vec<u8>, OptionQuery>;
+
+    /// The counter of the counted `UserDetailsStorage`.
+    #[frame_support::storage_alias]
+    type CounterForUserDetailsStorage<T: Config> = StorageValue<Pallet<T>, u32, ValueQuery>;
+
+    fn map_prefix<T: Config>() -> [u8; 32] {
+        storage_prefix(<Pallet<T> as PalletInfoAccess>::name().as_bytes(), b"UserDetailsStorage")
+    }
+
+    /// Key of `who` under the v2 layout.
+    fn old_key<T: Config>(who: &T::AccountId) -> Vec<u8> {
+        [&map_prefix::<T>()[..], &who.using_encoded(Blake2_128::hash)[..]].concat()
+    }
+
+    /// Key lengths of `UserDetailsStorage`, without the prefix, under either layout.
+    fn key_lengths<T: Config>() -> KeyPrefixIterator<usize> {
+        let prefix = map_prefix::<T>().to_vec();
+        KeyPrefixIterator::new(prefix.clone(), prefix, |key| Ok(key.len()))
+    }
+
+    /// Number of entries still under the v2 key layout. Walks the whole map; off-chain use only.
+    pub fn old_layout_entries<T: Config>() -> u32 {
+        key_lengths::<T>().filter(|len| *len == 16).count() as u32
+    }
+
+    /// Number of records under either key layout, whatever their value layout. Walks the whole
+    /// map; off-chain use only.
+    pub fn entries<T: Config>() -> u32 {
+        key_lengths::<T>().count() as u32
+    }
+
+    /// The re-keying, or the removal of what it could not re-key, has started and not finished
+    /// yet.
+    pub fn rekey_pending<T: Config>() -> bool {
+        RekeyCursor::<T>::exists() || SweepCursor::<T>::exists()
+    }
+
+    /// Translate every value of the map at `prefix` from `O` to `V`, whether its key is still a
+    /// bare `Blake2_128` hash or already `Blake2_128Concat`. `f` gets the key of the latter
+    /// only; the former can not be reversed. Values that do not decode as `O` are logged and
+    /// left alone. Returns the number of values translated.
+    pub fn translate_either<K: Decode, O: Decode, V: Encode>(
+        prefix: &[u8],
+        mut f: impl FnMut(Option<K>, O) -> V,
+    ) -> u64 {
+        let keys = KeyPrefixIterator::new(prefix.to_vec(), prefix.to_vec(), |key| Ok(key.to_vec()));
+        let mut translated = 0u64;
+        for key in keys {
+            let full_key = [prefix, &key[..]].concat();
+            let Some(old) = unhashed::get::<O>(&full_key) else {
+                log::warn!(target: LOG_TARGET, "could not decode the value at {:?}", full_key);
+                continue;
+            };
+            // `Blake2_128Concat` appends the encoded key to the 16 hash bytes
+            let decoded = key
+                .get(16..)
+                .filter(|rest| !rest.is_empty())
+                .and_then(|mut rest| K::decode(&mut rest).ok());
+            unhashed::put(&full_key, &f(decoded, old));
+            translated += 1;
+        }
+        translated
+    }
+
+    /// Translate every record from `O` to `V` under either key layout, see
+    /// [`translate_either`].
+    pub fn translate_records<T: Config, O: Decode, V: Encode>(
+        f: impl FnMut(Option<T::AccountId>, O) -> V,
+    ) -> u64 {
+        translate_either(&map_prefix::<T>(), f)
+    }
+
+    /// Remove the entries of the map at `prefix` still under a `Blake2_128` key, i.e. a bare
+    /// 16-byte hash, visiting at most `limit` keys after `last`. Returns the last key visited,
+    /// `None` once the map is done, and the numbers of keys visited and removed.
+    pub fn sweep_old_keys(prefix: &[u8], last: Vec<u8>, limit: u32) -> (Option<Vec<u8>>, u64, u64) {
+        let mut keys = KeyPrefixIterator::new(prefix.to_vec(), last, |key| Ok(key.len() == 16));
+        let (mut visited, mut removed) = (0u64, 0u64);
+        while visited < limit as u64 {
+            let Some(old) = keys.next() else {
+                return (None, visited, removed);
+            };
+            visited += 1;
+            if old {
+                unhashed::kill(keys.last_raw_key());
+                removed += 1;
+            }
+        }
+        (Some(keys.last_raw_key().to_vec()), visited, removed)
+    }
+
+    /// `who` still has a record under the v2 key, waiting to be re-keyed.
+    pub fn awaits_rekey<T: Config>(who: &T::AccountId) -> bool {
+        rekey_pending::<T>() && unhashed::exists(&old_key::<T>(who))
+    }
+
+    /// Re-key the records of the next `limit` accounts, or once all are visited remove up to
+    /// `limit` records that were not re-keyed, and return the weight consumed.
+    ///
+    /// Values are moved as raw bytes, so this works whatever the record layout is by the time
+    /// it runs.
+    pub fn step<T: Config>(limit: u32) -> Weight {
+        let Some(cursor) = RekeyCursor::<T>::get() else {
+            return sweep::<T>(limit);
+        };
+        let mut accounts = match &cursor {
+            Some(last) => frame_system::Account::<T>::iter_keys_from(
+                frame_system::Account::<T>::hashed_key_for(last),
+            ),
+            None => frame_system::Account::<T>::iter_keys(),
+        };
+
+        let (mut visited, mut found, mut moved) = (0u64, 0u64, 0u64);
+        let mut last = cursor;
+        let mut done = false;
+        while visited < limit as u64 {
+            let Some(who) = accounts.next() else {
+                done = true;
+                break;
+            };
+            visited += 1;
+            let key = old_key::<T>(&who);
+            if let Some(raw) = unhashed::get_raw(&key) {
+                unhashed::kill(&key);
+                found += 1;
+                let new_key = UserDetailsStorage::<T>::hashed_key_for(&who);
+                if unhashed::exists(&new_key) {
+                    log::warn!(target: LOG_TARGET, "v3: {:?} has two records, kept the new one", who);
+                } else {
+                    unhashed::put_raw(&new_key, &raw);
+                    CounterForUserDetailsStorage::<T>::mutate(|count| *count += 1);
+                    moved += 1;
+                }
+            }
+            last = Some(who);
+        }
+
+        if done {
+            RekeyCursor::<T>::kill();
+            SweepCursor::<T>::put(map_prefix::<T>().to_vec());
+            log::info!(target: LOG_TARGET, "v3: re-keying done");
+        } else {
+            RekeyCursor::<T>::put(last);
+        }
+        if moved > 0 {
+            log::info!(target: LOG_TARGET, "v3: re-keyed {} users", moved);
+        }
+
+        // the cursor; per account: read it and probe the old key; per record found: probe the
+        // new key and remove the old one; per move: write it and bump the counter
+        T::DbWeight::get().reads_writes(
+            1 + visited.saturating_mul(2) + found + moved,
+            1 + found + moved.saturating_mul(2),
+        )
+    }
+
+    /// Remove up to `limit` records left under the v2 key layout and return the weight
+    /// consumed.
+    fn sweep<T: Config>(limit: u32) -> Weight {
+        let Some(last) = SweepCursor::<T>::get() else {
+            // both cursors
+            return T::DbWeight::get().reads(2);
+        };
+        let (last, visited, removed) = sweep_old_keys(&map_prefix::<T>(), last, limit);
+        match last {
+            Some(last) => SweepCursor::<T>::put(last),
+            None => {
+                SweepCursor::<T>::kill();
+                log::info!(target: LOG_TARGET, "v3: removal of records without an account done");
+            },
+        }
+        if removed > 0 {
+            log::warn!(target: LOG_TARGET, "v3: removed {} records without a known account", removed);
+        }
+
+        // both cursors; per key: read it; per record removed: remove it
+        T::DbWeight::get().reads_writes(2 + visited, 1 + removed)
+    }
+
+    /// Unversioned body of the v2 -> v3 migration. Use [`MigrateV2ToV3`] instead.
+    pub struct UncheckedMigrateToV3<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV3<T> {
+        fn on_runtime_upgrade() -> Weight {
+            RekeyCursor::<T>::put(None::<T::AccountId>);
+            log::info!(target: LOG_TARGET, "v3: re-keying users over the next blocks");
+            T::DbWeight::get().writes(1)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok(old_layout_entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let users_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v3: bad pre_upgrade state"))?;
+            ensure!(rekey_pending::<T>(), "v3: re-keying not scheduled");
+            ensure!(old_layout_entries::<T>() == users_before, "v3: records moved early");
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 2 to 3.
+    pub type MigrateV2ToV3<T> = VersionedMigration<
+        2,
+        3,
+        UncheckedMigrateToV3<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// v3 -> v4: add a lifecycle `status` to `UserDetails`. Existing records become `Active`.
+///
+/// The record grows by one byte; the held deposit catches up on the owner's next update.
+pub mod v4 {
+    use super::*;
+    use frame_support::sp_runtime::BoundedVec;
+
+    /// `UserDetails` as stored from v2 up to v3.
+    #[derive(Encode, Decode, TypeInfo, MaxEncodedLen, PartialEq, Eq, RuntimeDebug)]
+    pub struct OldUserDetails {
+        pub fname: BoundedVec<u8, ConstU32<64>>,
+        pub lname: BoundedVec<u8, ConstU32<64>>,
+        pub address: BoundedVec<u8, ConstU32<128>>,
+        pub birth_date: Date,
+    }
+
+    /// Unversioned body of the v3 -> v4 migration. Use [`MigrateV3ToV4`] instead.
+    pub struct UncheckedMigrateToV4<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV4<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+            UserDetailsStorage::<T>::translate_values::<OldUserDetails, _>(|old| {
+                translated += 1;
+                // the bounds became configurable later; longer values only appear if the
+                // runtime configured them below the old constants
+                Some(UserDetails {
+                    fname: bounds::truncated(old.fname.into_inner()),
+                    lname: bounds::truncated(old.lname.into_inner()),
+                    address: bounds::truncated(old.address.into_inner()),
+                    birth_date: old.birth_date,
+                    status: UserStatus::Active,
+                })
+            });
+            log::info!(target: LOG_TARGET, "v4: marked {} users active", translated);
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            // not `count()`: records still waiting for the v3 re-keying are not counted yet
+            Ok(v3::entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let users_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v4: bad pre_upgrade state"))?;
+            let mut users_after = 0u32;
+            for details in UserDetailsStorage::<T>::iter_values() {
+                ensure!(details.status == UserStatus::Active, "v4: status not set");
+                users_after += 1;
+            }
+            ensure!(users_after == users_before, "v4: user records lost");
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 3 to 4.
+    pub type MigrateV3ToV4<T> = VersionedMigration<
+        3,
+        4,
+        UncheckedMigrateToV4<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// v4 -> v5: key `UsernameOf` with `Blake2_128Concat`, like its sibling `UsernameOwner`.
+///
+/// The old keys can not be reversed to accounts, so the entries are found through
+/// `UsernameOwner`, which holds the same pairs the other way round.
+pub mod v5 {
+    use super::*;
+
+    /// The reverse lookup under the v4 key layout.
+    #[frame_support::storage_alias]
+    type UsernameOf<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128,
+        <T as frame_system::Config>::AccountId,
+        Username,
+        OptionQuery,
+    >;
+
+    /// Unversioned body of the v4 -> v5 migration. Use [`MigrateV4ToV5`] instead.
+    pub struct UncheckedMigrateToV5<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV5<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let (mut owners, mut moved) = (0u64, 0u64);
+            for (username, who) in UsernameOwner::<T>::iter() {
+                owners += 1;
+                if let Some(name) = UsernameOf::<T>::take(&who) {
+                    if name != username {
+                        log::warn!(target: LOG_TARGET, "v5: {:?} holds a second username", who);
+                    }
+                    crate::UsernameOf::<T>::insert(&who, name);
+                    moved += 1;
+                }
+            }
+            log::info!(target: LOG_TARGET, "v5: re-keyed {} usernames", moved);
+
+            // per owner: read it and probe the old key; per move: remove and insert
+            T::DbWeight::get().reads_writes(owners.saturating_mul(2), moved.saturating_mul(2))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let held = UsernameOwner::<T>::iter_values()
+                .filter(|who| UsernameOf::<T>::contains_key(who))
+                .count() as u32;
+            Ok(held.encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let held_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("v5: bad pre_upgrade state"))?;
+            for (username, who) in UsernameOwner::<T>::iter() {
+                ensure!(!UsernameOf::<T>::contains_key(&who), "v5: old key left behind");
+                if let Some(name) = crate::UsernameOf::<T>::get(&who) {
+                    ensure!(name == username, "v5: username mismatch");
+                }
+            }
+            ensure!(
+                crate::UsernameOf::<T>::iter_keys().count() as u32 == held_before,
+                "v5: usernames lost"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate from storage version 4 to 5.
+    pub type MigrateV4ToV5<T> = VersionedMigration<
+        4,
+        5,
+        UncheckedMigrateToV5<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Fit stored records into lowered `MaxNameLen`/`MaxAddressLen` bounds.
+///
+/// `BoundedVec` encodes like `Vec`, so raising a bound needs no migration. Records longer than
+/// a lowered bound would no longer decode; add [`bounds::TruncateToBounds`] to the runtime's
+/// `Migrations` in the release that lowers it. It is not versioned and leaves records that
+/// already fit unchanged, so it can stay in the tuple.
+pub mod bounds {
+    use super::*;
+
+    /// `UserDetails` with its bounds erased.
+    #[derive(Encode, Decode)]
+    pub struct UnboundedUserDetails {
+        pub fname: Vec<u8>,
+        pub lname: Vec<u8>,
+        pub address: Vec<u8>,
+        pub birth_date: Date,
+        pub status: UserStatus,
+    }
+
+    /// `bytes` cut to at most `S` bytes, at a character boundary of valid UTF-8.
+    pub fn truncated<S: Get<u32>>(mut bytes: Vec<u8>) -> BoundedVec<u8, S> {
+        let mut len = bytes.len().min(S::get() as usize);
+        if let Ok(text) = core::str::from_utf8(&bytes) {
+            while !text.is_char_boundary(len) {
+                len -= 1;
+            }
+        }
+        bytes.truncate(len);
+        BoundedVec::truncate_from(bytes)
+    }
+
+    /// Truncate the text fields of every record to the configured bounds.
+    pub struct TruncateToBounds<T>(core::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for TruncateToBounds<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let (mut records, mut shortened) = (0u64, 0u64);
+            v3::translate_records::<T, UnboundedUserDetails, _>(|who, old| {
+                records += 1;
+                let size = old.encoded_size();
+                let details = UserDetails {
+                    fname: truncated::<T::MaxNameLen>(old.fname),
+                    lname: truncated::<T::MaxNameLen>(old.lname),
+                    address: truncated::<T::MaxAddressLen>(old.address),
+                    birth_date: old.birth_date,
+                    status: old.status,
+                };
+                if details.encoded_size() < size {
+                    shortened += 1;
+                    match who {
+                        Some(who) => log::warn!(target: LOG_TARGET, "bounds: truncated the record of {:?}", who),
+                        None => log::warn!(target: LOG_TARGET, "bounds: truncated a record not re-keyed yet"),
+                    }
+                }
+                details
+            });
+            log::info!(target: LOG_TARGET, "bounds: truncated {} of {} users", shortened, records);
+
+            T::DbWeight::get().reads_writes(records, records)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            // not `count()`: records still waiting for the v3 re-keying are not counted yet
+            Ok(v3::entries::<T>().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let users_before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("bounds: bad pre_upgrade state"))?;
+            ensure!(
+                UserDetailsStorage::<T>::iter_values().count() as u32 == users_before,
+                "bounds: user records lost"
+            );
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,1471 @@
+use crate::{
+	migrations, mock::*, Date, Error, Event, HoldReason, UserApi, UserDetailsOf,
+	UserDetailsStorage, UserDetailsUpdate, UserDetailsViewFunction, UserField, Username, UsernameOf,
+	UsernameOwner, UserStatus, ActiveRecoveries, LinkedAccounts, PendingLinks, PrimaryOf,
+	RecoveryConfigs, Registrars, MAX_PAGE_LEN,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::{storage_prefix, unhashed},
+	traits::{fungible::InspectHold, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	view_functions::{DispatchViewFunction, ViewFunction},
+	Blake2_128, StorageHasher,
+};
+
+fn s(len: usize, c: char) -> String {
+	c.to_string().repeat(len)
+}
+
+/// 30 years old on `mock::NOW`.
+const BIRTH_DATE: Date = Date::new(1996, 3, 14);
+
+fn create(who: u64) {
+	assert_ok!(User::create_user(
+		RuntimeOrigin::signed(who),
+		"Nimal".into(),
+		"Perera".into(),
+		"12 Galle Road, Colombo".into(),
+		BIRTH_DATE,
+	));
+}
+
+fn username(raw: &str) -> Username {
+	raw.as_bytes().to_vec().try_into().unwrap()
+}
+
+fn held(who: u64) -> u64 {
+	Balances::balance_on_hold(&HoldReason::UserDeposit.into(), &who)
+}
+
+#[test]
+fn create_user_stores_details_and_holds_deposit() {
+	new_test_ext().execute_with(|| {
+		create(1);
+
+		let details = UserDetailsStorage::<Test>::get(1).unwrap();
+		assert_eq!(details.fname.to_vec(), b"Nimal".to_vec());
+		assert_eq!(details.lname.to_vec(), b"Perera".to_vec());
+		assert_eq!(details.address.to_vec(), b"12 Galle Road, Colombo".to_vec());
+		assert_eq!(details.birth_date, BIRTH_DATE);
+		assert_eq!(details.status, UserStatus::Pending);
+
+		let deposit = User::deposit_for(&details);
+		assert_eq!(deposit, DepositBase::get() + DepositPerByte::get() * 41);
+		assert_eq!(held(1), deposit);
+		System::assert_last_event(Event::UserDataCreated { account: 1, deposit }.into());
+	});
+}
+
+#[test]
+fn create_user_rejects_long_fields() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			User::create_user(RuntimeOrigin::signed(1), s(65, 'f'), "l".into(), "a".into(), BIRTH_DATE),
+			Error::<Test>::FirstNameTooLong
+		);
+		assert_noop!(
+			User::create_user(RuntimeOrigin::signed(1), "f".into(), s(65, 'l'), "a".into(), BIRTH_DATE),
+			Error::<Test>::LastNameTooLong
+		);
+		assert_noop!(
+			User::create_user(RuntimeOrigin::signed(1), "f".into(), "l".into(), s(129, 'a'), BIRTH_DATE),
+			Error::<Test>::AddressTooLong
+		);
+
+		// bounds are inclusive
+		assert_ok!(User::create_user(
+			RuntimeOrigin::signed(1),
+			s(64, 'f'),
+			s(64, 'l'),
+			s(128, 'a'),
+			BIRTH_DATE,
+		));
+	});
+}
+
+#[test]
+fn text_fields_are_validated_and_trimmed() {
+	new_test_ext().execute_with(|| {
+		let create_with = |fname: &str, lname: &str, address: &str| {
+			User::create_user(
+				RuntimeOrigin::signed(1),
+				fname.into(),
+				lname.into(),
+				address.into(),
+				BIRTH_DATE,
+			)
+		};
+		assert_noop!(create_with("Nimal2", "Perera", "Colombo"), Error::<Test>::InvalidFirstName);
+		assert_noop!(create_with("Nimal", "Per\u{7}era", "Colombo"), Error::<Test>::InvalidLastName);
+		assert_noop!(create_with("Nimal", "Perera", "12 Galle\nRoad"), Error::<Test>::InvalidAddress);
+		// the bound applies after trimming
+		assert_ok!(create_with(&format!("  {}  ", s(64, 'a')), "Perera", "Colombo"));
+		assert_eq!(UserDetailsStorage::<Test>::get(1).unwrap().fname.to_vec(), s(64, 'a').into_bytes());
+
+		// Sinhala names use combining signs and the al-lakuna
+		assert_ok!(User::update_user(
+			RuntimeOrigin::signed(1),
+			UserDetailsUpdate {
+				fname: Some(" නිමල් ".into()),
+				lname: Some("D'Silva-Perera".into()),
+				address: Some("No. 12/3, Galle Road".into()),
+				..Default::default()
+			}
+		));
+		let details = UserDetailsStorage::<Test>::get(1).unwrap();
+		assert_eq!(details.fname.to_vec(), "නිමල්".as_bytes().to_vec());
+		assert_eq!(details.address.to_vec(), b"No. 12/3, Galle Road".to_vec());
+
+		assert_noop!(
+			User::update_user(
+				RuntimeOrigin::signed(1),
+				UserDetailsUpdate { lname: Some("<script>".into()), ..Default::default() }
+			),
+			Error::<Test>::InvalidLastName
+		);
+	});
+}
+
+#[test]
+fn create_user_rejects_invalid_birth_dates() {
+	new_test_ext().execute_with(|| {
+		let create_born = |birth_date| {
+			User::create_user(
+				RuntimeOrigin::signed(1),
+				"f".into(),
+				"l".into(),
+				"a".into(),
+				birth_date,
+			)
+		};
+
+		for bad in [
+			Date::new(1996, 2, 30),
+			Date::new(1995, 2, 29),
+			Date::new(1996, 13, 1),
+			Date::new(1996, 4, 0),
+			Date::new(0, 1, 1),
+			// the day after `mock::NOW`
+			Date::new(2026, 10, 18),
+		] {
+			assert_noop!(create_born(bad), Error::<Test>::InvalidBirthDate);
+		}
+
+		// leap day, and someone born today
+		assert_ok!(create_born(Date::new(1996, 2, 29)));
+		assert_ok!(User::update_user(
+			RuntimeOrigin::signed(1),
+			UserDetailsUpdate { birth_date: Some(Date::new(2026, 10, 17)), ..Default::default() },
+		));
+		assert_noop!(
+			User::update_user(
+				RuntimeOrigin::signed(1),
+				UserDetailsUpdate {
+					birth_date: Some(Date::new(2000, 6, 31)),
+					..Default::default()
+				},
+			),
+			Error::<Test>::InvalidBirthDate
+		);
+	});
+}
+
+#[test]
+fn dates_parse_and_format_as_iso() {
+	assert_eq!(Date::parse(b"1996-02-29"), Some(Date::new(1996, 2, 29)));
+	assert_eq!(Date::new(7, 3, 1).to_string(), "0007-03-01");
+
+	for bad in [
+		&b"2023-99-99"[..],
+		b"1995-02-29",
+		b"0000-01-01",
+		b"1996-3-14",
+		b"1996/03/14",
+		b"1996-03-14 ",
+		b"+996-03-14",
+		b"",
+	] {
+		assert_eq!(Date::parse(bad), None);
+	}
+}
+
+#[test]
+fn age_follows_the_clock() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(<User as UserApi<u64>>::age_of(&1), None);
+		create(1);
+		assert_eq!(<User as UserApi<u64>>::age_of(&1), Some(30));
+
+		// 2027-03-13, the day before the 31st birthday
+		Timestamp::set_timestamp(1_804_896_000_000);
+		assert_eq!(<User as UserApi<u64>>::age_of(&1), Some(30));
+
+		// 2027-03-14
+		Timestamp::set_timestamp(1_804_982_400_000);
+		assert_eq!(<User as UserApi<u64>>::age_of(&1), Some(31));
+	});
+}
+
+#[test]
+fn create_user_rejects_duplicate() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		let deposit = held(1);
+
+		assert_noop!(
+			User::create_user(RuntimeOrigin::signed(1), "f".into(), "l".into(), "a".into(), BIRTH_DATE),
+			Error::<Test>::DuplicateUser
+		);
+		assert_noop!(
+			User::force_create_user(
+				RuntimeOrigin::signed(Registrar::get()),
+				1,
+				"f".into(),
+				"l".into(),
+				"a".into(),
+				BIRTH_DATE,
+			),
+			Error::<Test>::DuplicateUser
+		);
+
+		assert_eq!(UserDetailsStorage::<Test>::get(1).unwrap().fname.to_vec(), b"Nimal".to_vec());
+		assert_eq!(held(1), deposit);
+	});
+}
+
+#[test]
+fn create_user_requires_funds_for_deposit() {
+	new_test_ext().execute_with(|| {
+		assert!(User::create_user(
+			RuntimeOrigin::signed(9),
+			"f".into(),
+			"l".into(),
+			"a".into(),
+			BIRTH_DATE
+		)
+		.is_err());
+		assert!(!UserDetailsStorage::<Test>::contains_key(9));
+	});
+}
+
+#[test]
+fn view_functions_read_without_events() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(User::user_details(1), None);
+		assert_eq!(User::user_age(1), None);
+		create(1);
+		assert_ok!(User::claim_username(RuntimeOrigin::signed(1), "nimal".into()));
+		System::reset_events();
+
+		let details = User::user_details(1).unwrap();
+		assert_eq!(details.fname.to_vec(), b"Nimal".to_vec());
+		assert_eq!(details.birth_date, BIRTH_DATE);
+		assert_eq!(User::user_age(1), Some(30));
+		assert_eq!(User::username_of(1), Some(username("nimal")));
+		assert!(System::events().is_empty());
+	});
+}
+
+#[test]
+fn view_functions_dispatch_through_the_runtime() {
+	new_test_ext().execute_with(|| {
+		create(1);
+
+		let mut output = Vec::new();
+		assert_ok!(RuntimeViewFunction::dispatch_view_function(
+			&UserDetailsViewFunction::<Test>::id(),
+			&mut &1u64.encode()[..],
+			&mut output,
+		));
+		let details = Option::<UserDetailsOf<Test>>::decode(&mut &output[..]).unwrap();
+		assert_eq!(details, UserDetailsStorage::<Test>::get(1));
+	});
+}
+
+#[test]
+#[allow(deprecated)]
+fn get_my_user_details_is_a_failing_stub() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		assert_noop!(User::get_my_user_details(RuntimeOrigin::signed(1)), Error::<Test>::Deprecated);
+		assert_eq!(
+			RuntimeCall::User(crate::Call::get_my_user_details {}).encode()[1],
+			2,
+			"keeps its call index"
+		);
+	});
+}
+
+#[test]
+fn update_user_patches_supplied_fields_and_adjusts_deposit() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		let before = held(1);
+
+		assert_ok!(User::update_user(
+			RuntimeOrigin::signed(1),
+			UserDetailsUpdate { address: Some("Kandy".into()), ..Default::default() },
+		));
+
+		let details = UserDetailsStorage::<Test>::get(1).unwrap();
+		assert_eq!(details.fname.to_vec(), b"Nimal".to_vec());
+		assert_eq!(details.lname.to_vec(), b"Perera".to_vec());
+		assert_eq!(details.address.to_vec(), b"Kandy".to_vec());
+		assert_eq!(details.birth_date, BIRTH_DATE);
+
+		// shorter record, smaller deposit
+		let deposit = User::deposit_for(&details);
+		assert!(deposit < before);
+		assert_eq!(held(1), deposit);
+		System::assert_last_event(
+			Event::UserDataUpdated { account: 1, deposit, changed: vec![UserField::Address] }.into(),
+		);
+	});
+}
+
+#[test]
+fn update_user_reports_only_changed_fields() {
+	new_test_ext().execute_with(|| {
+		create(1);
+
+		assert_ok!(User::update_user(
+			RuntimeOrigin::signed(1),
+			UserDetailsUpdate {
+				fname: Some("Nimal".into()),
+				lname: Some("Silva".into()),
+				address: None,
+				birth_date: Some(Date::new(1995, 3, 14)),
+			},
+		));
+
+		let deposit = held(1);
+		System::assert_last_event(
+			Event::UserDataUpdated {
+				account: 1,
+				deposit,
+				changed: vec![UserField::LastName, UserField::BirthDate],
+			}
+			.into(),
+		);
+
+		// an empty patch is accepted and changes nothing
+		assert_ok!(User::update_user(RuntimeOrigin::signed(1), Default::default()));
+		System::assert_last_event(
+			Event::UserDataUpdated { account: 1, deposit, changed: vec![] }.into(),
+		);
+	});
+}
+
+#[test]
+fn update_user_fails_without_record() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			User::update_user(RuntimeOrigin::signed(1), Default::default()),
+			Error::<Test>::NoUserDataFound
+		);
+	});
+}
+
+#[test]
+fn update_user_rejects_long_fields() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		assert_noop!(
+			User::update_user(
+				RuntimeOrigin::signed(1),
+				UserDetailsUpdate { fname: Some(s(65, 'f')), ..Default::default() },
+			),
+			Error::<Test>::FirstNameTooLong
+		);
+		assert_noop!(
+			User::update_user(
+				RuntimeOrigin::signed(1),
+				UserDetailsUpdate { lname: Some(s(65, 'l')), ..Default::default() },
+			),
+			Error::<Test>::LastNameTooLong
+		);
+		assert_noop!(
+			User::update_user(
+				RuntimeOrigin::signed(1),
+				UserDetailsUpdate { address: Some(s(129, 'a')), ..Default::default() },
+			),
+			Error::<Test>::AddressTooLong
+		);
+	});
+}
+
+#[test]
+fn remove_user_deletes_record_and_releases_deposit() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		let deposit = held(1);
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+
+		assert!(!UserDetailsStorage::<Test>::contains_key(1));
+		assert_eq!(held(1), 0);
+		assert_eq!(Balances::free_balance(1), 1_000);
+		System::assert_last_event(Event::UserDataRemoved { account: 1, released: deposit }.into());
+	});
+}
+
+#[test]
+fn remove_user_fails_without_record() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(User::remove_user(RuntimeOrigin::signed(1)), Error::<Test>::NoUserDataFound);
+	});
+}
+
+#[test]
+fn calls_require_signed_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			User::create_user(RuntimeOrigin::none(), "f".into(), "l".into(), "a".into(), BIRTH_DATE),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(User::remove_user(RuntimeOrigin::root()), sp_runtime::DispatchError::BadOrigin);
+	});
+}
+
+#[test]
+fn user_api_reports_registered_users() {
+	new_test_ext().execute_with(|| {
+		assert!(!<User as UserApi<u64>>::user_exists(&1));
+		assert_eq!(<User as UserApi<u64>>::full_name(&1), None);
+
+		create(1);
+
+		assert!(<User as UserApi<u64>>::user_exists(&1));
+		assert!(!<User as UserApi<u64>>::user_exists(&2));
+		assert_eq!(
+			<User as UserApi<u64>>::full_name(&1),
+			Some((b"Nimal".to_vec(), b"Perera".to_vec()))
+		);
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+		assert!(!<User as UserApi<u64>>::user_exists(&1));
+	});
+}
+
+#[test]
+fn force_create_user_enrolls_account_without_deposit() {
+	new_test_ext().execute_with(|| {
+		// account 9 has no funds at all
+		assert_ok!(User::force_create_user(
+			RuntimeOrigin::signed(Registrar::get()),
+			9,
+			"Sunil".into(),
+			"Fernando".into(),
+			"Matara".into(),
+			Date::new(1981, 6, 1),
+		));
+
+		let details = UserDetailsStorage::<Test>::get(9).unwrap();
+		assert_eq!(details.fname.to_vec(), b"Sunil".to_vec());
+		assert_eq!(held(9), 0);
+		System::assert_last_event(
+			Event::UserDataForceCreated { registrar: Registrar::get(), account: 9 }.into(),
+		);
+	});
+}
+
+#[test]
+fn force_update_user_keeps_existing_deposit() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		let deposit = held(1);
+
+		assert_ok!(User::force_update_user(
+			RuntimeOrigin::signed(Registrar::get()),
+			1,
+			UserDetailsUpdate { fname: Some("N".into()), ..Default::default() },
+		));
+
+		assert_eq!(UserDetailsStorage::<Test>::get(1).unwrap().fname.to_vec(), b"N".to_vec());
+		assert_eq!(held(1), deposit);
+		System::assert_last_event(
+			Event::UserDataForceUpdated {
+				registrar: Registrar::get(),
+				account: 1,
+				changed: vec![UserField::FirstName],
+			}
+			.into(),
+		);
+
+		assert_noop!(
+			User::force_update_user(RuntimeOrigin::signed(Registrar::get()), 2, Default::default()),
+			Error::<Test>::NoUserDataFound
+		);
+		assert_noop!(
+			User::force_update_user(
+				RuntimeOrigin::signed(Registrar::get()),
+				1,
+				UserDetailsUpdate { fname: Some(s(65, 'f')), ..Default::default() },
+			),
+			Error::<Test>::FirstNameTooLong
+		);
+	});
+}
+
+#[test]
+fn force_remove_user_releases_owner_deposit() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		let deposit = held(1);
+
+		assert_ok!(User::force_remove_user(RuntimeOrigin::signed(Registrar::get()), 1));
+
+		assert!(!UserDetailsStorage::<Test>::contains_key(1));
+		assert_eq!(held(1), 0);
+		System::assert_last_event(
+			Event::UserDataForceRemoved { registrar: Registrar::get(), account: 1, released: deposit }
+				.into(),
+		);
+
+		assert_noop!(
+			User::force_remove_user(RuntimeOrigin::signed(Registrar::get()), 1),
+			Error::<Test>::NoUserDataFound
+		);
+	});
+}
+
+#[test]
+fn force_calls_require_registrar_origin() {
+	new_test_ext().execute_with(|| {
+		create(1);
+
+		assert_noop!(
+			User::force_create_user(
+				RuntimeOrigin::signed(1),
+				2,
+				"f".into(),
+				"l".into(),
+				"a".into(),
+				BIRTH_DATE,
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			User::force_update_user(RuntimeOrigin::root(), 1, Default::default()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			User::force_remove_user(RuntimeOrigin::signed(2), 1),
+			sp_runtime::DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn claim_username_normalises_and_indexes_both_ways() {
+	new_test_ext().execute_with(|| {
+		create(1);
+
+		assert_ok!(User::claim_username(RuntimeOrigin::signed(1), "Nimal_P".into()));
+
+		assert_eq!(UsernameOwner::<Test>::get(username("nimal_p")), Some(1));
+		assert_eq!(UsernameOf::<Test>::get(1), Some(username("nimal_p")));
+		assert_eq!(<User as UserApi<u64>>::account_by_username(b"NIMAL_P"), Some(1));
+		assert_eq!(<User as UserApi<u64>>::account_by_username(b"nimal"), None);
+		System::assert_last_event(
+			Event::UsernameClaimed { account: 1, username: username("nimal_p") }.into(),
+		);
+	});
+}
+
+#[test]
+fn claim_username_enforces_format_and_uniqueness() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		create(2);
+
+		assert_noop!(
+			User::claim_username(RuntimeOrigin::signed(1), s(33, 'n')),
+			Error::<Test>::UsernameTooLong
+		);
+		for bad in ["ab", "nimal perera", "nimal-p", "nimäl"] {
+			assert_noop!(
+				User::claim_username(RuntimeOrigin::signed(1), bad.into()),
+				Error::<Test>::InvalidUsername
+			);
+		}
+		assert_ok!(User::claim_username(RuntimeOrigin::signed(1), s(32, 'n')));
+
+		// case-insensitive clash
+		assert_noop!(
+			User::claim_username(RuntimeOrigin::signed(2), s(32, 'N')),
+			Error::<Test>::UsernameTaken
+		);
+		// one handle per account
+		assert_noop!(
+			User::claim_username(RuntimeOrigin::signed(1), "other".into()),
+			Error::<Test>::UsernameAlreadySet
+		);
+		// only registered users
+		assert_noop!(
+			User::claim_username(RuntimeOrigin::signed(3), "sunil".into()),
+			Error::<Test>::NoUserDataFound
+		);
+	});
+}
+
+#[test]
+fn release_and_transfer_username() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		create(2);
+		assert_noop!(User::release_username(RuntimeOrigin::signed(1)), Error::<Test>::NoUsername);
+		assert_ok!(User::claim_username(RuntimeOrigin::signed(1), "nimal".into()));
+
+		// recipients need a record and no handle of their own
+		assert_noop!(
+			User::transfer_username(RuntimeOrigin::signed(1), 3),
+			Error::<Test>::NoUserDataFound
+		);
+		assert_ok!(User::transfer_username(RuntimeOrigin::signed(1), 2));
+		assert_eq!(<User as UserApi<u64>>::account_by_username(b"nimal"), Some(2));
+		assert_eq!(UsernameOf::<Test>::get(1), None);
+		System::assert_last_event(
+			Event::UsernameTransferred { from: 1, to: 2, username: username("nimal") }.into(),
+		);
+		assert_noop!(
+			User::transfer_username(RuntimeOrigin::signed(1), 2),
+			Error::<Test>::UsernameAlreadySet
+		);
+
+		assert_ok!(User::release_username(RuntimeOrigin::signed(2)));
+		assert_eq!(<User as UserApi<u64>>::account_by_username(b"nimal"), None);
+		assert_eq!(UsernameOf::<Test>::get(2), None);
+		assert_ok!(User::claim_username(RuntimeOrigin::signed(1), "nimal".into()));
+	});
+}
+
+#[test]
+fn removing_user_releases_username() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		assert_ok!(User::claim_username(RuntimeOrigin::signed(1), "nimal".into()));
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+
+		assert_eq!(<User as UserApi<u64>>::account_by_username(b"nimal"), None);
+		assert_eq!(UsernameOf::<Test>::get(1), None);
+		System::assert_has_event(
+			Event::UsernameReleased { account: 1, username: username("nimal") }.into(),
+		);
+	});
+}
+
+#[test]
+fn registrars_are_root_controlled_and_pass_registrar_origin() {
+	new_test_ext().execute_with(|| {
+		let enrol = |registrar: u64, who: u64| {
+			User::force_create_user(
+				RuntimeOrigin::signed(registrar),
+				who,
+				"f".into(),
+				"l".into(),
+				"a".into(),
+				BIRTH_DATE,
+			)
+		};
+		assert_noop!(User::add_registrar(RuntimeOrigin::signed(6), 6), sp_runtime::DispatchError::BadOrigin);
+		assert_noop!(enrol(6, 9), sp_runtime::DispatchError::BadOrigin);
+		assert!(!<User as UserApi<u64>>::is_registrar(&6));
+
+		assert_ok!(User::add_registrar(RuntimeOrigin::root(), 6));
+		assert_ok!(User::add_registrar(RuntimeOrigin::root(), 5));
+		assert_eq!(Registrars::<Test>::get().into_inner(), vec![5, 6]);
+		System::assert_last_event(Event::RegistrarAdded { registrar: 5 }.into());
+		assert_noop!(User::add_registrar(RuntimeOrigin::root(), 6), Error::<Test>::AlreadyRegistrar);
+		assert_noop!(User::add_registrar(RuntimeOrigin::root(), 7), Error::<Test>::TooManyRegistrars);
+
+		assert!(<User as UserApi<u64>>::is_registrar(&6));
+		assert_ok!(enrol(6, 9));
+		System::assert_last_event(Event::UserDataForceCreated { registrar: 6, account: 9 }.into());
+
+		assert_ok!(User::remove_registrar(RuntimeOrigin::root(), 6));
+		System::assert_last_event(Event::RegistrarRemoved { registrar: 6 }.into());
+		assert_noop!(User::remove_registrar(RuntimeOrigin::root(), 6), Error::<Test>::NotRegistrar);
+		assert_noop!(enrol(6, 8), sp_runtime::DispatchError::BadOrigin);
+		// records it enrolled are kept
+		assert!(UserDetailsStorage::<Test>::contains_key(9));
+	});
+}
+
+#[test]
+fn reserved_usernames_are_root_controlled() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		assert_noop!(
+			User::reserve_username(RuntimeOrigin::signed(1), "admin".into()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(User::reserve_username(RuntimeOrigin::root(), "Admin".into()));
+
+		assert_noop!(
+			User::claim_username(RuntimeOrigin::signed(1), "ADMIN".into()),
+			Error::<Test>::UsernameReserved
+		);
+
+		assert_ok!(User::unreserve_username(RuntimeOrigin::root(), "admin".into()));
+		assert_noop!(
+			User::unreserve_username(RuntimeOrigin::root(), "admin".into()),
+			Error::<Test>::UsernameNotReserved
+		);
+		assert_ok!(User::claim_username(RuntimeOrigin::signed(1), "admin".into()));
+	});
+}
+
+#[test]
+fn users_are_counted_and_paged() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(UserDetailsStorage::<Test>::count(), 0);
+		assert!(User::users_page(None, 10).is_empty());
+
+		for who in 1..=3 {
+			create(who);
+		}
+		assert_eq!(UserDetailsStorage::<Test>::count(), 3);
+
+		let first = User::users_page(None, 2);
+		assert_eq!(first.len(), 2);
+		let rest = User::users_page(first.last().copied(), 2);
+		assert_eq!(rest.len(), 1);
+
+		let mut all = [first, rest].concat();
+		all.sort();
+		assert_eq!(all, vec![1, 2, 3]);
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(2)));
+		assert_eq!(UserDetailsStorage::<Test>::count(), 2);
+		assert_eq!(User::users_page(None, 0), Vec::<u64>::new());
+	});
+}
+
+#[test]
+fn users_page_is_capped() {
+	new_test_ext().execute_with(|| {
+		for who in 1000..1000 + MAX_PAGE_LEN as u64 + 1 {
+			assert_ok!(User::force_create_user(
+				RuntimeOrigin::signed(Registrar::get()),
+				who,
+				"f".into(),
+				"l".into(),
+				"a".into(),
+				BIRTH_DATE,
+			));
+		}
+		assert_eq!(User::users_page(None, u32::MAX).len() as u32, MAX_PAGE_LEN);
+	});
+}
+
+/// User 1 names guardians 2, 3 and 4 (not registered users themselves), two of which must
+/// vouch.
+fn configure_recovery() {
+	create(1);
+	assert_ok!(User::set_recovery_config(RuntimeOrigin::signed(1), vec![4, 3, 2], 2));
+}
+
+fn vouch(guardian: u64) -> sp_runtime::DispatchResult {
+	User::vouch_recovery(RuntimeOrigin::signed(guardian), 1, 7)
+}
+
+#[test]
+fn set_recovery_config_validates_guardians() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			User::set_recovery_config(RuntimeOrigin::signed(1), vec![2], 1),
+			Error::<Test>::NoUserDataFound
+		);
+		create(1);
+
+		for bad in [vec![], vec![2, 2], vec![1, 2], vec![2, 3, 4, 5]] {
+			assert_noop!(
+				User::set_recovery_config(RuntimeOrigin::signed(1), bad, 1),
+				Error::<Test>::InvalidGuardians
+			);
+		}
+		for bad in [0, 3] {
+			assert_noop!(
+				User::set_recovery_config(RuntimeOrigin::signed(1), vec![2, 3], bad),
+				Error::<Test>::InvalidThreshold
+			);
+		}
+
+		assert_ok!(User::set_recovery_config(RuntimeOrigin::signed(1), vec![3, 2], 2));
+		let config = RecoveryConfigs::<Test>::get(1).unwrap();
+		assert_eq!(config.guardians.to_vec(), vec![2, 3]);
+		System::assert_last_event(
+			Event::RecoveryConfigured { account: 1, guardians: 2, threshold: 2 }.into(),
+		);
+
+		assert_ok!(User::remove_recovery_config(RuntimeOrigin::signed(1)));
+		assert_noop!(
+			User::remove_recovery_config(RuntimeOrigin::signed(1)),
+			Error::<Test>::NoRecoveryConfig
+		);
+	});
+}
+
+#[test]
+fn recovery_moves_records_and_deposit_after_delay() {
+	new_test_ext().execute_with(|| {
+		configure_recovery();
+		assert_ok!(User::claim_username(RuntimeOrigin::signed(1), "nimal".into()));
+		let details = UserDetailsStorage::<Test>::get(1).unwrap();
+		let deposit = held(1);
+
+		assert_noop!(vouch(5), Error::<Test>::NotGuardian);
+		assert_ok!(vouch(2));
+		assert_noop!(vouch(2), Error::<Test>::AlreadyVouched);
+		assert_noop!(
+			User::vouch_recovery(RuntimeOrigin::signed(3), 1, 1),
+			Error::<Test>::RecoveryTargetMismatch
+		);
+		// one vouch is not enough
+		assert_noop!(
+			User::claim_recovery(RuntimeOrigin::signed(5), 1, 7),
+			Error::<Test>::RecoveryNotClaimable
+		);
+
+		assert_ok!(vouch(3));
+		System::assert_last_event(
+			Event::RecoveryApproved { lost: 1, new_account: 7, claimable_at: 11 }.into(),
+		);
+		// the delay has not passed
+		System::set_block_number(10);
+		assert_noop!(
+			User::claim_recovery(RuntimeOrigin::signed(5), 1, 7),
+			Error::<Test>::RecoveryNotClaimable
+		);
+
+		System::set_block_number(11);
+		assert_ok!(User::claim_recovery(RuntimeOrigin::signed(5), 1, 7));
+
+		assert_eq!(UserDetailsStorage::<Test>::get(7), Some(details));
+		assert!(!UserDetailsStorage::<Test>::contains_key(1));
+		assert_eq!(UserDetailsStorage::<Test>::count(), 1);
+		assert_eq!(<User as UserApi<u64>>::account_by_username(b"nimal"), Some(7));
+		assert_eq!(UsernameOf::<Test>::get(1), None);
+		assert_eq!(held(1), 0);
+		assert_eq!(held(7), deposit);
+		assert_eq!(ActiveRecoveries::<Test>::iter_prefix(1).count(), 0);
+		assert!(!RecoveryConfigs::<Test>::contains_key(1));
+		System::assert_last_event(Event::UserRecovered { lost: 1, new_account: 7 }.into());
+
+		// the new owner manages the record as usual
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(7)));
+		assert_eq!(held(7), 0);
+	});
+}
+
+#[test]
+fn rogue_vouch_does_not_block_recovery() {
+	new_test_ext().execute_with(|| {
+		configure_recovery();
+
+		// a rogue guardian starts a recovery to its own account first
+		assert_ok!(User::vouch_recovery(RuntimeOrigin::signed(4), 1, 8));
+		// an honest guardian mistypes the new account, then corrects it
+		assert_ok!(User::vouch_recovery(RuntimeOrigin::signed(2), 1, 9));
+		assert_ok!(vouch(2));
+		assert!(!ActiveRecoveries::<Test>::contains_key(1, 9));
+
+		assert_ok!(vouch(3));
+		System::assert_last_event(
+			Event::RecoveryApproved { lost: 1, new_account: 7, claimable_at: 11 }.into(),
+		);
+		assert_eq!(ActiveRecoveries::<Test>::get(1, 8).unwrap().vouchers.to_vec(), vec![4]);
+
+		System::set_block_number(11);
+		assert_noop!(
+			User::claim_recovery(RuntimeOrigin::signed(5), 1, 8),
+			Error::<Test>::RecoveryNotClaimable
+		);
+		assert_ok!(User::claim_recovery(RuntimeOrigin::signed(5), 1, 7));
+
+		assert!(UserDetailsStorage::<Test>::contains_key(7));
+		// the rival recovery ends with the claim
+		assert_eq!(ActiveRecoveries::<Test>::iter_prefix(1).count(), 0);
+	});
+}
+
+#[test]
+fn withdrawn_vouch_stops_approved_recovery() {
+	new_test_ext().execute_with(|| {
+		configure_recovery();
+		assert_ok!(vouch(2));
+		assert_ok!(vouch(3));
+
+		// guardian 3 moves to another account, leaving the first below the threshold
+		assert_ok!(User::vouch_recovery(RuntimeOrigin::signed(3), 1, 8));
+		assert_eq!(ActiveRecoveries::<Test>::get(1, 7).unwrap().claimable_at, None);
+
+		System::set_block_number(11);
+		assert_noop!(
+			User::claim_recovery(RuntimeOrigin::signed(5), 1, 7),
+			Error::<Test>::RecoveryNotClaimable
+		);
+		// the threshold is reached again with the third guardian
+		assert_ok!(vouch(4));
+		System::set_block_number(21);
+		assert_ok!(User::claim_recovery(RuntimeOrigin::signed(5), 1, 7));
+	});
+}
+
+#[test]
+fn owner_can_cancel_recovery() {
+	new_test_ext().execute_with(|| {
+		configure_recovery();
+		assert_ok!(vouch(2));
+		assert_ok!(vouch(3));
+		assert_ok!(User::vouch_recovery(RuntimeOrigin::signed(4), 1, 8));
+
+		// the configuration is frozen while the recovery runs
+		assert_noop!(
+			User::set_recovery_config(RuntimeOrigin::signed(1), vec![2], 1),
+			Error::<Test>::RecoveryInProgress
+		);
+		assert_noop!(
+			User::cancel_recovery(RuntimeOrigin::signed(2)),
+			Error::<Test>::NoActiveRecovery
+		);
+
+		assert_ok!(User::cancel_recovery(RuntimeOrigin::signed(1)));
+		System::assert_last_event(Event::RecoveryCancelled { lost: 1 }.into());
+		// every recovery of the account ends
+		assert_eq!(ActiveRecoveries::<Test>::iter_prefix(1).count(), 0);
+
+		System::set_block_number(20);
+		assert_noop!(
+			User::claim_recovery(RuntimeOrigin::signed(5), 1, 7),
+			Error::<Test>::NoActiveRecovery
+		);
+		assert!(UserDetailsStorage::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn recovery_target_must_be_unregistered() {
+	new_test_ext().execute_with(|| {
+		configure_recovery();
+		create(2);
+		assert_noop!(
+			User::vouch_recovery(RuntimeOrigin::signed(3), 1, 2),
+			Error::<Test>::DuplicateUser
+		);
+
+		// the target registering during the delay blocks the claim
+		assert_ok!(vouch(3));
+		assert_ok!(vouch(4));
+		assert_ok!(User::force_create_user(
+			RuntimeOrigin::signed(Registrar::get()),
+			7,
+			"f".into(),
+			"l".into(),
+			"a".into(),
+			BIRTH_DATE,
+		));
+		System::set_block_number(11);
+		assert_noop!(
+			User::claim_recovery(RuntimeOrigin::signed(5), 1, 7),
+			Error::<Test>::DuplicateUser
+		);
+	});
+}
+
+#[test]
+fn removing_user_drops_recovery_state() {
+	new_test_ext().execute_with(|| {
+		configure_recovery();
+		assert_ok!(vouch(2));
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+
+		assert!(!RecoveryConfigs::<Test>::contains_key(1));
+		assert_eq!(ActiveRecoveries::<Test>::iter_prefix(1).count(), 0);
+	});
+}
+
+/// Link `secondary` to the record of `primary`, both sides signing.
+fn link(primary: u64, secondary: u64) {
+	assert_ok!(User::request_link(RuntimeOrigin::signed(secondary), primary));
+	assert_ok!(User::accept_link(RuntimeOrigin::signed(primary), secondary));
+}
+
+#[test]
+fn linking_needs_both_signatures() {
+	new_test_ext().execute_with(|| {
+		create(1);
+
+		assert_noop!(
+			User::accept_link(RuntimeOrigin::signed(1), 2),
+			Error::<Test>::NoPendingLink
+		);
+		assert_ok!(User::request_link(RuntimeOrigin::signed(2), 1));
+		System::assert_last_event(Event::LinkRequested { primary: 1, secondary: 2 }.into());
+		// a request alone does not link
+		assert!(!<User as UserApi<u64>>::user_exists(&2));
+		assert_noop!(
+			User::accept_link(RuntimeOrigin::signed(3), 2),
+			Error::<Test>::NoPendingLink
+		);
+
+		assert_ok!(User::accept_link(RuntimeOrigin::signed(1), 2));
+		System::assert_last_event(Event::AccountLinked { primary: 1, secondary: 2 }.into());
+		assert_eq!(PrimaryOf::<Test>::get(2), Some(1));
+		assert_eq!(LinkedAccounts::<Test>::get(1).unwrap().to_vec(), vec![2]);
+		assert!(!PendingLinks::<Test>::contains_key(2));
+	});
+}
+
+#[test]
+fn link_requests_are_validated() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		create(2);
+
+		assert_noop!(
+			User::request_link(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::CannotLinkSelf
+		);
+		assert_noop!(
+			User::request_link(RuntimeOrigin::signed(3), 4),
+			Error::<Test>::NoUserDataFound
+		);
+		// accounts with a record of their own can not become secondaries
+		assert_noop!(
+			User::request_link(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::DuplicateUser
+		);
+
+		link(1, 3);
+		assert_noop!(
+			User::request_link(RuntimeOrigin::signed(3), 2),
+			Error::<Test>::DuplicateUser
+		);
+		// nor can secondaries register
+		assert_noop!(
+			User::create_user(
+				RuntimeOrigin::signed(3),
+				"f".into(),
+				"l".into(),
+				"a".into(),
+				BIRTH_DATE
+			),
+			Error::<Test>::DuplicateUser
+		);
+	});
+}
+
+#[test]
+fn linked_accounts_are_bounded() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		link(1, 4);
+		link(1, 5);
+
+		assert_ok!(User::request_link(RuntimeOrigin::signed(6), 1));
+		assert_noop!(
+			User::accept_link(RuntimeOrigin::signed(1), 6),
+			Error::<Test>::TooManyLinkedAccounts
+		);
+	});
+}
+
+#[test]
+fn lookups_resolve_secondary_accounts() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		link(1, 4);
+
+		assert!(<User as UserApi<u64>>::user_exists(&4));
+		assert_eq!(<User as UserApi<u64>>::primary_account(&4), 1);
+		assert_eq!(<User as UserApi<u64>>::primary_account(&5), 5);
+		assert_eq!(<User as UserApi<u64>>::age_of(&4), Some(30));
+		assert_eq!(
+			<User as UserApi<u64>>::full_name(&4),
+			<User as UserApi<u64>>::full_name(&1)
+		);
+		assert_eq!(User::user_details(4), User::user_details(1));
+		assert_eq!(User::linked_accounts(4), vec![4]);
+		assert_eq!(User::linked_accounts(1), vec![4]);
+	});
+}
+
+#[test]
+fn either_side_can_unlink() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		link(1, 4);
+		link(1, 5);
+
+		assert_noop!(User::unlink(RuntimeOrigin::signed(4), 5), Error::<Test>::NotLinked);
+
+		assert_ok!(User::unlink(RuntimeOrigin::signed(4), 1));
+		System::assert_last_event(Event::AccountUnlinked { primary: 1, secondary: 4 }.into());
+		assert!(!<User as UserApi<u64>>::user_exists(&4));
+
+		assert_ok!(User::unlink(RuntimeOrigin::signed(1), 5));
+		assert!(!PrimaryOf::<Test>::contains_key(5));
+		assert!(!LinkedAccounts::<Test>::contains_key(1));
+
+		// a pending request is withdrawn the same way
+		assert_ok!(User::request_link(RuntimeOrigin::signed(4), 1));
+		assert_ok!(User::unlink(RuntimeOrigin::signed(4), 1));
+		assert!(!PendingLinks::<Test>::contains_key(4));
+	});
+}
+
+#[test]
+fn removing_user_unlinks_secondaries() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		link(1, 4);
+
+		assert_ok!(User::remove_user(RuntimeOrigin::signed(1)));
+
+		assert!(!PrimaryOf::<Test>::contains_key(4));
+		assert!(!LinkedAccounts::<Test>::contains_key(1));
+		System::assert_has_event(Event::AccountUnlinked { primary: 1, secondary: 4 }.into());
+		// the freed account can register on its own
+		assert_ok!(User::force_create_user(
+			RuntimeOrigin::signed(Registrar::get()),
+			4,
+			"f".into(),
+			"l".into(),
+			"a".into(),
+			BIRTH_DATE,
+		));
+	});
+}
+
+#[test]
+fn recovery_moves_linked_accounts() {
+	new_test_ext().execute_with(|| {
+		configure_recovery();
+		link(1, 5);
+		// a secondary is not a valid recovery target
+		assert_noop!(
+			User::vouch_recovery(RuntimeOrigin::signed(2), 1, 5),
+			Error::<Test>::DuplicateUser
+		);
+		assert_ok!(vouch(2));
+		assert_ok!(vouch(3));
+
+		System::set_block_number(11);
+		assert_ok!(User::claim_recovery(RuntimeOrigin::signed(5), 1, 7));
+
+		assert_eq!(PrimaryOf::<Test>::get(5), Some(7));
+		assert_eq!(LinkedAccounts::<Test>::get(7).unwrap().to_vec(), vec![5]);
+		assert!(!LinkedAccounts::<Test>::contains_key(1));
+	});
+}
+
+fn set_status(who: u64, status: UserStatus) -> sp_runtime::DispatchResult {
+	User::set_user_status(RuntimeOrigin::signed(Registrar::get()), who, status)
+}
+
+#[test]
+fn new_records_start_pending_or_active() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		assert_ok!(User::force_create_user(
+			RuntimeOrigin::signed(Registrar::get()),
+			2,
+			"f".into(),
+			"l".into(),
+			"a".into(),
+			BIRTH_DATE,
+		));
+
+		assert_eq!(User::user_status(1), Some(UserStatus::Pending));
+		assert!(!<User as UserApi<u64>>::is_active(&1));
+		assert_eq!(User::user_status(2), Some(UserStatus::Active));
+		assert!(<User as UserApi<u64>>::is_active(&2));
+		assert_eq!(User::user_status(3), None);
+	});
+}
+
+#[test]
+fn registrar_moves_status() {
+	new_test_ext().execute_with(|| {
+		create(1);
+
+		assert_noop!(
+			User::set_user_status(RuntimeOrigin::signed(1), 1, UserStatus::Active),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(set_status(2, UserStatus::Active), Error::<Test>::NoUserDataFound);
+
+		assert_ok!(set_status(1, UserStatus::Active));
+		System::assert_last_event(
+			Event::UserStatusChanged {
+				registrar: Registrar::get(),
+				account: 1,
+				from: UserStatus::Pending,
+				to: UserStatus::Active,
+			}
+			.into(),
+		);
+		assert_ok!(set_status(1, UserStatus::Suspended));
+		assert_ok!(set_status(1, UserStatus::Active));
+
+		for invalid in [UserStatus::Active, UserStatus::Pending] {
+			assert_noop!(set_status(1, invalid), Error::<Test>::InvalidStatusTransition);
+		}
+
+		assert_ok!(set_status(1, UserStatus::Deceased));
+		for status in [UserStatus::Pending, UserStatus::Active, UserStatus::Suspended] {
+			assert_noop!(set_status(1, status), Error::<Test>::InvalidStatusTransition);
+		}
+	});
+}
+
+#[test]
+fn locked_records_keep_their_evidence() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		assert_ok!(set_status(1, UserStatus::Suspended));
+
+		assert_noop!(
+			User::update_user(
+				RuntimeOrigin::signed(1),
+				UserDetailsUpdate { fname: Some("Kamal".into()), ..Default::default() }
+			),
+			Error::<Test>::RecordLocked
+		);
+		assert_noop!(User::remove_user(RuntimeOrigin::signed(1)), Error::<Test>::RecordLocked);
+
+		// the registrar still can
+		assert_ok!(User::force_update_user(
+			RuntimeOrigin::signed(Registrar::get()),
+			1,
+			UserDetailsUpdate { fname: Some("Kamal".into()), ..Default::default() }
+		));
+		assert_ok!(User::force_remove_user(RuntimeOrigin::signed(Registrar::get()), 1));
+	});
+}
+
+#[test]
+fn linked_accounts_share_the_status() {
+	new_test_ext().execute_with(|| {
+		create(1);
+		link(1, 4);
+		assert!(!<User as UserApi<u64>>::is_active(&4));
+
+		assert_ok!(set_status(1, UserStatus::Active));
+
+		assert!(<User as UserApi<u64>>::is_active(&4));
+		assert_eq!(User::user_status(4), Some(UserStatus::Active));
+	});
+}
+
+#[test]
+fn migrate_v0_to_v1_removes_stale_items() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<User>();
+		create(1);
+
+		let issuance = storage_prefix(b"User", b"TotalIssuance");
+		let balances = storage_prefix(b"User", b"Balances");
+		unhashed::put(&issuance, &500u128);
+		unhashed::put(&[&balances[..], &[1u8; 16]].concat(), &100u128);
+		unhashed::put(&[&balances[..], &[2u8; 16]].concat(), &400u128);
+
+		migrations::v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+		assert!(!unhashed::exists(&issuance));
+		assert!(!unhashed::contains_prefixed_key(&balances));
+		assert!(UserDetailsStorage::<Test>::contains_key(1));
+		assert_eq!(User::on_chain_storage_version(), StorageVersion::new(1));
+
+		// running again is a no-op
+		unhashed::put(&issuance, &1u128);
+		migrations::v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+		assert!(unhashed::exists(&issuance));
+	});
+}
+
+#[test]
+fn migrate_v1_to_v2_estimates_birth_dates() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(1).put::<User>();
+		let old = migrations::v2::OldUserDetails {
+			fname: b"Nimal".to_vec().try_into().unwrap(),
+			lname: b"Perera".to_vec().try_into().unwrap(),
+			address: b"Colombo".to_vec().try_into().unwrap(),
+			age: 30,
+		};
+		unhashed::put(&UserDetailsStorage::<Test>::hashed_key_for(1), &old);
+
+		migrations::v2::MigrateV1ToV2::<Test>::on_runtime_upgrade();
+
+		let details: migrations::v4::OldUserDetails =
+			unhashed::get(&UserDetailsStorage::<Test>::hashed_key_for(1)).unwrap();
+		assert_eq!(details.fname.to_vec(), b"Nimal".to_vec());
+		assert_eq!(details.address.to_vec(), b"Colombo".to_vec());
+		assert_eq!(details.birth_date, Date::new(1996, 10, 17));
+		assert_eq!(details.birth_date.years_until(&User::today()), 30);
+		assert_eq!(User::on_chain_storage_version(), StorageVersion::new(2));
+	});
+}
+
+#[test]
+fn migrate_v2_to_v3_rekeys_known_accounts() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(2).put::<User>();
+		let prefix = storage_prefix(b"User", b"UserDetailsStorage");
+		let old_key = |who: u64| [&prefix[..], &Blake2_128::hash(&who.encode())[..]].concat();
+
+		// account 1 has a balance; account 9 was enrolled without one
+		let details = migrations::v4::OldUserDetails {
+			fname: b"Nimal".to_vec().try_into().unwrap(),
+			lname: b"Perera".to_vec().try_into().unwrap(),
+			address: b"Colombo".to_vec().try_into().unwrap(),
+			birth_date: BIRTH_DATE,
+		};
+		unhashed::put(&old_key(1), &details);
+		unhashed::put(&old_key(9), &details);
+		assert_eq!(migrations::v3::old_layout_entries::<Test>(), 2);
+
+		migrations::v3::MigrateV2ToV3::<Test>::on_runtime_upgrade();
+
+		// the upgrade only schedules the work
+		assert!(migrations::v3::rekey_pending::<Test>());
+		assert_eq!(migrations::v3::old_layout_entries::<Test>(), 2);
+		assert_eq!(User::on_chain_storage_version(), StorageVersion::new(3));
+		// a record waiting for its new key still counts as registered
+		assert_noop!(
+			User::create_user(RuntimeOrigin::signed(1), "f".into(), "l".into(), "a".into(), BIRTH_DATE),
+			Error::<Test>::DuplicateUser
+		);
+
+		// one account per step takes several blocks
+		let mut steps = 0;
+		while migrations::v3::rekey_pending::<Test>() {
+			migrations::v3::step::<Test>(1);
+			steps += 1;
+		}
+		assert!(steps > 1);
+
+		assert_eq!(unhashed::get(&UserDetailsStorage::<Test>::hashed_key_for(1)), Some(details));
+		assert_eq!(UserDetailsStorage::<Test>::count(), 1);
+		assert_eq!(UserDetailsStorage::<Test>::iter_keys().collect::<Vec<_>>(), vec![1]);
+		// the unknown account can not be re-keyed and is removed once every account is visited
+		assert!(!unhashed::exists(&old_key(9)));
+		assert_eq!(migrations::v3::old_layout_entries::<Test>(), 0);
+	});
+}
+
+#[cfg(feature = "try-runtime")]
+#[test]
+fn migrations_v0_to_v5_pass_try_runtime_checks() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<User>();
+		let prefix = storage_prefix(b"User", b"UserDetailsStorage");
+		let old_key = |who: u64| [&prefix[..], &Blake2_128::hash(&who.encode())[..]].concat();
+
+		// account 1 has a balance; account 9 was enrolled without one
+		let old = migrations::v2::OldUserDetails {
+			fname: b"Nimal".to_vec().try_into().unwrap(),
+			lname: b"Perera".to_vec().try_into().unwrap(),
+			address: b"Colombo".to_vec().try_into().unwrap(),
+			age: 30,
+		};
+		unhashed::put(&old_key(1), &old);
+		unhashed::put(&old_key(9), &old);
+
+		assert_ok!(<(
+			migrations::v1::MigrateV0ToV1<Test>,
+			migrations::v2::MigrateV1ToV2<Test>,
+			migrations::v3::MigrateV2ToV3<Test>,
+			migrations::v4::MigrateV3ToV4<Test>,
+			migrations::v5::MigrateV4ToV5<Test>,
+		) as OnRuntimeUpgrade>::try_on_runtime_upgrade(true));
+		assert_eq!(User::on_chain_storage_version(), StorageVersion::new(5));
+
+		while migrations::v3::rekey_pending::<Test>() {
+			migrations::v3::step::<Test>(migrations::v3::ACCOUNTS_PER_STEP);
+		}
+		assert_eq!(UserDetailsStorage::<Test>::get(1).unwrap().status, UserStatus::Active);
+		assert_eq!(UserDetailsStorage::<Test>::iter_keys().collect::<Vec<_>>(), vec![1]);
+		assert_eq!(migrations::v3::entries::<Test>(), 1);
+	});
+}
+
+#[test]
+fn migrate_v3_to_v4_marks_users_active() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(3).put::<User>();
+		let old = migrations::v4::OldUserDetails {
+			fname: b"Nimal".to_vec().try_into().unwrap(),
+			lname: b"Perera".to_vec().try_into().unwrap(),
+			address: b"Colombo".to_vec().try_into().unwrap(),
+			birth_date: BIRTH_DATE,
+		};
+		unhashed::put(&UserDetailsStorage::<Test>::hashed_key_for(1), &old);
+
+		migrations::v4::MigrateV3ToV4::<Test>::on_runtime_upgrade();
+
+		let details = UserDetailsStorage::<Test>::get(1).unwrap();
+		assert_eq!(details.fname.to_vec(), b"Nimal".to_vec());
+		assert_eq!(details.birth_date, BIRTH_DATE);
+		assert_eq!(details.status, UserStatus::Active);
+		assert!(<User as UserApi<u64>>::is_active(&1));
+		assert_eq!(User::on_chain_storage_version(), StorageVersion::new(4));
+	});
+}
+
+#[test]
+fn migrate_v4_to_v5_rekeys_usernames() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(4).put::<User>();
+		let prefix = storage_prefix(b"User", b"UsernameOf");
+		let old_key = |who: u64| [&prefix[..], &Blake2_128::hash(&who.encode())[..]].concat();
+		UsernameOwner::<Test>::insert(username("nimal"), 1);
+		unhashed::put(&old_key(1), &username("nimal"));
+		UsernameOwner::<Test>::insert(username("kamal"), 2);
+		unhashed::put(&old_key(2), &username("kamal"));
+
+		migrations::v5::MigrateV4ToV5::<Test>::on_runtime_upgrade();
+
+		assert_eq!(UsernameOf::<Test>::get(1), Some(username("nimal")));
+		assert_eq!(UsernameOf::<Test>::get(2), Some(username("kamal")));
+		assert!(!unhashed::exists(&old_key(1)));
+		assert!(!unhashed::exists(&old_key(2)));
+		let mut keys = UsernameOf::<Test>::iter_keys().collect::<Vec<_>>();
+		keys.sort();
+		assert_eq!(keys, vec![1, 2]);
+		assert_eq!(User::on_chain_storage_version(), StorageVersion::new(5));
+	});
+}
+
+#[test]
+fn truncate_to_bounds_fits_long_records() {
+	new_test_ext().execute_with(|| {
+		create(2);
+		let fitting = UserDetailsStorage::<Test>::get(2).unwrap();
+		// 22 Sinhala letters of 3 bytes each, over the mock's 64-byte bound
+		let long = migrations::bounds::UnboundedUserDetails {
+			fname: "අ".repeat(22).into_bytes(),
+			lname: b"Perera".to_vec(),
+			address: s(130, 'a').into_bytes(),
+			birth_date: BIRTH_DATE,
+			status: UserStatus::Active,
+		};
+		unhashed::put(&UserDetailsStorage::<Test>::hashed_key_for(1), &long);
+		assert_eq!(UserDetailsStorage::<Test>::get(1), None);
+
+		migrations::bounds::TruncateToBounds::<Test>::on_runtime_upgrade();
+
+		let details = UserDetailsStorage::<Test>::get(1).unwrap();
+		// cut at a character boundary
+		assert_eq!(details.fname.to_vec(), "අ".repeat(21).into_bytes());
+		assert_eq!(details.lname.to_vec(), b"Perera".to_vec());
+		assert_eq!(details.address.len(), 128);
+		assert_eq!(UserDetailsStorage::<Test>::get(2), Some(fitting));
+	});
+}
@@ -0,0 +1,66 @@
+// pallets/user/src/text.rs
+//! Validation and normalisation of user-supplied text, shared by the user and profile
+//! pallets.
+//!
+//! Every field is decoded as UTF-8, trimmed of surrounding whitespace and refused if it holds
+//! control characters; on top of that each field accepts one [`CharClass`].
+
+use frame_support::{
+    pallet_prelude::{Get, RuntimeDebug},
+    BoundedVec,
+};
+
+/// Sinhala al-lakuna and Tamil pulli, which are not `Alphabetic` but appear in names.
+const VIRAMAS: [char; 2] = ['\u{0DCA}', '\u{0BCD}'];
+
+/// Zero-width non-joiner and joiner, used to pick conjunct forms in Sinhala.
+const JOINERS: [char; 2] = ['\u{200C}', '\u{200D}'];
+
+/// Characters a field accepts.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum CharClass {
+    /// Letters of any script with their combining signs, spaces and `-`, `'`, `.`.
+    Name,
+    /// Any character that is not a control character.
+    Text,
+    /// Printable ASCII without spaces, e.g. file paths.
+    Path,
+}
+
+impl CharClass {
+    pub fn allows(self, c: char) -> bool {
+        match self {
+            Self::Name => {
+                c.is_alphabetic()
+                    || matches!(c, ' ' | '-' | '\'' | '.')
+                    || VIRAMAS.contains(&c)
+                    || JOINERS.contains(&c)
+            },
+            Self::Text => true,
+            Self::Path => c.is_ascii_graphic(),
+        }
+    }
+}
+
+/// Why a field was refused.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum TextError {
+    InvalidUtf8,
+    ControlCharacter,
+    /// A character outside the field's `CharClass`.
+    InvalidCharacter,
+    /// Longer than the field's bound after trimming.
+    TooLong,
+}
+
+/// Validate `raw` for a field of class `class` and return it trimmed.
+pub fn normalize<S: Get<u32>>(raw: &[u8], class: CharClass) -> Result<BoundedVec<u8, S>, TextError> {
+    let text = core::str::from_utf8(raw).map_err(|_| TextError::InvalidUtf8)?.trim();
+    if text.chars().any(char::is_control) {
+        return Err(TextError::ControlCharacter);
+    }
+    if !text.chars().all(|c| class.allows(c)) {
+        return Err(TextError::InvalidCharacter);
+    }
+    text.as_bytes().to_vec().try_into().map_err(|_| TextError::TooLong)
+}
@@ -1,52 +1,239 @@
-// pallets/foo/src/weights.rs   ← will be RE-GENERATED later
-#![allow(dead_code)]
-use frame_support::weights::Weight;
-use frame_support::{traits::Get, weights::{constants::RocksDbWeight}};
+//! Weights for `user`.
+//!
+//! NOT BENCHMARK RESULTS. Until the runtime is benchmarked on reference hardware these are
+//! deliberately conservative formulas: every call is charged the storage reads and writes it
+//! performs in its worst case at `DbWeight`, a flat [`BASE`] of execution time, [`PER_BYTE`]
+//! per byte of string input and [`PER_ITEM`] per linked account, guardian or registrar it walks.
+//! Proof size is not charged; the solochain does not bound it.
+//!
+//! Replace this file with the output of
+//! `solochain-template-node benchmark pallet --chain dev --pallet user --extrinsic '*'
+//! --steps 50 --repeat 20 --wasm-execution compiled --output pallets/user/src/weights.rs`
+//! once that can be run.
+
+#![allow(missing_docs)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, RuntimeDbWeight, Weight},
+};
 use core::marker::PhantomData;
 
-/// Declare only the calls you want benchmarked
+/// Execution time charged to every call, in picoseconds.
+pub const BASE: u64 = 100_000_000;
+/// Execution time charged per byte of string input, in picoseconds.
+pub const PER_BYTE: u64 = 20_000;
+/// Execution time charged per linked account, guardian or registrar walked, in picoseconds.
+pub const PER_ITEM: u64 = 5_000_000;
+
+/// `BASE` plus `bytes` times `PER_BYTE`, `items` times `PER_ITEM`, and `reads` and `writes`
+/// at `db`.
+fn formula(db: RuntimeDbWeight, bytes: u32, items: u32, reads: u64, writes: u64) -> Weight {
+    let ref_time = BASE
+        .saturating_add(PER_BYTE.saturating_mul(bytes.into()))
+        .saturating_add(PER_ITEM.saturating_mul(items.into()));
+    Weight::from_parts(ref_time, 0).saturating_add(db.reads_writes(reads, writes))
+}
+
+/// Weight functions needed for `user`. `f`, `l` and `a` are the byte lengths of the first
+/// name, last name and address, `g` the number of guardians or of recoveries in progress for
+/// one account, `l` alone the number of linked accounts and `n` the number of registrars.
 pub trait WeightInfo {
-    fn create_user()  -> Weight;
-    fn get_my_user_details()  -> Weight;
-    fn update_user() -> Weight;
-    fn remove_user() -> Weight;
+    /// Reads and writes `Balances::Holds`, `System::Account`, `UserDetailsStorage` and its
+    /// counter.
+    fn create_user(f: u32, l: u32, a: u32) -> Weight;
+    /// Reads and writes `UserDetailsStorage`, `Balances::Holds` and `System::Account`.
+    fn update_user(f: u32, l: u32, a: u32) -> Weight;
+    /// Reads `UserDetailsStorage`, its counter, `Balances::Holds`, `System::Account`,
+    /// `UsernameOf` and `LinkedAccounts`; writes those, `UsernameOwner`, `RecoveryConfigs`, one
+    /// `PrimaryOf` per linked account and clears `g` recoveries.
+    fn remove_user(l: u32, g: u32) -> Weight;
+    /// Reads and writes `UserDetailsStorage` and its counter.
+    fn force_create_user(f: u32, l: u32, a: u32) -> Weight;
+    /// Reads and writes `UserDetailsStorage`.
+    fn force_update_user(f: u32, l: u32, a: u32) -> Weight;
+    /// As `remove_user`.
+    fn force_remove_user(l: u32, g: u32) -> Weight;
+    /// Reads `UserDetailsStorage`, `UsernameOf`, `ReservedUsernames` and `UsernameOwner`;
+    /// writes `UsernameOf` and `UsernameOwner`.
+    fn claim_username() -> Weight;
+    /// Reads `UsernameOf`; writes it and `UsernameOwner`.
+    fn release_username() -> Weight;
+    /// Reads `UserDetailsStorage` and both `UsernameOf` entries; writes those and
+    /// `UsernameOwner`.
+    fn transfer_username() -> Weight;
+    /// Writes `ReservedUsernames`.
+    fn reserve_username() -> Weight;
+    /// Reads and writes `ReservedUsernames`.
+    fn unreserve_username() -> Weight;
+    /// Reads `UserDetailsStorage` and `ActiveRecoveries`; writes `RecoveryConfigs`.
+    fn set_recovery_config(g: u32) -> Weight;
+    /// Reads `ActiveRecoveries` and `RecoveryConfigs`; writes `RecoveryConfigs`.
+    fn remove_recovery_config() -> Weight;
+    /// Reads `RecoveryConfigs`, the recovery vouched for, the new account's
+    /// `UserDetailsStorage` and `PrimaryOf` and the `g` recoveries of the lost account; writes
+    /// the recovery vouched for and the one the guardian backed before.
+    fn vouch_recovery(g: u32) -> Weight;
+    /// Clears `g` recoveries.
+    fn cancel_recovery(g: u32) -> Weight;
+    /// Moves every record of the lost account to the new one: reads both accounts'
+    /// `UserDetailsStorage`, `Balances::Holds` and `System::Account`, the recovery, the
+    /// counter, `UsernameOf` and `LinkedAccounts`; writes those, `UsernameOwner`,
+    /// `RecoveryConfigs` and one `PrimaryOf` per linked account, and clears `g` recoveries.
+    fn claim_recovery(l: u32, g: u32) -> Weight;
+    /// Reads both accounts' `UserDetailsStorage` and `PrimaryOf`; writes `PendingLinks`.
+    fn request_link() -> Weight;
+    /// Reads `PendingLinks`, both accounts' `UserDetailsStorage`, `PrimaryOf` and
+    /// `LinkedAccounts`; writes `PendingLinks`, `PrimaryOf` and `LinkedAccounts`.
+    fn accept_link() -> Weight;
+    /// Reads both `PrimaryOf` entries and `LinkedAccounts`; writes `PrimaryOf` and
+    /// `LinkedAccounts`.
+    fn unlink() -> Weight;
+    /// Reads and writes `UserDetailsStorage`.
+    fn set_user_status() -> Weight;
+    /// Reads and writes `Registrars`, searching `n` accounts.
+    fn add_registrar(n: u32) -> Weight;
+    /// Reads and writes `Registrars`, searching `n` accounts.
+    fn remove_registrar(n: u32) -> Weight;
 }
 
-/// Weights for pallet_template using the Substrate node and recommended hardware.
+/// The formulas, with storage accesses at the runtime's `DbWeight`.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
-    fn create_user()  -> Weight {
-        todo!()
+    fn create_user(f: u32, l: u32, a: u32) -> Weight {
+        formula(T::DbWeight::get(), f + l + a, 0, 4, 4)
     }
-
-    fn get_my_user_details()  -> Weight {
-        todo!()
+    fn update_user(f: u32, l: u32, a: u32) -> Weight {
+        formula(T::DbWeight::get(), f + l + a, 0, 3, 3)
     }
-
-    fn update_user() -> Weight {
-        todo!()
+    fn remove_user(l: u32, g: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, l, 6 + u64::from(g), 8 + u64::from(l) + u64::from(g))
     }
-
-    fn remove_user() -> Weight {
-        todo!()
+    fn force_create_user(f: u32, l: u32, a: u32) -> Weight {
+        formula(T::DbWeight::get(), f + l + a, 0, 2, 2)
+    }
+    fn force_update_user(f: u32, l: u32, a: u32) -> Weight {
+        formula(T::DbWeight::get(), f + l + a, 0, 1, 1)
+    }
+    fn force_remove_user(l: u32, g: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, l, 6 + u64::from(g), 8 + u64::from(l) + u64::from(g))
+    }
+    fn claim_username() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 4, 2)
+    }
+    fn release_username() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1, 2)
+    }
+    fn transfer_username() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 3, 3)
+    }
+    fn reserve_username() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 0, 1)
+    }
+    fn unreserve_username() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1, 1)
+    }
+    fn set_recovery_config(g: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, g, 2, 1)
+    }
+    fn remove_recovery_config() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 2, 1)
+    }
+    fn vouch_recovery(g: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, g, 4 + u64::from(g), 2)
+    }
+    fn cancel_recovery(g: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, 0, u64::from(g), u64::from(g))
+    }
+    fn claim_recovery(l: u32, g: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, l, 9 + u64::from(g), 12 + u64::from(l) + u64::from(g))
+    }
+    fn request_link() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 3, 1)
+    }
+    fn accept_link() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 5, 3)
+    }
+    fn unlink() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 3, 2)
+    }
+    fn set_user_status() -> Weight {
+        formula(T::DbWeight::get(), 0, 0, 1, 1)
+    }
+    fn add_registrar(n: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, n, 1, 1)
+    }
+    fn remove_registrar(n: u32) -> Weight {
+        formula(T::DbWeight::get(), 0, n, 1, 1)
     }
 }
 
-// For backwards compatibility and tests
+// For backwards compatibility and tests.
 impl WeightInfo for () {
-    fn create_user()  -> Weight {
-        todo!()
+    fn create_user(f: u32, l: u32, a: u32) -> Weight {
+        formula(RocksDbWeight::get(), f + l + a, 0, 4, 4)
     }
-
-    fn get_my_user_details()  -> Weight {
-        todo!()
+    fn update_user(f: u32, l: u32, a: u32) -> Weight {
+        formula(RocksDbWeight::get(), f + l + a, 0, 3, 3)
     }
-
-    fn update_user() -> Weight {
-        todo!()
+    fn remove_user(l: u32, g: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, l, 6 + u64::from(g), 8 + u64::from(l) + u64::from(g))
     }
-
-    fn remove_user() -> Weight {
-        todo!()
+    fn force_create_user(f: u32, l: u32, a: u32) -> Weight {
+        formula(RocksDbWeight::get(), f + l + a, 0, 2, 2)
+    }
+    fn force_update_user(f: u32, l: u32, a: u32) -> Weight {
+        formula(RocksDbWeight::get(), f + l + a, 0, 1, 1)
+    }
+    fn force_remove_user(l: u32, g: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, l, 6 + u64::from(g), 8 + u64::from(l) + u64::from(g))
+    }
+    fn claim_username() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 4, 2)
+    }
+    fn release_username() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1, 2)
+    }
+    fn transfer_username() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 3, 3)
+    }
+    fn reserve_username() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 0, 1)
+    }
+    fn unreserve_username() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1, 1)
+    }
+    fn set_recovery_config(g: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, g, 2, 1)
+    }
+    fn remove_recovery_config() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 2, 1)
+    }
+    fn vouch_recovery(g: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, g, 4 + u64::from(g), 2)
+    }
+    fn cancel_recovery(g: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, u64::from(g), u64::from(g))
+    }
+    fn claim_recovery(l: u32, g: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, l, 9 + u64::from(g), 12 + u64::from(l) + u64::from(g))
+    }
+    fn request_link() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 3, 1)
+    }
+    fn accept_link() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 5, 3)
+    }
+    fn unlink() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 3, 2)
+    }
+    fn set_user_status() -> Weight {
+        formula(RocksDbWeight::get(), 0, 0, 1, 1)
+    }
+    fn add_registrar(n: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, n, 1, 1)
+    }
+    fn remove_registrar(n: u32) -> Weight {
+        formula(RocksDbWeight::get(), 0, n, 1, 1)
     }
 }
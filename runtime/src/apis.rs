@@ -43,7 +43,7 @@ use scale_info::prelude::string::String;
 
 // Local module imports
 use super::{
-	AccountId, Aura, Balance, Block, Executive, Grandpa, InherentDataExt, Nonce, Runtime,
+	AccountId, Aura, Balance, Block, BlockNumber, Executive, Grandpa, InherentDataExt, Nonce, Runtime,
 	RuntimeCall, RuntimeGenesisConfig, SessionKeys, System, TransactionPayment, VERSION,
 };
 
@@ -305,60 +305,81 @@ impl_runtime_apis! {
 
 	impl user::runtime_api::UserDetailsApi<Block> for Runtime {
 		fn get_user_details(account: AccountId) -> Option<(String, String, String, u32)> {
-			// Convert to internal AccountId type
-			let acc: AccountId = account.into();
+			// Convert to internal AccountId type, resolving linked accounts
+			let acc: AccountId = user::Pallet::<Runtime>::primary_account(&account);
 
-			// Use storage getter and decode BoundedVecs to Strings
+			// Use storage getter and decode BoundedVecs to Strings. Records stored before text
+			// validation may hold invalid UTF-8, which shows up as replacement characters.
 			user::UserDetailsStorage::<Runtime>::get(acc).map(|details| {
 				(
-					String::from_utf8(details.fname.to_vec()).unwrap_or_default(),
-					String::from_utf8(details.lname.to_vec()).unwrap_or_default(),
-					String::from_utf8(details.address.to_vec()).unwrap_or_default(),
-					details.age,
+					String::from_utf8_lossy(&details.fname).into_owned(),
+					String::from_utf8_lossy(&details.lname).into_owned(),
+					String::from_utf8_lossy(&details.address).into_owned(),
+					user::Pallet::<Runtime>::age(&details),
 				)
 			})
 		}
+
+		fn age_of(account: AccountId) -> Option<u32> {
+			<user::Pallet<Runtime> as user::UserApi<AccountId>>::age_of(&account)
+		}
+
+		fn account_by_username(username: Vec<u8>) -> Option<AccountId> {
+			<user::Pallet<Runtime> as user::UserApi<AccountId>>::account_by_username(&username)
+		}
+
+		fn list_users(start_after: Option<AccountId>, limit: u32) -> Vec<AccountId> {
+			user::Pallet::<Runtime>::users_page(start_after, limit)
+		}
+
+		fn count_users() -> u32 {
+			user::UserDetailsStorage::<Runtime>::count()
+		}
+
+		fn primary_account(account: AccountId) -> AccountId {
+			user::Pallet::<Runtime>::primary_account(&account)
+		}
 	}
 
 	impl profile::runtime_api::ProfileApi<Block> for Runtime {
-		fn get_user_profile(acc: AccountId)
-			-> Option<(
-				AccountId, 
-				String, 
-				String, 
-				String,
-				profile::Gender, 
-				profile::MaritalStatus,
-				String, 
-				profile::BloodType,
-				String, 
-				String, 
-				String,
-				profile::Province, 
-				profile::District,
-				String, String,
-				u32, String
-			)>
+		fn get_user_profile(acc: AccountId, auth: Option<profile::runtime_api::ApiReadAuth>)
+			-> Option<profile::ProfileView<AccountId>>
 		{
-			profile::Profiles::<Runtime>::get(&acc).map(|p| (
-				acc.clone(),
-				String::from_utf8(p.f_name.to_vec()).unwrap_or_default(),
-				String::from_utf8(p.m_name.to_vec()).unwrap_or_default(),
-				String::from_utf8(p.l_name.to_vec()).unwrap_or_default(),
-				p.gender,
-				p.marital_status,
-				String::from_utf8(p.pf_pic_path.to_vec()).unwrap_or_default(),
-				p.blood_group,
-				String::from_utf8(p.nationality.to_vec()).unwrap_or_default(),
-				String::from_utf8(p.religion.to_vec()).unwrap_or_default(),
-				String::from_utf8(p.lit_lang.to_vec()).unwrap_or_default(),
-				p.province,
-				p.district,
-				String::from_utf8(p.city.to_vec()).unwrap_or_default(),
-				String::from_utf8(p.division.to_vec()).unwrap_or_default(),
-				p.postal_code,
-				String::from_utf8(p.birth_date.to_vec()).unwrap_or_default(),
-			))
+			let requester = auth.as_ref().and_then(profile::Pallet::<Runtime>::authenticated);
+			profile::Pallet::<Runtime>::profile_view(&acc, requester.as_ref())
+		}
+
+		fn get_shared_profile(owner: AccountId, auth: profile::runtime_api::ApiReadAuth)
+			-> Option<profile::ProfileView<AccountId>>
+		{
+			let requester = profile::Pallet::<Runtime>::authenticated(&auth)?;
+			profile::Pallet::<Runtime>::shared_profile(&owner, &requester)
+		}
+
+		fn get_encrypted_fields(owner: AccountId, reader: AccountId) -> Option<profile::EncryptedProfile> {
+			profile::Pallet::<Runtime>::encrypted_fields(&owner, &reader)
+		}
+
+		fn get_profile_history(
+			acc: AccountId,
+			auth: Option<profile::runtime_api::ApiReadAuth>,
+			before: Option<u32>,
+			limit: u32,
+		) -> Vec<profile::RevisionView<AccountId, BlockNumber>> {
+			let requester = auth.as_ref().and_then(profile::Pallet::<Runtime>::authenticated);
+			profile::Pallet::<Runtime>::profile_history(&acc, requester.as_ref(), before, limit)
+		}
+
+		fn get_judgements(acc: AccountId) -> profile::Verification<AccountId> {
+			profile::Pallet::<Runtime>::verification(&acc)
+		}
+
+		fn list_profiles(start_after: Option<AccountId>, limit: u32) -> Vec<AccountId> {
+			profile::Pallet::<Runtime>::profiles_page(start_after, limit)
+		}
+
+		fn count_profiles() -> u32 {
+			profile::Profiles::<Runtime>::count()
 		}
 	}
 }
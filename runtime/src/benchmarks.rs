@@ -31,5 +31,6 @@ frame_benchmarking::define_benchmarks!(
 	[pallet_timestamp, Timestamp]
 	[pallet_sudo, Sudo]
 	[pallet_template, Template]
-	// [user, UserTemplate]
+	[user, User]
+	[profile, Profile]
 );
@@ -26,23 +26,31 @@
 // Substrate and Polkadot dependencies
 use frame_support::{
 	derive_impl, parameter_types,
-	traits::{ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, VariantCountOf},
+	traits::{ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, EitherOf, VariantCountOf},
+	PalletId,
 	weights::{
 		constants::{RocksDbWeight, WEIGHT_REF_TIME_PER_SECOND},
 		IdentityFee, Weight,
 	},
 };
-use frame_system::limits::{BlockLength, BlockWeights};
+use frame_system::{
+	limits::{BlockLength, BlockWeights},
+	EnsureRoot, EnsureRootWithSuccess, EnsureSignedBy,
+};
 use pallet_transaction_payment::{ConstFeeMultiplier, FungibleAdapter, Multiplier};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_runtime::{traits::One, Perbill};
+use sp_runtime::{
+	traits::{AccountIdConversion, One},
+	Perbill,
+};
 use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-	AccountId, Aura, Balance, Balances, Block, BlockNumber, Hash, Nonce, PalletInfo, Runtime,
-	RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask,
-	System, EXISTENTIAL_DEPOSIT, SLOT_DURATION, VERSION,
+	AccountId, Aura, Balance, Balances, Block, BlockNumber, Hash, Nonce, PalletInfo, Profile,
+	Runtime, RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin,
+	RuntimeTask, Signature, System, Timestamp, User, DAYS, EXISTENTIAL_DEPOSIT, MICRO_UNIT,
+	MILLI_UNIT, MINUTES, SLOT_DURATION, VERSION,
 };
 
 const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
@@ -179,12 +187,76 @@ impl custom_pallet_crud::Config for Runtime {
 	type CounterMaxValue = MaxCounterValue;
 }
 
+parameter_types! {
+	/// Flat deposit held for every registered user record.
+	pub const UserDepositBase: Balance = 10 * MILLI_UNIT;
+	/// Deposit held per byte of encoded user details.
+	pub const UserDepositPerByte: Balance = 100 * MICRO_UNIT;
+	/// Account recorded as the registrar when user records are administered through root.
+	pub UserRegistrarAccount: AccountId = PalletId(*b"usr/regs").into_account_truncating();
+	/// Largest number of guardians a citizen can name for account recovery.
+	pub const UserMaxGuardians: u32 = 10;
+	/// Time the old owner has to cancel an approved account recovery.
+	pub const UserRecoveryDelay: BlockNumber = 3 * DAYS;
+	/// Wallets a citizen can link to their record besides the one holding it.
+	pub const UserMaxLinkedAccounts: u32 = 5;
+	/// Sinhala and Tamil names take three bytes per character in UTF-8.
+	pub const UserMaxNameLen: u32 = 128;
+	pub const UserMaxAddressLen: u32 = 256;
+	/// Registrar accounts root can appoint.
+	pub const UserMaxRegistrars: u32 = 32;
+}
+
 impl user::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	//type WeightInfo = user::weights::SubstrateWeight<Runtime>;
+	type WeightInfo = user::weights::SubstrateWeight<Runtime>;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	/// Root, recorded as `UserRegistrarAccount`, or a signed account in `User::Registrars`.
+	type RegistrarOrigin =
+		EitherOf<EnsureRootWithSuccess<AccountId, UserRegistrarAccount>, EnsureSignedBy<User, AccountId>>;
+	type UnixTime = Timestamp;
+	type OnUserCreated = Profile;
+	type OnUserRemoved = Profile;
+	type OnUserMoved = Profile;
+	type MaxGuardians = UserMaxGuardians;
+	type RecoveryDelay = UserRecoveryDelay;
+	type MaxLinkedAccounts = UserMaxLinkedAccounts;
+	type MaxNameLen = UserMaxNameLen;
+	type MaxAddressLen = UserMaxAddressLen;
+	type DepositBase = UserDepositBase;
+	type DepositPerByte = UserDepositPerByte;
+	type MaxRegistrars = UserMaxRegistrars;
+}
+
+parameter_types! {
+	pub const ProfileMaxTextLen: u32 = 128;
+	pub const ProfileMaxPictureSize: u32 = 2 * 1024 * 1024;
+	pub const ProfileMaxGrants: u32 = 16;
+	pub const ProfileMaxGrantDuration: BlockNumber = 365 * DAYS;
+	pub const ProfileMaxCiphertextLen: u32 = 256;
+	pub const ProfileMaxRevisions: u32 = 32;
+	pub const ProfileMaxVerifiers: u32 = 32;
+	pub const ProfileMaxReadAuthAge: BlockNumber = 10 * MINUTES;
 }
 
 impl profile::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = profile::weights::SubstrateWeight<Runtime>;
 	type UserPallet   = user::Pallet<Runtime>;
+	type UnixTime = Timestamp;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type ArchiveRemovedProfiles = ConstBool<false>;
+	type MaxNameLen = UserMaxNameLen;
+	type MaxTextLen = ProfileMaxTextLen;
+	type MaxPictureSize = ProfileMaxPictureSize;
+	type MaxGrants = ProfileMaxGrants;
+	type MaxGrantDuration = ProfileMaxGrantDuration;
+	type MaxCiphertextLen = ProfileMaxCiphertextLen;
+	type MaxRevisions = ProfileMaxRevisions;
+	type MaxVerifiers = ProfileMaxVerifiers;
+	type ReadSignature = Signature;
+	type MaxReadAuthAge = ProfileMaxReadAuthAge;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = user::Pallet<Runtime>;
 }
@@ -7,6 +7,8 @@ pub mod apis;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarks;
 pub mod configs;
+#[cfg(test)]
+mod tests;
 
 extern crate alloc;
 use alloc::vec::Vec;
@@ -69,10 +71,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 120,
 	impl_version: 1,
 	apis: apis::RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 3,
 	system_version: 1,
 };
 
@@ -171,7 +173,17 @@ pub type SignedPayload = generic::SignedPayload<RuntimeCall, TxExtension>;
 ///
 /// This can be a tuple of types, each implementing `OnRuntimeUpgrade`.
 #[allow(unused_parens)]
-type Migrations = ();
+type Migrations = (
+	user::migrations::v1::MigrateV0ToV1<Runtime>,
+	user::migrations::v2::MigrateV1ToV2<Runtime>,
+	user::migrations::v3::MigrateV2ToV3<Runtime>,
+	user::migrations::v4::MigrateV3ToV4<Runtime>,
+	user::migrations::v5::MigrateV4ToV5<Runtime>,
+	profile::migrations::v1::MigrateV0ToV1<Runtime>,
+	profile::migrations::v2::MigrateV1ToV2<Runtime>,
+	profile::migrations::v3::MigrateV2ToV3<Runtime>,
+	profile::migrations::v4::MigrateV3ToV4<Runtime>,
+);
 
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
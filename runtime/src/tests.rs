@@ -0,0 +1,74 @@
+use crate::{AccountId, BuildStorage, Profile, Runtime, RuntimeOrigin, User};
+use frame_support::assert_ok;
+use profile::{BloodType, District, Gender, MaritalStatus, ProfileField, Province, Visibility};
+use sp_keyring::Sr25519Keyring;
+
+/// 2026-10-17 00:00 UTC, in milliseconds.
+const NOW: u64 = 1_792_195_200_000;
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let storage = frame_system::GenesisConfig::<Runtime>::default().build_storage().unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| {
+		frame_system::Pallet::<Runtime>::set_block_number(1);
+		pallet_timestamp::Now::<Runtime>::put(NOW);
+	});
+	ext
+}
+
+fn account(who: Sr25519Keyring) -> AccountId {
+	who.to_account_id()
+}
+
+#[test]
+fn registrars_read_registrars_only_fields() {
+	new_test_ext().execute_with(|| {
+		let (owner, registrar, stranger) =
+			(account(Sr25519Keyring::Alice), account(Sr25519Keyring::Bob), account(Sr25519Keyring::Charlie));
+
+		assert_ok!(User::add_registrar(RuntimeOrigin::root(), registrar.clone()));
+		// the registrar passes `RegistrarOrigin` under its own account
+		assert_ok!(User::force_create_user(
+			RuntimeOrigin::signed(registrar.clone()),
+			owner.clone(),
+			"Nimal".into(),
+			"Perera".into(),
+			"Colombo".into(),
+			user::Date::new(1994, 5, 17),
+		));
+		assert_ok!(Profile::create_profile(
+			RuntimeOrigin::signed(owner.clone()),
+			"Nimal".into(),
+			"".into(),
+			"Perera".into(),
+			MaritalStatus::Single,
+			None,
+			Gender::Male,
+			BloodType::OPos,
+			"Sri Lankan".into(),
+			"Buddhism".into(),
+			"Sinhala".into(),
+			Province::Western,
+			District::Colombo,
+			"Colombo".into(),
+			"Colombo 03".into(),
+			300,
+			user::Date::new(1994, 5, 17),
+		));
+		assert_ok!(Profile::set_visibility(
+			RuntimeOrigin::signed(owner.clone()),
+			vec![(ProfileField::Religion, Visibility::RegistrarsOnly)].try_into().unwrap(),
+		));
+
+		let religion = |requester: &AccountId| {
+			Profile::profile_view(&owner, Some(requester)).and_then(|view| view.religion)
+		};
+		assert_eq!(religion(&registrar), Some("Buddhism".into()));
+		assert_eq!(religion(&owner), Some("Buddhism".into()));
+		assert_eq!(religion(&stranger), None);
+
+		// a removed registrar reads like anyone else
+		assert_ok!(User::remove_registrar(RuntimeOrigin::root(), registrar.clone()));
+		assert_eq!(religion(&registrar), None);
+	});
+}